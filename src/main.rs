@@ -1,7 +1,118 @@
 #[macro_use]
 extern crate linked_list_macro;
 mod terminal_snake_game;
+use terminal_snake_game::cli::{self, Command};
+use terminal_snake_game::TerminalSnakeGame;
 
 fn main() {
-    terminal_snake_game::TerminalSnakeGame::new().main();
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(Command::Play(options)) => *options,
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(error) => exit_with_error(&error),
+    };
+    if let Err(error) = options.check_bot() {
+        exit_with_error(&error);
+    }
+
+    if options.headless && options.measure_rendering {
+        let renderer = match options.get_renderer() {
+            Ok(renderer) => renderer,
+            Err(error) => exit_with_error(&error),
+        };
+        match options.into_runner() {
+            Ok((runner, _)) => print!("{}", runner.measure_rendering(renderer)),
+            Err(error) => exit_with_error(&error),
+        }
+        return;
+    }
+
+    if options.headless {
+        match options.into_runner() {
+            Ok((runner, format)) => print!("{}", runner.run().format(format)),
+            Err(error) => exit_with_error(&error),
+        }
+        return;
+    }
+
+    if options.serve_telnet.is_some() {
+        match options.into_telnet_server() {
+            Ok(mut server) => {
+                if let Ok(address) = server.local_addr() {
+                    println!("Serving telnet games on {}", address);
+                }
+                server.run();
+            }
+            Err(error) => exit_with_error(&error),
+        }
+        return;
+    }
+
+    if options.host.is_some() && options.race {
+        match options.into_coordinator() {
+            Ok(mut coordinator) => {
+                if let Ok(address) = coordinator.local_addr() {
+                    println!("Hosting the race on {}", address);
+                }
+                coordinator.run();
+            }
+            Err(error) => exit_with_error(&error),
+        }
+        return;
+    }
+
+    if options.host.is_some() {
+        match options.into_server() {
+            Ok(mut server) => {
+                if let Ok(address) = server.local_addr() {
+                    println!("Hosting the match on {}", address);
+                }
+                server.run();
+            }
+            Err(error) => exit_with_error(&error),
+        }
+        return;
+    }
+
+    if let Some(address) = &options.join {
+        if let Err(error) = TerminalSnakeGame::join(address, &options) {
+            eprintln!("rust-snake: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(address) = &options.watch {
+        if let Err(error) = TerminalSnakeGame::watch(address, &options) {
+            eprintln!("rust-snake: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = &options.replay {
+        match TerminalSnakeGame::play_back(path, &options) {
+            Ok(score) => println!(
+                "Replay verified: {} points in {} ticks",
+                score.points, score.ticks
+            ),
+            Err(error) => {
+                eprintln!("rust-snake: {}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    match TerminalSnakeGame::new(options) {
+        Ok(mut game) => game.main(),
+        Err(error) => exit_with_error(&error),
+    }
+}
+
+fn exit_with_error(error: &dyn std::error::Error) -> ! {
+    eprintln!("rust-snake: {}\n\n{}", error, cli::USAGE);
+    std::process::exit(2);
 }
@@ -0,0 +1,459 @@
+use super::*;
+use crate::terminal_snake_game::snake_game::game::vec2::Vec2;
+
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+fn options(line: &str) -> Options {
+    match parse(args(line)) {
+        Ok(Command::Play(options)) => *options,
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn it_can_parse_options() {
+    let options = options("--width 30 --height 20 --speed hard --accelerate --ascii");
+
+    assert_eq!(
+        options,
+        Options {
+            width: Some(30),
+            height: Some(20),
+            difficulty: Some(Difficulty::Hard),
+            accelerate: true,
+            ascii: true,
+            ..Options::default()
+        }
+    );
+}
+
+#[test]
+fn it_can_ask_for_help() {
+    assert_eq!(parse(args("--width 30 --help")), Ok(Command::Help));
+}
+
+#[test]
+fn it_reports_bad_arguments() {
+    assert_eq!(
+        parse(args("--size 3")),
+        Err(CliError::UnknownArgument("--size".to_string()))
+    );
+    assert_eq!(
+        parse(args("--width")),
+        Err(CliError::MissingValue("--width"))
+    );
+    assert_eq!(
+        parse(args("--height -3")),
+        Err(CliError::InvalidValue {
+            flag: "--height",
+            value: "-3".to_string()
+        })
+    );
+    assert_eq!(
+        parse(args("--speed turbo")),
+        Err(CliError::InvalidValue {
+            flag: "--speed",
+            value: "turbo".to_string()
+        })
+    );
+}
+
+#[test]
+fn it_fills_the_terminal_by_default() {
+    let config = options("--seed 3").into_config((80, 24)).unwrap();
+
+    assert_eq!(
+        config,
+        Config {
+            seed: 3,
+            ..Config::new(80, 24 - HUD_HEIGHT)
+        }
+    );
+}
+
+#[test]
+fn it_uses_the_given_seed() {
+    let one = options("--seed 42").into_config((80, 24)).unwrap();
+    let two = options("--seed 42").into_config((80, 24)).unwrap();
+
+    assert_eq!(one.seed, 42);
+    assert_eq!(one, two);
+}
+
+#[test]
+fn it_refuses_boards_larger_than_the_terminal() {
+    assert_eq!(
+        options("--width 81").into_config((80, 24)),
+        Err(CliError::BoardTooLarge {
+            width: 81,
+            height: 23,
+            terminal_size: (80, 24)
+        })
+    );
+}
+
+#[test]
+fn it_refuses_boards_smaller_than_the_snake() {
+    assert_eq!(
+        options("--width 5 --height 5").into_config((80, 24)),
+        Err(CliError::Config(ConfigError::BoardTooSmall {
+            size: Vec2 { x: 5, y: 5 },
+            min: Vec2 { x: 7, y: 6 },
+        }))
+    );
+}
+
+#[test]
+fn it_can_parse_replay_options() {
+    let options = options("--record game.replay --replay old.replay --replay-speed 2.5");
+
+    assert_eq!(options.record, Some(PathBuf::from("game.replay")));
+    assert_eq!(options.replay, Some(PathBuf::from("old.replay")));
+    assert_eq!(options.replay_speed, Some(2.5));
+}
+
+#[test]
+fn it_refuses_non_positive_replay_speeds() {
+    assert_eq!(
+        parse(args("--replay-speed 0")),
+        Err(CliError::InvalidValue {
+            flag: "--replay-speed",
+            value: "0".to_string()
+        })
+    );
+}
+
+#[test]
+fn it_can_remove_the_walls() {
+    let config = options("--wrap").into_config((80, 24)).unwrap();
+
+    assert!(config.wrap);
+}
+
+#[test]
+fn it_can_play_on_a_bundled_level() {
+    let config = options("--map corridors").into_config((80, 24)).unwrap();
+
+    assert_eq!((config.width, config.height), (40, 18));
+    assert_eq!(config.difficulty, Difficulty::Easy);
+    assert_eq!(config.level.unwrap().name, "Corridors");
+}
+
+#[test]
+fn it_reports_missing_maps() {
+    assert!(matches!(
+        options("--map no/such/level.txt").into_config((80, 24)),
+        Err(CliError::InvalidMap { .. })
+    ));
+}
+
+#[test]
+fn it_reports_options_that_a_map_overrides() {
+    assert_eq!(
+        options("--map box --width 30").into_config((80, 24)),
+        Err(CliError::Conflict("--map", "--width"))
+    );
+    assert_eq!(
+        options("--map box --height 20").into_config((80, 24)),
+        Err(CliError::Conflict("--map", "--height"))
+    );
+    assert_eq!(
+        options("--map box --wrap").into_config((80, 24)),
+        Err(CliError::Conflict("--map", "--wrap"))
+    );
+}
+
+#[test]
+fn it_can_start_the_campaign_from_the_first_level() {
+    let config = options("--campaign-level 1").into_config((80, 24)).unwrap();
+
+    assert_eq!(config.campaign, Some(0));
+    assert_eq!(config.level.unwrap().name, "Box");
+}
+
+#[test]
+fn it_reports_bad_campaign_options() {
+    assert_eq!(
+        options("--campaign-level 9").into_config((80, 24)),
+        Err(CliError::InvalidValue {
+            flag: "--campaign-level",
+            value: "9".to_string()
+        })
+    );
+    assert_eq!(
+        options("--campaign --map box").into_config((80, 24)),
+        Err(CliError::Conflict("--map", "--campaign"))
+    );
+}
+
+#[test]
+fn it_can_start_a_versus_match() {
+    let options = options("--versus --no-color");
+    assert!(options.no_color);
+
+    let config = options.into_config((80, 24)).unwrap();
+
+    assert!(config.versus);
+}
+
+#[test]
+fn it_refuses_versus_on_levels() {
+    assert_eq!(
+        options("--versus --map box").into_config((80, 24)),
+        Err(CliError::Conflict("--versus", "--map"))
+    );
+    assert_eq!(
+        options("--versus --campaign").into_config((80, 24)),
+        Err(CliError::Conflict("--versus", "--campaign"))
+    );
+}
+
+#[test]
+fn it_can_play_against_the_computer() {
+    let config = options("--opponent greedy --demo hamiltonian")
+        .into_config((80, 24))
+        .unwrap();
+
+    assert!(config.versus);
+    assert_eq!(config.opponent, Some(Strategy::Greedy));
+    assert_eq!(config.demo, Some(Strategy::Hamiltonian));
+}
+
+#[test]
+fn it_reports_bad_computer_players() {
+    assert_eq!(
+        parse(args("--opponent smart")),
+        Err(CliError::InvalidValue {
+            flag: "--opponent",
+            value: "smart".to_string()
+        })
+    );
+    assert_eq!(
+        options("--opponent bfs --map box").into_config((80, 24)),
+        Err(CliError::Conflict("--opponent", "--map"))
+    );
+}
+
+#[test]
+fn it_can_run_headless_games() {
+    let (runner, format) = options("--headless --strategy greedy --games 3 --format csv")
+        .into_runner()
+        .unwrap();
+
+    assert_eq!(format, OutputFormat::Csv);
+    assert_eq!(runner.run().results.len(), 3);
+    assert!(options("--headless --measure-rendering").measure_rendering);
+    assert_eq!(
+        parse(args("--format xml")),
+        Err(CliError::InvalidValue {
+            flag: "--format",
+            value: "xml".to_string()
+        })
+    );
+}
+
+#[test]
+fn it_can_choose_the_kinds_of_food() {
+    let config = options("--food apple=3,poison=1 --food-count 2")
+        .into_config((80, 24))
+        .unwrap();
+
+    assert_eq!(config.food, FoodWeights::parse("apple=3,poison=1").unwrap());
+    assert_eq!(config.food_count, 2);
+    assert_eq!(
+        parse(args("--food apple=3,pear=1")),
+        Err(CliError::InvalidValue {
+            flag: "--food",
+            value: "apple=3,pear=1".to_string()
+        })
+    );
+    assert_eq!(
+        parse(args("--food-count 0")),
+        Err(CliError::InvalidValue {
+            flag: "--food-count",
+            value: "0".to_string()
+        })
+    );
+}
+
+#[test]
+fn it_can_resume_a_saved_game() {
+    let path = std::env::temp_dir().join(format!("rust-snake-test-{}-resume", std::process::id()));
+    let config = Config {
+        seed: 3,
+        ..Config::new(60, 20)
+    };
+    let snake_game = crate::terminal_snake_game::snake_game::SnakeGame::new_unranked(&config);
+    Save::new(&config, &snake_game).save(&path).unwrap();
+
+    let resumed = options("--resume").into_save(&path, (80, 24));
+    let too_large = options("--resume").into_save(&path, (40, 24));
+    let recorded = options("--resume --record game.replay").into_save(&path, (80, 24));
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(resumed.map(|save| save.config), Ok(config));
+    assert_eq!(
+        too_large.err(),
+        Some(CliError::BoardTooLarge {
+            width: 60,
+            height: 20,
+            terminal_size: (40, 24)
+        })
+    );
+    assert_eq!(
+        recorded.err(),
+        Some(CliError::Conflict("--resume", "--record"))
+    );
+    assert_eq!(
+        options("--resume").into_save(&path, (80, 24)).err(),
+        Some(CliError::Resume(SaveError::Io(
+            std::io::ErrorKind::NotFound
+        )))
+    );
+}
+
+#[test]
+fn it_can_choose_the_theme_and_the_colors() {
+    let options = options("--theme neon --colors 256");
+
+    assert_eq!(options.theme, Some(PathBuf::from("neon")));
+    assert_eq!(options.colors, Some(ColorSupport::Extended));
+    assert!(options.get_renderer().is_ok());
+    assert_eq!(
+        parse(args("--colors millions")),
+        Err(CliError::InvalidValue {
+            flag: "--colors",
+            value: "millions".to_string()
+        })
+    );
+}
+
+#[test]
+fn it_reports_bad_theme_options() {
+    assert_eq!(
+        options("--ascii --theme neon").get_renderer().err(),
+        Some(CliError::Conflict("--ascii", "--theme"))
+    );
+    assert_eq!(
+        options("--no-color --colors 16").get_renderer().err(),
+        Some(CliError::Conflict("--no-color", "--colors"))
+    );
+    assert_eq!(
+        options("--theme /nonexistent/sepia.txt")
+            .get_renderer()
+            .err(),
+        Some(CliError::InvalidTheme {
+            theme: PathBuf::from("/nonexistent/sepia.txt"),
+            error: ThemeError::Io(std::io::ErrorKind::NotFound)
+        })
+    );
+}
+
+#[test]
+fn it_can_host_and_join_online_matches() {
+    let host = options("--host 127.0.0.1:0 --width 40 --height 16");
+    let join = options("--join example.com:7777");
+
+    assert_eq!(host.host, Some("127.0.0.1:0".to_string()));
+    assert_eq!(join.join, Some("example.com:7777".to_string()));
+    assert!(host.into_server().is_ok());
+}
+
+#[test]
+fn it_reports_bad_online_options() {
+    assert_eq!(
+        options("--host 127.0.0.1:0 --map box").into_server().err(),
+        Some(CliError::Conflict("--host", "--map"))
+    );
+    assert_eq!(
+        options("--host 127.0.0.1:0 --join 127.0.0.1:7777")
+            .into_server()
+            .err(),
+        Some(CliError::Conflict("--host", "--join"))
+    );
+    assert!(matches!(
+        options("--host nowhere").into_server().err(),
+        Some(CliError::Online(_))
+    ));
+}
+
+#[test]
+fn it_can_host_races() {
+    let race = options("--host 127.0.0.1:0 --race --map box --seed 4");
+
+    assert!(race.race);
+    assert!(race.into_coordinator().is_ok());
+    assert_eq!(
+        options("--host 127.0.0.1:0 --race --versus")
+            .into_coordinator()
+            .err(),
+        Some(CliError::Conflict("--race", "--versus"))
+    );
+    assert_eq!(
+        options("--host 127.0.0.1:0 --race --campaign")
+            .into_coordinator()
+            .err(),
+        Some(CliError::Conflict("--race", "--campaign"))
+    );
+}
+
+#[test]
+fn it_can_stream_and_watch_games() {
+    let stream = options("--stream /tmp/snake.sock --seed 2");
+    let watch = options("--watch 127.0.0.1:7777");
+
+    assert_eq!(stream.stream, Some("/tmp/snake.sock".to_string()));
+    assert_eq!(watch.watch, Some("127.0.0.1:7777".to_string()));
+    assert_eq!(
+        parse(args("--watch")),
+        Err(CliError::MissingValue("--watch"))
+    );
+}
+
+#[test]
+fn it_serves_telnet_on_every_interface_given_a_port() {
+    let port = options("--serve-telnet 2323");
+    let address = options("--serve-telnet 127.0.0.1:2323");
+
+    assert_eq!(port.serve_telnet, Some("0.0.0.0:2323".to_string()));
+    assert_eq!(address.serve_telnet, Some("127.0.0.1:2323".to_string()));
+    assert_eq!(
+        options("--serve-telnet 2323 --host 0.0.0.0:7777")
+            .into_telnet_server()
+            .err(),
+        Some(CliError::Conflict("--serve-telnet", "--host"))
+    );
+}
+
+#[test]
+fn it_lets_a_bot_steer_the_first_snake() {
+    let bot = options("--bot ./bot.py --bot-time 20 --opponent bfs");
+
+    assert_eq!(bot.bot, Some("./bot.py".to_string()));
+    assert_eq!(bot.bot_time, Some(20));
+    assert_eq!(options("--opponent bfs").get_bot().err(), None);
+    assert_eq!(
+        options("--bot ./bot.py --versus").get_bot().err(),
+        Some(CliError::Conflict("--bot", "--versus"))
+    );
+    assert_eq!(
+        options("--bot ./bot.py --record game.replay")
+            .get_bot()
+            .err(),
+        Some(CliError::Conflict("--bot", "--record"))
+    );
+    for (args, flag) in [
+        ("--headless", "--headless"),
+        ("--host 0.0.0.0:7777", "--host"),
+        ("--join 127.0.0.1:7777", "--join"),
+        ("--race", "--race"),
+        ("--serve-telnet 2323", "--serve-telnet"),
+    ] {
+        assert_eq!(
+            options(&format!("--bot ./bot.py {}", args)).get_bot().err(),
+            Some(CliError::Conflict("--bot", flag))
+        );
+    }
+}
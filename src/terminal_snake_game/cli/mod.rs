@@ -0,0 +1,686 @@
+use super::headless::{HeadlessRunner, OutputFormat};
+use super::network::race::Coordinator;
+use super::network::server::Server;
+use super::network::telnet::TelnetServer;
+use super::network::NetworkError;
+use super::render::theme::{ColorSupport, Theme, ThemeError};
+use super::render::Renderer;
+use super::save::{Save, SaveError};
+use super::snake_game::ai::bot::{Bot, BotError, DEFAULT_MOVE_TIME};
+use super::snake_game::ai::Strategy;
+use super::snake_game::campaign::{Campaign, Progress};
+use super::snake_game::config::{Config, ConfigError};
+use super::snake_game::game::food::FoodWeights;
+use super::snake_game::high_scores::HighScores;
+use super::snake_game::level::{Level, LevelError};
+use super::snake_game::speed::Difficulty;
+use super::snake_game::HUD_HEIGHT;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Size of the board of the headless games if it isn't given
+const HEADLESS_SIZE: (usize, usize) = (40, 20);
+/// Size of the board of an online match if it isn't given, it fits into most terminals
+const ONLINE_SIZE: (usize, usize) = (60, 20);
+
+pub const USAGE: &str = "\
+Usage: rust-snake [OPTIONS]
+
+Options:
+  --width <CELLS>    Width of the board, defaults to the terminal's width
+  --height <CELLS>   Height of the board, defaults to the terminal's height
+  --speed <PRESET>   easy, normal, hard or insane, defaults to normal
+  --accelerate       Speed up as the snake grows
+  --seed <NUMBER>    Seed of the random generator, defaults to a random one
+  --map <FILE|NAME>  Play on a level file or a bundled level: box, pillars, corridors,
+                     tunnels, hazards; the level sets the size of the board
+  --wrap             Remove the border walls, the snake re-enters on the opposite edge
+  --campaign         Play the bundled levels one after the other, from the furthest one reached
+  --campaign-level <NUMBER>
+                     Start the campaign from an earlier level
+  --versus           Two players on one keyboard, the first to win 3 rounds wins the match
+  --opponent <STRATEGY>
+                     Play versus against the computer: greedy, bfs or hamiltonian
+  --demo <STRATEGY>  Let the computer play on the title screen
+  --bot <COMMAND>    Let a program steer the first snake instead of the keyboard, it gets the
+                     board on its input and answers its moves, see the README for the protocol
+  --bot-time <MS>    Time of the bot for a move, the snake goes straight on if it's late;
+                     defaults to 50
+  --food <KIND=WEIGHT,...>
+                     How often each kind of food appears, e.g. apple=10,golden=2,poison=1;
+                     kinds: apple, golden, shrinking, speed-up, slow-down, bonus, poison,
+                     and the power-ups ghost, invincible, magnet, multiplier;
+                     defaults to apples only
+  --food-count <NUMBER>
+                     Number of foods on the board at the same time, defaults to 1
+  --theme <FILE|NAME>
+                     Glyphs and colors from a theme file or a bundled theme: classic, ascii,
+                     mono, neon; defaults to classic
+  --colors <DEPTH>   Colors of the terminal: none, 16, 256 or truecolor, detected by default
+  --no-color         Don't use colors, the same as --colors none
+  --ascii            Use ASCII characters only, the same as --theme ascii
+  --headless         Let the computer play games without a terminal and print statistics
+  --strategy <STRATEGY>
+                     Computer player of the headless games, defaults to bfs
+  --games <NUMBER>   Number of headless games, defaults to 100; their seeds follow --seed
+  --max-ticks <NUMBER>
+                     Headless games still running after this many ticks are stopped,
+                     defaults to 10000
+  --jobs <NUMBER>    Number of headless games played at the same time,
+                     defaults to the number of CPUs
+  --format <FORMAT>  json for a summary or csv for a line per game, defaults to json
+  --measure-rendering
+                     With --headless, play the first game only and print the bytes written
+                     to the terminal per tick, repainting every cell and only the changed ones
+  --host <ADDRESS>   Host an online versus match on the given address, e.g. 0.0.0.0:7777;
+                     the first two to join play, the others watch
+  --race             With --host, host a race instead: everyone plays their own board with
+                     the same seed and sees the standings of the others
+  --join <ADDRESS>   Join an online match or race, the other game options are ignored
+  --stream <ADDRESS> Let others watch the game played in this terminal with --watch;
+                     the address is a TCP one or the path of a Unix domain socket
+  --watch <ADDRESS>  Watch a streamed game or an online match without playing
+  --serve-telnet <PORT|ADDRESS>
+                     Host a game of their own for everyone who connects with telnet,
+                     the games share the high-score table of this machine
+  --record <FILE>    Save the game into a replay file
+  --resume           Continue the game saved on the last quit,
+                     the other game options are ignored
+  --replay <FILE>    Play back a replay file, the other game options are ignored
+  --replay-speed <FACTOR>
+                     Speed of the playback, defaults to 1
+  -h, --help         Print this message
+
+Quitting in the middle of a round saves it, Ctrl+S saves it without quitting.
+
+Replay controls:
+  Space: pause  Right: step  +/-: speed  [/]: seek 100 ticks  Home: restart  Q: quit";
+
+/// What the user asked for on the command line
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Play(Box<Options>),
+    Help,
+}
+
+/// Parsed command-line options
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub difficulty: Option<Difficulty>,
+    pub accelerate: bool,
+    pub seed: Option<u64>,
+    pub map: Option<PathBuf>,
+    pub wrap: bool,
+    pub campaign: bool,
+    pub campaign_level: Option<usize>,
+    pub versus: bool,
+    pub opponent: Option<Strategy>,
+    pub demo: Option<Strategy>,
+    pub bot: Option<String>,
+    pub bot_time: Option<u64>,
+    pub food: Option<FoodWeights>,
+    pub food_count: Option<usize>,
+    pub theme: Option<PathBuf>,
+    pub colors: Option<ColorSupport>,
+    pub no_color: bool,
+    pub ascii: bool,
+    pub headless: bool,
+    pub strategy: Option<Strategy>,
+    pub games: Option<usize>,
+    pub max_ticks: Option<u64>,
+    pub jobs: Option<usize>,
+    pub format: Option<OutputFormat>,
+    pub measure_rendering: bool,
+    pub host: Option<String>,
+    pub race: bool,
+    pub join: Option<String>,
+    pub stream: Option<String>,
+    pub watch: Option<String>,
+    pub serve_telnet: Option<String>,
+    pub record: Option<PathBuf>,
+    pub resume: bool,
+    pub replay: Option<PathBuf>,
+    pub replay_speed: Option<f64>,
+}
+
+impl Options {
+    /// Creates the settings of the game, missing sizes are taken from the given terminal size
+    pub fn into_config(self, terminal_size: (usize, usize)) -> Result<Config, CliError> {
+        let max_height = terminal_size.1.saturating_sub(HUD_HEIGHT);
+        let config = self.into_sized_config((terminal_size.0, max_height))?;
+        let (width, height) = (config.width, config.height);
+        if width > terminal_size.0 || height > max_height {
+            return Err(CliError::BoardTooLarge {
+                width,
+                height,
+                terminal_size,
+            });
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Creates the renderer with the chosen theme and colors
+    pub fn get_renderer(&self) -> Result<Renderer, CliError> {
+        let theme = match (&self.theme, self.ascii) {
+            (Some(_), true) => return Err(CliError::Conflict("--ascii", "--theme")),
+            (Some(theme), false) => load_theme(theme)?,
+            (None, true) => Theme::bundled("ascii").expect("Missing ASCII theme"),
+            (None, false) => Theme::default(),
+        };
+        let colors = match (self.colors, self.no_color) {
+            (Some(_), true) => return Err(CliError::Conflict("--no-color", "--colors")),
+            (Some(colors), false) => colors,
+            (None, true) => ColorSupport::None,
+            (None, false) => ColorSupport::detect(),
+        };
+        Ok(Renderer::new(theme, colors))
+    }
+
+    /// Reports the first option that can't be played by a bot, e.g. a game without a terminal
+    pub fn check_bot(&self) -> Result<(), CliError> {
+        if self.bot.is_none() {
+            return Ok(());
+        }
+        let conflict = find_given(&[
+            ("--headless", self.headless),
+            ("--host", self.host.is_some()),
+            ("--join", self.join.is_some()),
+            ("--race", self.race),
+            ("--serve-telnet", self.serve_telnet.is_some()),
+            ("--versus", self.versus),
+            ("--record", self.record.is_some()),
+            ("--resume", self.resume),
+        ]);
+        match conflict {
+            Some(flag) => Err(CliError::Conflict("--bot", flag)),
+            None => Ok(()),
+        }
+    }
+
+    /// Starts the bot that steers the first snake, if one is given
+    ///
+    /// The bot can't be recorded or saved, and it takes the keys of the first player of a
+    /// versus match only against the computer.
+    pub fn get_bot(&self) -> Result<Option<Bot>, CliError> {
+        let command = match &self.bot {
+            Some(command) => command,
+            None => return Ok(None),
+        };
+        self.check_bot()?;
+
+        let move_time = self
+            .bot_time
+            .map_or(DEFAULT_MOVE_TIME, Duration::from_millis);
+        Ok(Some(Bot::spawn(command, move_time)?))
+    }
+
+    /// Loads the game saved in the given file, it has to fit into the given terminal size
+    pub fn into_save(self, path: &Path, terminal_size: (usize, usize)) -> Result<Save, CliError> {
+        if self.record.is_some() {
+            return Err(CliError::Conflict("--resume", "--record"));
+        }
+        let save = Save::load(path)?;
+        let (width, height) = (save.config.width, save.config.height);
+        if width > terminal_size.0 || height + HUD_HEIGHT > terminal_size.1 {
+            return Err(CliError::BoardTooLarge {
+                width,
+                height,
+                terminal_size,
+            });
+        }
+        Ok(save)
+    }
+
+    /// Starts the server of an online match with the chosen settings
+    pub fn into_server(self) -> Result<Server, CliError> {
+        let address = self.host.clone().unwrap_or_default();
+        let conflict = find_given(&[
+            ("--join", self.join.is_some()),
+            ("--map", self.map.is_some()),
+            ("--campaign", self.campaign || self.campaign_level.is_some()),
+            ("--opponent", self.opponent.is_some()),
+            ("--record", self.record.is_some()),
+        ]);
+        if let Some(flag) = conflict {
+            return Err(CliError::Conflict("--host", flag));
+        }
+
+        let config = Config {
+            online: true,
+            ..Options {
+                versus: true,
+                ..self
+            }
+            .into_sized_config(ONLINE_SIZE)?
+        };
+        config.validate()?;
+        Ok(Server::bind(&address, &config)?)
+    }
+
+    /// Starts the coordinator of an online race with the chosen settings, levels can be raced too
+    pub fn into_coordinator(self) -> Result<Coordinator, CliError> {
+        let address = self.host.clone().unwrap_or_default();
+        let conflict = find_given(&[
+            ("--join", self.join.is_some()),
+            ("--campaign", self.campaign || self.campaign_level.is_some()),
+            ("--versus", self.versus),
+            ("--opponent", self.opponent.is_some()),
+            ("--record", self.record.is_some()),
+        ]);
+        if let Some(flag) = conflict {
+            return Err(CliError::Conflict("--race", flag));
+        }
+
+        let config = self.into_sized_config(ONLINE_SIZE)?;
+        config.validate()?;
+        Ok(Coordinator::bind(&address, &config)?)
+    }
+
+    /// Starts the telnet server with the chosen settings, every session plays a game of its own
+    ///
+    /// The colors of the clients can't be detected, they get the 16 basic ones by default.
+    pub fn into_telnet_server(self) -> Result<TelnetServer, CliError> {
+        let address = self.serve_telnet.clone().unwrap_or_default();
+        let conflict = find_given(&[
+            ("--host", self.host.is_some()),
+            ("--join", self.join.is_some()),
+            ("--stream", self.stream.is_some()),
+            ("--watch", self.watch.is_some()),
+            ("--record", self.record.is_some()),
+        ]);
+        if let Some(flag) = conflict {
+            return Err(CliError::Conflict("--serve-telnet", flag));
+        }
+
+        let renderer = match (self.colors, self.no_color) {
+            (None, false) => Options {
+                colors: Some(ColorSupport::Basic),
+                theme: self.theme.clone(),
+                ascii: self.ascii,
+                ..Options::default()
+            }
+            .get_renderer()?,
+            _ => self.get_renderer()?,
+        };
+        let high_scores = match HighScores::default_path() {
+            Some(path) => HighScores::load(&path),
+            None => HighScores::new(),
+        };
+        let seed = self.seed;
+        let config = self.into_sized_config(ONLINE_SIZE)?;
+        config.validate()?;
+        Ok(TelnetServer::bind(
+            &address,
+            &config,
+            seed,
+            renderer,
+            high_scores,
+        )?)
+    }
+
+    /// Creates the runner of the headless games and the format of its statistics
+    pub fn into_runner(self) -> Result<(HeadlessRunner, OutputFormat), CliError> {
+        let strategy = self.strategy.unwrap_or(Strategy::Bfs);
+        let games = self.games.unwrap_or(100);
+        let format = self.format.unwrap_or(OutputFormat::Json);
+        let (max_ticks, jobs) = (self.max_ticks, self.jobs);
+
+        let config = self.into_sized_config(HEADLESS_SIZE)?;
+        config.validate()?;
+
+        let mut runner = HeadlessRunner::new(config, strategy, games);
+        if let Some(max_ticks) = max_ticks {
+            runner = runner.with_max_ticks(max_ticks);
+        }
+        if let Some(jobs) = jobs {
+            runner = runner.with_jobs(jobs);
+        }
+        Ok((runner, format))
+    }
+
+    /// Creates the settings without checking them, missing sizes are taken from the given size
+    fn into_sized_config(self, size: (usize, usize)) -> Result<Config, CliError> {
+        let campaign = self.campaign || self.campaign_level.is_some();
+        let versus = match (self.versus, self.opponent) {
+            (true, _) => Some("--versus"),
+            (false, Some(_)) => Some("--opponent"),
+            (false, None) => None,
+        };
+        // a level brings its own size and edges
+        let conflict = find_given(&[
+            ("--width", self.width.is_some()),
+            ("--height", self.height.is_some()),
+            ("--wrap", self.wrap),
+        ]);
+        if let (Some(_), Some(flag)) = (&self.map, conflict) {
+            return Err(CliError::Conflict("--map", flag));
+        }
+        let defaults = match (&self.map, versus) {
+            (Some(_), _) if campaign => return Err(CliError::Conflict("--map", "--campaign")),
+            (Some(_), Some(flag)) => return Err(CliError::Conflict(flag, "--map")),
+            (None, Some(flag)) if campaign => return Err(CliError::Conflict(flag, "--campaign")),
+            (Some(map), _) => Config::with_level(load_level(map)?),
+            (None, _) if campaign => {
+                Config::with_campaign(get_campaign_start(self.campaign_level)?)
+            }
+            (None, _) => Config::new(self.width.unwrap_or(size.0), self.height.unwrap_or(size.1)),
+        };
+
+        Ok(Config {
+            difficulty: self.difficulty.unwrap_or(defaults.difficulty),
+            accelerate: self.accelerate,
+            wrap: self.wrap,
+            versus: versus.is_some(),
+            opponent: self.opponent,
+            demo: self.demo,
+            food: self.food.unwrap_or_default(),
+            food_count: self.food_count.unwrap_or(1),
+            seed: self.seed.unwrap_or(defaults.seed),
+            ..defaults
+        })
+    }
+}
+
+/// Returns the first of the flags that has been given
+fn find_given(flags: &[(&'static str, bool)]) -> Option<&'static str> {
+    flags
+        .iter()
+        .find(|(_, given)| *given)
+        .map(|(flag, _)| *flag)
+}
+
+/// Parses the arguments without the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, CliError> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--width" => options.width = Some(parse_value("--width", args.next())?),
+            "--height" => options.height = Some(parse_value("--height", args.next())?),
+            "--speed" => {
+                let value = args.next().ok_or(CliError::MissingValue("--speed"))?;
+                options.difficulty = Some(parse_difficulty(&value)?);
+            }
+            "--accelerate" => options.accelerate = true,
+            "--seed" => options.seed = Some(parse_value("--seed", args.next())?),
+            "--map" => options.map = Some(parse_value("--map", args.next())?),
+            "--wrap" => options.wrap = true,
+            "--campaign" => options.campaign = true,
+            "--campaign-level" => {
+                options.campaign_level = Some(parse_value("--campaign-level", args.next())?)
+            }
+            "--versus" => options.versus = true,
+            "--opponent" => {
+                let value = args.next().ok_or(CliError::MissingValue("--opponent"))?;
+                options.opponent = Some(parse_strategy("--opponent", &value)?);
+            }
+            "--demo" => {
+                let value = args.next().ok_or(CliError::MissingValue("--demo"))?;
+                options.demo = Some(parse_strategy("--demo", &value)?);
+            }
+            "--bot" => options.bot = Some(parse_value("--bot", args.next())?),
+            "--bot-time" => options.bot_time = Some(parse_value("--bot-time", args.next())?),
+            "--food" => {
+                let value = args.next().ok_or(CliError::MissingValue("--food"))?;
+                options.food = Some(parse_food(&value)?);
+            }
+            "--food-count" => {
+                let value = args.next();
+                let count: usize = parse_value("--food-count", value.clone())?;
+                if count == 0 {
+                    return Err(CliError::InvalidValue {
+                        flag: "--food-count",
+                        value: value.unwrap_or_default(),
+                    });
+                }
+                options.food_count = Some(count);
+            }
+            "--theme" => options.theme = Some(parse_value("--theme", args.next())?),
+            "--colors" => {
+                let value = args.next().ok_or(CliError::MissingValue("--colors"))?;
+                options.colors = Some(parse_colors(&value)?);
+            }
+            "--no-color" => options.no_color = true,
+            "--ascii" => options.ascii = true,
+            "--headless" => options.headless = true,
+            "--strategy" => {
+                let value = args.next().ok_or(CliError::MissingValue("--strategy"))?;
+                options.strategy = Some(parse_strategy("--strategy", &value)?);
+            }
+            "--games" => options.games = Some(parse_value("--games", args.next())?),
+            "--max-ticks" => options.max_ticks = Some(parse_value("--max-ticks", args.next())?),
+            "--jobs" => options.jobs = Some(parse_value("--jobs", args.next())?),
+            "--format" => {
+                let value = args.next().ok_or(CliError::MissingValue("--format"))?;
+                options.format = Some(parse_format(&value)?);
+            }
+            "--measure-rendering" => options.measure_rendering = true,
+            "--host" => options.host = Some(parse_value("--host", args.next())?),
+            "--race" => options.race = true,
+            "--join" => options.join = Some(parse_value("--join", args.next())?),
+            "--stream" => options.stream = Some(parse_value("--stream", args.next())?),
+            "--watch" => options.watch = Some(parse_value("--watch", args.next())?),
+            "--serve-telnet" => {
+                let value: String = parse_value("--serve-telnet", args.next())?;
+                options.serve_telnet = Some(get_telnet_address(value));
+            }
+            "--record" => options.record = Some(parse_value("--record", args.next())?),
+            "--resume" => options.resume = true,
+            "--replay" => options.replay = Some(parse_value("--replay", args.next())?),
+            "--replay-speed" => {
+                let value = args.next();
+                let speed: f64 = parse_value("--replay-speed", value.clone())?;
+                if speed <= 0.0 || !speed.is_finite() {
+                    return Err(CliError::InvalidValue {
+                        flag: "--replay-speed",
+                        value: value.unwrap_or_default(),
+                    });
+                }
+                options.replay_speed = Some(speed);
+            }
+            _ => return Err(CliError::UnknownArgument(arg)),
+        }
+    }
+
+    Ok(Command::Play(Box::new(options)))
+}
+
+/// Returns the index of the first level of the campaign, the furthest unlocked one by default
+fn get_campaign_start(level: Option<usize>) -> Result<usize, CliError> {
+    let count = Campaign::get_levels().len();
+    let unlocked = Progress::load_default().get_unlocked().min(count);
+    match level {
+        None => Ok(unlocked - 1),
+        Some(level) if level == 0 || level > count => Err(CliError::InvalidValue {
+            flag: "--campaign-level",
+            value: level.to_string(),
+        }),
+        Some(level) if level > unlocked => Err(CliError::LevelLocked { level, unlocked }),
+        Some(level) => Ok(level - 1),
+    }
+}
+
+/// Returns the bundled level with the given name or loads it from a file
+fn load_level(map: &Path) -> Result<Level, CliError> {
+    let bundled = map.to_str().and_then(Level::bundled);
+    match bundled {
+        Some(level) => Ok(level),
+        None => Level::load(map).map_err(|error| CliError::InvalidMap {
+            map: map.to_path_buf(),
+            error,
+        }),
+    }
+}
+
+/// Returns the address to listen on for telnet, a port alone is a port of every interface
+fn get_telnet_address(value: String) -> String {
+    match !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+        true => format!("0.0.0.0:{}", value),
+        false => value,
+    }
+}
+
+/// Returns the bundled theme with the given name or loads it from a file
+fn load_theme(theme: &Path) -> Result<Theme, CliError> {
+    let bundled = theme.to_str().and_then(Theme::bundled);
+    match bundled {
+        Some(theme) => Ok(theme),
+        None => Theme::load(theme).map_err(|error| CliError::InvalidTheme {
+            theme: theme.to_path_buf(),
+            error,
+        }),
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(
+    flag: &'static str,
+    value: Option<String>,
+) -> Result<T, CliError> {
+    let value = value.ok_or(CliError::MissingValue(flag))?;
+    value
+        .parse()
+        .map_err(|_| CliError::InvalidValue { flag, value })
+}
+
+fn parse_difficulty(value: &str) -> Result<Difficulty, CliError> {
+    Difficulty::from_name(value).ok_or_else(|| CliError::InvalidValue {
+        flag: "--speed",
+        value: value.to_string(),
+    })
+}
+
+fn parse_strategy(flag: &'static str, value: &str) -> Result<Strategy, CliError> {
+    Strategy::from_name(value).ok_or_else(|| CliError::InvalidValue {
+        flag,
+        value: value.to_string(),
+    })
+}
+
+fn parse_food(value: &str) -> Result<FoodWeights, CliError> {
+    FoodWeights::parse(value).ok_or_else(|| CliError::InvalidValue {
+        flag: "--food",
+        value: value.to_string(),
+    })
+}
+
+fn parse_colors(value: &str) -> Result<ColorSupport, CliError> {
+    ColorSupport::from_name(value).ok_or_else(|| CliError::InvalidValue {
+        flag: "--colors",
+        value: value.to_string(),
+    })
+}
+
+fn parse_format(value: &str) -> Result<OutputFormat, CliError> {
+    OutputFormat::from_name(value).ok_or_else(|| CliError::InvalidValue {
+        flag: "--format",
+        value: value.to_string(),
+    })
+}
+
+/// # CliError
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    UnknownArgument(String),
+    MissingValue(&'static str),
+    InvalidValue {
+        flag: &'static str,
+        value: String,
+    },
+    Conflict(&'static str, &'static str),
+    LevelLocked {
+        level: usize,
+        unlocked: usize,
+    },
+    BoardTooLarge {
+        width: usize,
+        height: usize,
+        terminal_size: (usize, usize),
+    },
+    InvalidMap {
+        map: PathBuf,
+        error: LevelError,
+    },
+    InvalidTheme {
+        theme: PathBuf,
+        error: ThemeError,
+    },
+    Config(ConfigError),
+    Resume(SaveError),
+    Online(NetworkError),
+    Bot(BotError),
+}
+
+impl From<ConfigError> for CliError {
+    fn from(err: ConfigError) -> Self {
+        Self::Config(err)
+    }
+}
+
+impl From<NetworkError> for CliError {
+    fn from(err: NetworkError) -> Self {
+        Self::Online(err)
+    }
+}
+
+impl From<BotError> for CliError {
+    fn from(err: BotError) -> Self {
+        Self::Bot(err)
+    }
+}
+
+impl From<SaveError> for CliError {
+    fn from(err: SaveError) -> Self {
+        Self::Resume(err)
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownArgument(arg) => write!(f, "Unknown argument: {}", arg),
+            Self::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            Self::InvalidValue { flag, value } => {
+                write!(f, "Invalid value for {}: {}", flag, value)
+            }
+            Self::Conflict(one, other) => write!(f, "{} can't be used with {}", one, other),
+            Self::LevelLocked { level, unlocked } => write!(
+                f,
+                "Level {} of the campaign is locked, only the first {} can be played",
+                level, unlocked
+            ),
+            Self::BoardTooLarge {
+                width,
+                height,
+                terminal_size,
+            } => write!(
+                f,
+                "A {}x{} board doesn't fit into the {}x{} terminal",
+                width, height, terminal_size.0, terminal_size.1
+            ),
+            Self::InvalidMap { map, error } => {
+                write!(f, "Invalid map {}: {}", map.display(), error)
+            }
+            Self::InvalidTheme { theme, error } => {
+                write!(f, "Invalid theme {}: {}", theme.display(), error)
+            }
+            Self::Config(e) => write!(f, "{}", e),
+            Self::Resume(e) => write!(f, "Couldn't resume the game: {}", e),
+            Self::Online(e) => write!(f, "Couldn't host the game: {}", e),
+            Self::Bot(e) => write!(f, "Couldn't start the bot: {}", e),
+        }
+    }
+}
+
+impl Error for CliError {}
+
+#[cfg(test)]
+mod cli_test;
@@ -1,16 +1,23 @@
+use super::get_terminal_size;
 use super::traits::*;
+use signal_hook::consts::{SIGCONT, SIGTSTP, SIGWINCH};
+use signal_hook::iterator::Signals;
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
-use termion::event::Key;
+use termion::event::{self as term_event, Key};
 use termion::input::TermRead;
 
+/// Reported by terminals with focus reporting enabled when they lose focus
+pub(super) const FOCUS_OUT: &[u8] = b"\x1b[O";
+
 pub struct TerminalEventStream;
 
 impl EventStream for TerminalEventStream {
-    fn start() -> Receiver<Event> {
+    fn start(tick_interval: TickInterval) -> Receiver<Event> {
         let (tx, rx): (Sender<Event>, Receiver<Event>) = mpsc::channel();
 
-        Self::start_timer_thread(tx.clone());
+        Self::start_timer_thread(tx.clone(), tick_interval);
+        Self::start_signal_thread(tx.clone());
         Self::start_stdin_thread(tx);
 
         rx
@@ -18,10 +25,10 @@ impl EventStream for TerminalEventStream {
 }
 
 impl TerminalEventStream {
-    fn start_timer_thread(tx: Sender<Event>) {
+    fn start_timer_thread(tx: Sender<Event>, tick_interval: TickInterval) {
         // timer thread
         std::thread::spawn(move || loop {
-            std::thread::sleep(std::time::Duration::from_millis(100));
+            std::thread::sleep(tick_interval.get());
             if tx.send(Event::Time).is_err() {
                 println!("Timer is down");
                 break;
@@ -29,17 +36,47 @@ impl TerminalEventStream {
         });
     }
 
+    fn start_signal_thread(tx: Sender<Event>) {
+        // thread for job control and resize signals
+        let mut signals =
+            Signals::new([SIGTSTP, SIGCONT, SIGWINCH]).expect("Couldn't register signals");
+        std::thread::spawn(move || {
+            for signal in signals.forever() {
+                let event = match signal {
+                    SIGTSTP => Event::Suspend,
+                    SIGWINCH => {
+                        let (columns, rows) = get_terminal_size();
+                        Event::Resize(columns, rows)
+                    }
+                    _ => Event::Continue,
+                };
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    #[allow(clippy::manual_flatten)]
     fn start_stdin_thread(tx: Sender<Event>) {
         // thread for stdin events
         std::thread::spawn(move || {
             let stdin = std::io::stdin();
-            for c in stdin.keys() {
+            for c in stdin.events() {
                 if let Ok(r) = c {
-                    if tx.send(Event::Key(r)).is_err() {
+                    let event = match r {
+                        term_event::Event::Key(key) => Event::Key(key),
+                        term_event::Event::Unsupported(ref bytes) if bytes == FOCUS_OUT => {
+                            Event::FocusLost
+                        }
+                        _ => continue,
+                    };
+                    let quit = matches!(event, Event::Key(Key::Char('q')));
+                    if tx.send(event).is_err() {
                         println!("Stdin is down");
                         break;
                     }
-                    if let Key::Char('q') = r {
+                    if quit {
                         break;
                     }
                 }
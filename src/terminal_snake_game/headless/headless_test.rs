@@ -0,0 +1,91 @@
+use super::*;
+use crate::terminal_snake_game::render::theme::{ColorSupport, Theme};
+
+fn runner(strategy: Strategy, games: usize) -> HeadlessRunner {
+    let config = Config {
+        seed: 7,
+        ..Config::new(20, 12)
+    };
+    HeadlessRunner::new(config, strategy, games)
+}
+
+#[test]
+fn it_computes_statistics() {
+    let statistics = Statistics::of(&[4.0, 1.0, 3.0, 2.0]);
+
+    assert_eq!(
+        statistics,
+        Statistics {
+            mean: 2.5,
+            median: 2.5,
+            min: 1.0,
+            max: 4.0
+        }
+    );
+    assert_eq!(Statistics::of(&[5.0, 1.0, 3.0]).median, 3.0);
+    assert_eq!(Statistics::of(&[]).mean, 0.0);
+}
+
+#[test]
+fn it_plays_the_same_games_on_any_number_of_threads() {
+    let one = runner(Strategy::Greedy, 6).with_jobs(1).run();
+    let three = runner(Strategy::Greedy, 6).with_jobs(3).run();
+
+    assert_eq!(one.results, three.results);
+    let seeds: Vec<u64> = one.results.iter().map(|result| result.seed).collect();
+    assert_eq!(seeds, vec![7, 8, 9, 10, 11, 12]);
+}
+
+#[test]
+fn it_counts_the_causes_of_death() {
+    let report = runner(Strategy::Bfs, 4).run();
+
+    let deaths: usize = report.get_death_causes().values().sum();
+    assert_eq!(deaths + report.get_survivors(), 4);
+    assert!(report.results.iter().all(|result| result.ticks > 0));
+}
+
+#[test]
+fn it_stops_games_at_the_tick_limit() {
+    let report = runner(Strategy::Hamiltonian, 2).with_max_ticks(50).run();
+
+    assert_eq!(report.get_survivors(), 2);
+    assert!(report.results.iter().all(|result| result.ticks == 50));
+}
+
+#[test]
+fn it_can_print_a_line_for_every_game() {
+    let report = Report {
+        strategy: Strategy::Greedy,
+        results: vec![GameResult {
+            seed: 3,
+            points: 12,
+            apples: 12,
+            length: 16,
+            ticks: 240,
+            deaths: vec!["wall"],
+        }],
+    };
+
+    assert_eq!(
+        report.format(OutputFormat::Csv),
+        "seed,points,apples,length,ticks,death\n3,12,12,16,240,wall\n"
+    );
+    assert!(report
+        .format(OutputFormat::Json)
+        .contains("\"deaths\": {\"wall\": 1}"));
+}
+
+#[test]
+fn it_measures_the_output_of_the_renderer() {
+    let renderer = Renderer::new(Theme::default(), ColorSupport::Extended);
+    let report = runner(Strategy::Hamiltonian, 1)
+        .with_max_ticks(100)
+        .measure_rendering(renderer);
+
+    assert_eq!(report.ticks, 100);
+    assert!(report.diffed * 5 < report.repainted);
+    assert!(report
+        .to_string()
+        .contains("\"bytes_per_tick\": {\"repaint\": "));
+}
@@ -0,0 +1,319 @@
+use super::render::Renderer;
+use super::snake_game::ai::Strategy;
+use super::snake_game::config::Config;
+use super::snake_game::game::errors::GameError;
+use super::snake_game::state::State;
+use super::snake_game::SnakeGame;
+use super::traits::SnakeGame as SnakeGameTrait;
+use super::update_reason::{Control, UpdateReason};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::thread;
+
+/// Format of the statistics printed by the headless runner
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// Summary of all the games
+    Json,
+    /// One line for every game
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Plays games with a computer player as fast as possible, without a terminal
+pub struct HeadlessRunner {
+    config: Config,
+    strategy: Strategy,
+    games: usize,
+    /// Games still running after this many ticks are stopped
+    max_ticks: u64,
+    jobs: usize,
+}
+
+impl HeadlessRunner {
+    /// Creates a runner of the given number of games, their seeds follow the seed of the config
+    pub fn new(config: Config, strategy: Strategy, games: usize) -> HeadlessRunner {
+        HeadlessRunner {
+            config,
+            strategy,
+            games,
+            max_ticks: 10_000,
+            jobs: thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+        }
+    }
+
+    pub fn with_max_ticks(self, max_ticks: u64) -> HeadlessRunner {
+        HeadlessRunner { max_ticks, ..self }
+    }
+
+    /// Sets the number of games played at the same time
+    pub fn with_jobs(self, jobs: usize) -> HeadlessRunner {
+        HeadlessRunner {
+            jobs: jobs.max(1),
+            ..self
+        }
+    }
+
+    /// Plays every game and collects their results in the order of their seeds
+    pub fn run(&self) -> Report {
+        let jobs = self.jobs.min(self.games).max(1);
+        let mut results: Vec<GameResult> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..jobs)
+                .map(|job| {
+                    scope.spawn(move || {
+                        (job..self.games)
+                            .step_by(jobs)
+                            .map(|game| self.play(self.config.seed.wrapping_add(game as u64)))
+                            .collect::<Vec<GameResult>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("A game has panicked"))
+                .collect()
+        });
+        results.sort_by_key(|result| result.seed.wrapping_sub(self.config.seed));
+
+        Report {
+            strategy: self.strategy,
+            results,
+        }
+    }
+
+    /// Plays the game of the first seed and measures the output of the renderer on every tick,
+    /// painting every cell of the frame and only the changed ones
+    pub fn measure_rendering(&self, renderer: Renderer) -> RenderingReport {
+        let mut repainting = renderer.clone();
+        let mut diffing = renderer;
+        let (mut repainted, mut diffed) = (Vec::new(), Vec::new());
+
+        let result = self.play_with(self.config.seed, &mut |snake_game| {
+            repainting.invalidate();
+            repainting.paint(&mut repainted, snake_game.as_draw().draw());
+            diffing.paint(&mut diffed, snake_game.as_draw().draw());
+        });
+
+        RenderingReport {
+            width: self.config.width,
+            height: self.config.height,
+            ticks: result.ticks,
+            repainted: repainted.len(),
+            diffed: diffed.len(),
+        }
+    }
+
+    fn play(&self, seed: u64) -> GameResult {
+        self.play_with(seed, &mut |_| ())
+    }
+
+    /// Plays a game, the given function sees it after every update
+    fn play_with(&self, seed: u64, on_update: &mut dyn FnMut(&SnakeGame)) -> GameResult {
+        let config = Config {
+            seed,
+            ..self.config.clone()
+        };
+        let mut snake_game = SnakeGame::new_computer_game(&config, self.strategy);
+
+        let deaths = loop {
+            if snake_game.get_score().ticks >= self.max_ticks {
+                break Vec::new();
+            }
+            match snake_game.get_state() {
+                State::Playing => snake_game.update(UpdateReason::Time),
+                State::LevelIntro => snake_game.update(UpdateReason::Control(Control::Start)),
+                State::GameOver(error) => break get_causes(error),
+                State::Title | State::Paused | State::Completed => break Vec::new(),
+            }
+            on_update(&snake_game);
+        };
+
+        let score = snake_game.get_score();
+        GameResult {
+            seed,
+            points: score.points,
+            apples: score.apples,
+            length: score.length,
+            ticks: score.ticks,
+            deaths,
+        }
+    }
+}
+
+/// Returns a short name for every reason the round has ended with
+fn get_causes(error: &GameError) -> Vec<&'static str> {
+    match error {
+        GameError::KilledByWall => vec!["wall"],
+        GameError::KilledBySnake => vec!["snake"],
+        GameError::SelfTurningDirectionError(_) => vec!["self-turning"],
+        GameError::SelfEatingStepError(_) => vec!["self-eating"],
+        GameError::Poisoned => vec!["poison"],
+        GameError::BoardFilled => vec!["board-filled"],
+        GameError::PlayersLost(losers) => losers
+            .iter()
+            .flat_map(|(_, error)| get_causes(error))
+            .collect(),
+    }
+}
+
+/// Result of a single game of the first player
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameResult {
+    pub seed: u64,
+    pub points: u32,
+    pub apples: u32,
+    pub length: usize,
+    pub ticks: u64,
+    /// Reasons of the end of the game, empty if it was still running at the tick limit
+    pub deaths: Vec<&'static str>,
+}
+
+/// Results of all the games played by the runner
+pub struct Report {
+    pub strategy: Strategy,
+    pub results: Vec<GameResult>,
+}
+
+impl Report {
+    /// Returns the number of games ended for each reason
+    pub fn get_death_causes(&self) -> BTreeMap<&'static str, usize> {
+        let mut causes = BTreeMap::new();
+        for death in self.results.iter().flat_map(|result| &result.deaths) {
+            *causes.entry(*death).or_insert(0) += 1;
+        }
+        causes
+    }
+
+    /// Returns the number of games that were still running at the tick limit
+    pub fn get_survivors(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.deaths.is_empty())
+            .count()
+    }
+
+    pub fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.to_string(),
+            OutputFormat::Csv => self.to_csv(),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("seed,points,apples,length,ticks,death\n");
+        for result in &self.results {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                result.seed,
+                result.points,
+                result.apples,
+                result.length,
+                result.ticks,
+                result.deaths.join(" ")
+            ));
+        }
+        csv
+    }
+}
+
+/// Summary of the games as a JSON object
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let points: Vec<f64> = self.results.iter().map(|r| r.points as f64).collect();
+        let ticks: Vec<f64> = self.results.iter().map(|r| r.ticks as f64).collect();
+        let causes: Vec<String> = self
+            .get_death_causes()
+            .iter()
+            .map(|(cause, count)| format!("\"{}\": {}", cause, count))
+            .collect();
+
+        writeln!(f, "{{")?;
+        writeln!(f, "  \"strategy\": \"{}\",", self.strategy.get_name())?;
+        writeln!(f, "  \"games\": {},", self.results.len())?;
+        writeln!(f, "  \"points\": {},", Statistics::of(&points))?;
+        writeln!(f, "  \"survival_ticks\": {},", Statistics::of(&ticks))?;
+        writeln!(f, "  \"survivors\": {},", self.get_survivors())?;
+        writeln!(f, "  \"deaths\": {{{}}}", causes.join(", "))?;
+        writeln!(f, "}}")
+    }
+}
+
+/// Bytes written to the terminal while a game has been painted
+#[derive(Debug, PartialEq)]
+pub struct RenderingReport {
+    pub width: usize,
+    pub height: usize,
+    pub ticks: u64,
+    /// Bytes written by painting every cell of every frame
+    pub repainted: usize,
+    /// Bytes written by painting only the cells that have changed
+    pub diffed: usize,
+}
+
+impl Display for RenderingReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let per_tick = |bytes: usize| bytes as f64 / self.ticks.max(1) as f64;
+
+        writeln!(f, "{{")?;
+        writeln!(f, "  \"width\": {},", self.width)?;
+        writeln!(f, "  \"height\": {},", self.height)?;
+        writeln!(f, "  \"ticks\": {},", self.ticks)?;
+        writeln!(
+            f,
+            "  \"bytes_per_tick\": {{\"repaint\": {:.2}, \"diff\": {:.2}}}",
+            per_tick(self.repainted),
+            per_tick(self.diffed)
+        )?;
+        writeln!(f, "}}")
+    }
+}
+
+/// Mean, median and extremes of some values
+#[derive(Debug, PartialEq)]
+pub struct Statistics {
+    pub mean: f64,
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Statistics {
+    pub fn of(values: &[f64]) -> Statistics {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).expect("Not a number"));
+        let median = match sorted.len() {
+            0 => 0.0,
+            len if len % 2 == 0 => (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0,
+            len => sorted[len / 2],
+        };
+        Statistics {
+            mean: sorted.iter().sum::<f64>() / sorted.len().max(1) as f64,
+            median,
+            min: sorted.first().copied().unwrap_or(0.0),
+            max: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{\"mean\": {:.2}, \"median\": {}, \"min\": {}, \"max\": {}}}",
+            self.mean, self.median, self.min, self.max
+        )
+    }
+}
+
+#[cfg(test)]
+mod headless_test;
@@ -1,88 +1,314 @@
+pub mod cli;
+mod headless;
+mod network;
+mod playback;
+mod render;
+mod replay;
+mod save;
 mod snake_game;
 mod traits;
 mod update_reason;
 mod events;
-use snake_game::game::{
-    direction::{Directed, Direction},
-    draw_instruction::Symbol,
-};
-use std::io::Write;
+use cli::{CliError, Options};
+use network::client::{Connection, TerminalClient};
+use network::race::RaceClient;
+use network::stream::Broadcaster;
+use signal_hook::consts::SIGSTOP;
+use render::Renderer;
+use playback::TerminalPlayback;
+use replay::{FinalScore, Player, Recorder, Replay};
+use save::{Save, SaveError};
+use snake_game::config::Config;
+use snake_game::game::direction::Direction;
+use snake_game::speed::Difficulty;
+use std::error::Error;
+use std::io::Stdout;
+use std::path::{Path, PathBuf};
 use termion::event::Key;
-use termion::raw::IntoRawMode;
+use termion::raw::{IntoRawMode, RawTerminal};
 use traits::*;
 use update_reason::{Control, UpdateReason};
 
 pub struct TerminalSnakeGame {
     snake_game: Box<dyn SnakeGame>,
+    renderer: Renderer,
+    recording: Option<(PathBuf, Recorder)>,
+    /// Whether the WASD keys steer the second player
+    versus: bool,
+    /// Settings the game has been created with, they go into the save
+    config: Config,
+    /// Result of the last save, it is reported after leaving the game
+    last_save: Option<Result<PathBuf, SaveError>>,
+    /// Streams the game to the viewers if it is watched from other terminals
+    broadcaster: Option<Broadcaster>,
+    /// Whether a bot steers the first snake, its games aren't saved
+    bot: bool,
 }
 
 impl TerminalSnakeGame {
-    pub fn new() -> TerminalSnakeGame {
-        let size: (usize, usize) = match termion::terminal_size() {
-            Ok((w, h)) => (w as usize, h as usize),
-            Err(_) => (50, 50),
+    pub fn new(mut options: Options) -> Result<TerminalSnakeGame, CliError> {
+        let renderer = options.get_renderer()?;
+        let broadcaster = match options.stream.take() {
+            Some(address) => Some(Broadcaster::bind(&address)?),
+            None => None,
         };
+        let bot = options.get_bot()?;
+        if options.resume {
+            return Self::resume(options, renderer, broadcaster);
+        }
+        let record_path = options.record.take();
+        let config = options.into_config(get_terminal_size())?;
+        let has_bot = bot.is_some();
+        // the games of a bot don't get into the high scores of the players
+        let snake_game = match bot {
+            Some(bot) => {
+                snake_game::SnakeGame::new_unranked(&config).with_controller(0, Box::new(bot))
+            }
+            None => snake_game::SnakeGame::new(&config),
+        };
+
+        Ok(TerminalSnakeGame {
+            snake_game: Box::new(snake_game),
+            renderer,
+            recording: record_path.map(|path| (path, Recorder::new(&config))),
+            versus: config.versus && config.opponent.is_none(),
+            config,
+            last_save: None,
+            broadcaster,
+            bot: has_bot,
+        })
+    }
+
+    /// Continues the game saved on the last quit, the save is removed so it is continued only once
+    fn resume(
+        options: Options,
+        renderer: Renderer,
+        broadcaster: Option<Broadcaster>,
+    ) -> Result<TerminalSnakeGame, CliError> {
+        let path = Save::default_path().ok_or(SaveError::NoLocation)?;
+        let save = options.into_save(&path, get_terminal_size())?;
+        let snake_game = save.restore()?;
+        // A read-only data directory shouldn't stop the game
+        let _ = std::fs::remove_file(&path);
+
+        Ok(TerminalSnakeGame {
+            snake_game: Box::new(snake_game),
+            renderer,
+            recording: None,
+            versus: save.config.versus && save.config.opponent.is_none(),
+            config: save.config,
+            last_save: None,
+            broadcaster,
+            bot: false,
+        })
+    }
+
+    /// Plays back the replay in the given file instead of a live game
+    pub fn play_back(path: &Path, options: &Options) -> Result<FinalScore, Box<dyn Error>> {
+        let renderer = options.get_renderer()?;
+        let replay = Replay::load(path)?;
+
+        let terminal_size = get_terminal_size();
+        let (width, height) = (replay.config.width, replay.config.height);
+        if width > terminal_size.0 || height + snake_game::HUD_HEIGHT > terminal_size.1 {
+            return Err(Box::new(CliError::BoardTooLarge {
+                width,
+                height,
+                terminal_size,
+            }));
+        }
+
+        let mut playback = TerminalPlayback::new(
+            Player::new(replay),
+            renderer,
+            options.replay_speed.unwrap_or(1.0),
+        );
+        Ok(playback.main()?)
+    }
 
-        TerminalSnakeGame {
-            snake_game: Box::new(snake_game::SnakeGame::new(size)),
+    /// Joins the online match or race hosted on the given address instead of a local game
+    pub fn join(address: &str, options: &Options) -> Result<(), Box<dyn Error>> {
+        let renderer = options.get_renderer()?;
+        let connection = Connection::connect(address)?;
+        match connection.get_race().cloned() {
+            Some(config) => Ok(RaceClient::new(connection, renderer, &config).main()?),
+            None => Ok(TerminalClient::new(connection, renderer).main()?),
         }
     }
 
+    /// Watches the game streamed or the match hosted on the given address, without playing
+    pub fn watch(address: &str, options: &Options) -> Result<(), Box<dyn Error>> {
+        let renderer = options.get_renderer()?;
+        let connection = Connection::watch(address)?;
+        Ok(TerminalClient::new(connection, renderer).main()?)
+    }
+
     pub fn main(&mut self) {
-        let event_stream = events::TerminalEventStream::start();
+        let tick_interval = TickInterval::new(self.snake_game.get_tick_interval());
+        let event_stream = events::TerminalEventStream::start(tick_interval.clone());
+        if let Some(Ok(address)) = self.broadcaster.as_ref().map(Broadcaster::local_addr) {
+            println!("Streaming the game on {}", address);
+        }
 
         let mut stdout = std::io::stdout().into_raw_mode().unwrap();
-        write!(stdout, "{}", termion::cursor::Hide).unwrap();
+        self.renderer.resize(get_terminal_size());
+        self.renderer.enter_screen(&mut stdout);
         loop {
             match event_stream.recv().expect("Channel has stopped.") {
-                Event::Time => self.snake_game.update(UpdateReason::Time),
-                Event::Key(Key::Up) => self
-                    .snake_game
-                    .update(UpdateReason::Control(Control::Turn(Direction::Up))),
-                Event::Key(Key::Down) => self
-                    .snake_game
-                    .update(UpdateReason::Control(Control::Turn(Direction::Down))),
-                Event::Key(Key::Left) => self
-                    .snake_game
-                    .update(UpdateReason::Control(Control::Turn(Direction::Left))),
-                Event::Key(Key::Right) => self
-                    .snake_game
-                    .update(UpdateReason::Control(Control::Turn(Direction::Right))),
-                Event::Key(_) => break,
+                Event::Time => self.update(UpdateReason::Time),
+                Event::Resize(columns, rows) => {
+                    self.renderer.resize((columns, rows));
+                    self.renderer.enter_screen(&mut stdout);
+                }
+                Event::Key(Key::Ctrl('z')) | Event::Suspend => self.suspend(&mut stdout),
+                Event::Key(Key::Ctrl('s')) => self.save(),
+                Event::Key(key) => {
+                    match get_control(key, self.versus, self.snake_game.is_paused()) {
+                        Some(control) => self.update(UpdateReason::Control(control)),
+                        None => continue,
+                    }
+                }
+                Event::FocusLost => self.update(UpdateReason::Control(Control::Pause)),
+                Event::Continue => self.renderer.enter_screen(&mut stdout),
             };
-            let paint = self.snake_game.draw();
-            for instruction in paint {
-                let chars: String = instruction
-                    .shape
-                    .iter()
-                    .map(|directed_symbol| match directed_symbol {
-                        Directed(Direction::Up, Symbol::SnakeHead) => "▲",
-                        Directed(Direction::Left, Symbol::SnakeHead) => "◄",
-                        Directed(Direction::Right, Symbol::SnakeHead) => "►",
-                        Directed(Direction::Down, Symbol::SnakeHead) => "▼",
-                        Directed(_, Symbol::Wall) => "#",
-                        Directed(_, Symbol::SnakeBody) => "#",
-                        Directed(_, Symbol::Empty) => " ",
-                        Directed(_, Symbol::Apple) => "♥",
-                    })
-                    .collect();
-                write!(
-                    stdout,
-                    "{}{}",
-                    termion::cursor::Goto(
-                        instruction.pos.x as u16 + 1,
-                        instruction.pos.y as u16 + 1
-                    ),
-                    chars
-                )
-                .expect("Couldn't write stdout");
+            if self.snake_game.should_quit() {
+                break;
+            }
+            tick_interval.set(self.snake_game.get_tick_interval());
+            let frame = self.snake_game.as_draw().draw();
+            // a round can't go on while it doesn't fit into the terminal
+            let playing = self.snake_game.is_running() && !self.snake_game.is_paused();
+            if playing && !self.renderer.fits(&frame) {
+                self.update(UpdateReason::Control(Control::Pause));
+            }
+            if let Some(broadcaster) = &mut self.broadcaster {
+                broadcaster.publish(&frame);
+            }
+            self.renderer.paint(&mut stdout, frame);
+        }
+
+        self.renderer.leave_screen(&mut stdout);
+        drop(stdout);
+
+        if let Some((path, recorder)) = self.recording.take() {
+            match recorder.finish(self.snake_game.get_score()).save(&path) {
+                Ok(()) => println!("Replay saved to {}", path.display()),
+                Err(error) => eprintln!("Couldn't save the replay: {}", error),
             }
+        }
 
-            write!(stdout, "{}", termion::cursor::Goto(1, 1)).expect("Couldn't write stdout");
-            stdout.flush().expect("Couldn't flush stdout");
+        self.save_on_quit();
+        match &self.last_save {
+            Some(Ok(path)) => println!(
+                "Game saved to {}, continue it with --resume",
+                path.display()
+            ),
+            Some(Err(error)) => eprintln!("Couldn't save the game: {}", error),
+            None => (),
         }
 
-        write!(stdout, "{}{}", termion::cursor::Show, termion::clear::All).unwrap();
-        stdout.flush().expect("Couldn't flush stdout");
+        let score = self.snake_game.get_score();
+        println!(
+            "Score: {} (best: {}), seed: {}",
+            score.points,
+            score.best,
+            self.snake_game.get_seed()
+        );
+
+        let high_scores = self.snake_game.get_high_scores();
+        if !high_scores.is_empty() {
+            println!("High scores:");
+            for (rank, entry) in high_scores.iter().enumerate() {
+                println!(
+                    "{:>3}. {:>5} points, length {}, {} ticks",
+                    rank + 1,
+                    entry.points,
+                    entry.length,
+                    entry.ticks
+                );
+            }
+        }
+    }
+
+    /// Forwards the update to the game, recording it if needed
+    fn update(&mut self, reason: UpdateReason) {
+        if let Some((_, recorder)) = &mut self.recording {
+            recorder.record(&reason);
+        }
+        self.snake_game.update(reason);
+    }
+
+    /// Pauses the game and saves it, so it can be continued with `--resume`
+    fn save(&mut self) {
+        self.update(UpdateReason::Control(Control::Pause));
+        if self.snake_game.is_running() {
+            self.last_save = Some(self.save_game());
+        }
+    }
+
+    /// Saves a round left in the middle, so it can be continued later; a bot's round isn't saved
+    fn save_on_quit(&mut self) {
+        if self.snake_game.is_running() && !self.bot {
+            self.last_save = Some(self.save_game());
+        }
+    }
+
+    fn save_game(&self) -> Result<PathBuf, SaveError> {
+        if self.bot {
+            return Err(SaveError::BotGame);
+        }
+        let path = Save::default_path().ok_or(SaveError::NoLocation)?;
+        Save::new(&self.config, self.snake_game.as_ref()).save(&path)?;
+        Ok(path)
+    }
+
+    /// Pauses the game and stops the process until it gets continued by the shell
+    fn suspend(&mut self, stdout: &mut RawTerminal<Stdout>) {
+        self.update(UpdateReason::Control(Control::Pause));
+
+        self.renderer.leave_screen(stdout);
+        stdout.suspend_raw_mode().expect("Couldn't leave raw mode");
+        // SIGTSTP is handled by us, so we stop the old-fashioned way
+        signal_hook::low_level::raise(SIGSTOP).expect("Couldn't stop the process");
+
+        // We get here after SIGCONT
+        stdout.activate_raw_mode().expect("Couldn't enter raw mode");
+        self.renderer.enter_screen(stdout);
+    }
+}
+
+/// Returns the control of a key in a game played in a terminal, WASD steer the second snake
+/// of a versus match on a shared keyboard
+pub(super) fn get_control(key: Key, versus: bool, paused: bool) -> Option<Control> {
+    match key {
+        Key::Up => Some(Control::Turn(0, Direction::Up)),
+        Key::Down => Some(Control::Turn(0, Direction::Down)),
+        Key::Left => Some(Control::Turn(0, Direction::Left)),
+        Key::Right => Some(Control::Turn(0, Direction::Right)),
+        Key::Char('w') if versus => Some(Control::Turn(1, Direction::Up)),
+        Key::Char('s') if versus => Some(Control::Turn(1, Direction::Down)),
+        Key::Char('a') if versus => Some(Control::Turn(1, Direction::Left)),
+        Key::Char('d') if versus => Some(Control::Turn(1, Direction::Right)),
+        Key::Char('\n') | Key::Char(' ') => Some(Control::Start),
+        Key::Char('1') => Some(Control::SetDifficulty(Difficulty::Easy)),
+        Key::Char('2') => Some(Control::SetDifficulty(Difficulty::Normal)),
+        Key::Char('3') => Some(Control::SetDifficulty(Difficulty::Hard)),
+        Key::Char('4') => Some(Control::SetDifficulty(Difficulty::Insane)),
+        Key::Char('a') => Some(Control::ToggleAcceleration),
+        Key::Char('p') if paused => Some(Control::Resume),
+        Key::Char('p') => Some(Control::Pause),
+        Key::Char('q') | Key::Esc | Key::Ctrl('c') => Some(Control::Quit),
+        _ => None,
+    }
+}
+
+/// Returns the size of the terminal in cells
+fn get_terminal_size() -> (usize, usize) {
+    match termion::terminal_size() {
+        Ok((w, h)) => (w as usize, h as usize),
+        Err(_) => (50, 50),
     }
 }
+
+#[cfg(test)]
+mod terminal_snake_game_test;
@@ -0,0 +1,270 @@
+use super::super::events::TerminalEventStream;
+use super::super::get_terminal_size;
+use super::super::render::Renderer;
+use super::super::snake_game::config::Config;
+use super::super::snake_game::game::direction::Direction;
+use super::super::snake_game::game::draw_instruction::DrawInstruction;
+use super::super::snake_game::game::traits::Draw;
+use super::super::snake_game::game::vec2::Vec2;
+use super::super::snake_game::speed::Difficulty;
+use super::super::traits::{Event, EventStream, TickInterval};
+use super::super::update_reason::Control;
+use super::protocol::{ClientMessage, Framer, Role, ServerMessage, VERSION};
+use super::socket::Socket;
+use super::NetworkError;
+use std::io;
+use std::io::{Read, Stdout, Write};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+use termion::event::Key;
+use termion::raw::{IntoRawMode, RawTerminal};
+
+/// Longest wait for the answer to the hello
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time between two pings that measure the latency
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest wait for a terminal event before looking at the messages of the server
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A connection to a server, the messages of the server are read on a thread of their own
+pub struct Connection {
+    stream: Socket,
+    messages: Receiver<Result<ServerMessage, NetworkError>>,
+    role: Role,
+    /// Settings of the race, `None` if the server hosts a match on a shared board
+    race: Option<Config>,
+}
+
+impl Connection {
+    /// Connects to the server and joins the game
+    pub fn connect(address: &str) -> Result<Connection, NetworkError> {
+        Self::open(address, ClientMessage::Hello(VERSION))
+    }
+
+    /// Connects to the server only to watch the game
+    pub fn watch(address: &str) -> Result<Connection, NetworkError> {
+        Self::open(address, ClientMessage::Watch(VERSION))
+    }
+
+    fn open(address: &str, hello: ClientMessage) -> Result<Connection, NetworkError> {
+        let stream = Socket::connect(address)?;
+        let (tx, messages) = mpsc::channel();
+        let reader = stream.try_clone()?;
+        std::thread::spawn(move || {
+            if let Err(error) = Self::read_messages(reader, &tx) {
+                let _ = tx.send(Err(error));
+            }
+        });
+
+        let mut connection = Connection {
+            stream,
+            messages,
+            role: Role::Spectator,
+            race: None,
+        };
+        connection.send(&hello)?;
+        match connection.receive(HANDSHAKE_TIMEOUT)? {
+            Some(ServerMessage::Welcome(_)) | Some(ServerMessage::Race { .. }) => Ok(connection),
+            Some(ServerMessage::Refused(reason)) => Err(NetworkError::Refused(reason)),
+            Some(_) => Err(NetworkError::protocol("Expected a welcome")),
+            None => Err(NetworkError::Io(io::ErrorKind::TimedOut)),
+        }
+    }
+
+    /// Forwards the messages of the server until the connection is closed
+    fn read_messages(
+        mut stream: Socket,
+        tx: &Sender<Result<ServerMessage, NetworkError>>,
+    ) -> Result<(), NetworkError> {
+        let mut framer = Framer::default();
+        let mut buffer = [0; 4096];
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) => return Err(NetworkError::Disconnected),
+                Ok(count) => framer.push(&buffer[..count]),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+            while let Some(frame) = framer.next_frame()? {
+                if tx.send(Ok(ServerMessage::decode(&frame)?)).is_err() {
+                    // nobody listens anymore
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns the role the server has given to this client
+    pub fn get_role(&self) -> Role {
+        self.role
+    }
+
+    /// Returns the settings of the race if the server coordinates one
+    pub fn get_race(&self) -> Option<&Config> {
+        self.race.as_ref()
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> Result<(), NetworkError> {
+        self.stream.write_all(&message.encode())?;
+        Ok(())
+    }
+
+    /// Returns the next message of the server, it is waited for at most the given time
+    pub fn receive(&mut self, timeout: Duration) -> Result<Option<ServerMessage>, NetworkError> {
+        match self.messages.recv_timeout(timeout) {
+            Ok(Ok(message)) => {
+                match &message {
+                    ServerMessage::Welcome(role) => self.role = *role,
+                    ServerMessage::Race { player, config } => {
+                        self.role = Role::Player(*player);
+                        self.race = Some(config.clone());
+                    }
+                    _ => (),
+                }
+                Ok(Some(message))
+            }
+            Ok(Err(error)) => Err(error),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(NetworkError::Disconnected),
+        }
+    }
+}
+
+/// The reading thread has a handle of the socket too, so it has to be closed explicitly
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown();
+    }
+}
+
+/// Shows an online game in the terminal and sends the controls of the player to the server
+pub struct TerminalClient {
+    connection: Connection,
+    renderer: Renderer,
+    /// The newest board sent by the server
+    board: Vec<DrawInstruction>,
+    /// Round trip time of the last ping
+    latency: Option<Duration>,
+    /// Start of the clock of the pings
+    started: Instant,
+    quit: bool,
+}
+
+impl TerminalClient {
+    pub fn new(connection: Connection, renderer: Renderer) -> TerminalClient {
+        TerminalClient {
+            connection,
+            renderer,
+            board: Vec::new(),
+            latency: None,
+            started: Instant::now(),
+            quit: false,
+        }
+    }
+
+    /// Plays until the user leaves or the server closes the connection
+    pub fn main(&mut self) -> Result<(), NetworkError> {
+        let event_stream = TerminalEventStream::start(TickInterval::new(PING_INTERVAL));
+
+        let mut stdout = std::io::stdout().into_raw_mode().unwrap();
+        self.renderer.resize(get_terminal_size());
+        self.renderer.enter_screen(&mut stdout);
+        let result = self.play(&event_stream, &mut stdout);
+        self.renderer.leave_screen(&mut stdout);
+
+        result
+    }
+
+    fn play(
+        &mut self,
+        event_stream: &Receiver<Event>,
+        stdout: &mut RawTerminal<Stdout>,
+    ) -> Result<(), NetworkError> {
+        self.ping()?;
+        loop {
+            match event_stream.recv_timeout(POLL_INTERVAL) {
+                Ok(Event::Key(key)) => self.control(key)?,
+                Ok(Event::Time) => self.ping()?,
+                Ok(Event::Resize(columns, rows)) => {
+                    self.renderer.resize((columns, rows));
+                    self.renderer.enter_screen(stdout);
+                }
+                Ok(Event::Continue) => self.renderer.enter_screen(stdout),
+                Ok(Event::FocusLost) | Ok(Event::Suspend) | Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => panic!("Channel has stopped."),
+            }
+            if self.quit {
+                return self.connection.send(&ClientMessage::Bye);
+            }
+
+            while let Some(message) = self.connection.receive(Duration::from_secs(0))? {
+                match message {
+                    ServerMessage::Frame { instructions, .. } => self.board = instructions,
+                    ServerMessage::Pong(sent) => {
+                        let sent = Duration::from_micros(sent);
+                        self.latency = Some(self.started.elapsed().saturating_sub(sent));
+                    }
+                    ServerMessage::Welcome(_)
+                    | ServerMessage::Refused(_)
+                    | ServerMessage::Race { .. }
+                    | ServerMessage::Standings(_) => (),
+                }
+            }
+            self.renderer.paint(stdout, self.draw());
+        }
+    }
+
+    fn ping(&mut self) -> Result<(), NetworkError> {
+        let now = self.started.elapsed().as_micros() as u64;
+        self.connection.send(&ClientMessage::Ping(now))
+    }
+
+    fn control(&mut self, key: Key) -> Result<(), NetworkError> {
+        let control = match key {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => {
+                self.quit = true;
+                return Ok(());
+            }
+            // spectators can only leave
+            _ if self.connection.get_role() == Role::Spectator => return Ok(()),
+            Key::Up => Control::Turn(0, Direction::Up),
+            Key::Down => Control::Turn(0, Direction::Down),
+            Key::Left => Control::Turn(0, Direction::Left),
+            Key::Right => Control::Turn(0, Direction::Right),
+            Key::Char('\n') | Key::Char(' ') => Control::Start,
+            Key::Char('p') => Control::Pause,
+            Key::Char('1') => Control::SetDifficulty(Difficulty::Easy),
+            Key::Char('2') => Control::SetDifficulty(Difficulty::Normal),
+            Key::Char('3') => Control::SetDifficulty(Difficulty::Hard),
+            Key::Char('4') => Control::SetDifficulty(Difficulty::Insane),
+            Key::Char('a') => Control::ToggleAcceleration,
+            _ => return Ok(()),
+        };
+        self.connection.send(&ClientMessage::Input(control))
+    }
+
+    fn get_status(&self) -> String {
+        let role = match self.connection.get_role() {
+            Role::Player(player) => format!("player {}", player + 1),
+            Role::Spectator => "spectator".to_string(),
+        };
+        match self.latency {
+            Some(latency) => format!(" ONLINE {} {} ms ", role, latency.as_millis()),
+            None => format!(" ONLINE {} ", role),
+        }
+    }
+}
+
+impl Draw for TerminalClient {
+    fn draw(&self) -> Vec<DrawInstruction> {
+        let mut paint = self.board.clone();
+
+        // the status goes over the top border, cut to the width of the board
+        let width = paint.first().map_or(0, |row| row.shape.len());
+        let status: String = self.get_status().chars().take(width).collect();
+        paint.push(DrawInstruction::text(Vec2 { x: 0, y: 0 }, &status));
+
+        paint
+    }
+}
@@ -0,0 +1,57 @@
+pub mod client;
+pub mod protocol;
+pub mod race;
+pub mod server;
+pub mod socket;
+pub mod stream;
+pub mod telnet;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+/// # NetworkError
+#[derive(Debug, PartialEq)]
+pub enum NetworkError {
+    Io(io::ErrorKind),
+    /// The other side has sent something that isn't a valid message
+    Protocol(String),
+    /// The server has turned the client away
+    Refused(String),
+    /// The other side has closed the connection
+    Disconnected,
+}
+
+impl NetworkError {
+    fn protocol(message: &str) -> NetworkError {
+        NetworkError::Protocol(message.to_string())
+    }
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe => Self::Disconnected,
+            kind => Self::Io(kind),
+        }
+    }
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(kind) => write!(f, "{}", io::Error::from(*kind)),
+            Self::Protocol(message) => write!(f, "Invalid message: {}", message),
+            Self::Refused(reason) => write!(f, "The server has refused to let us in: {}", reason),
+            Self::Disconnected => write!(f, "The connection has been closed"),
+        }
+    }
+}
+
+impl Error for NetworkError {}
+
+#[cfg(test)]
+mod network_test;
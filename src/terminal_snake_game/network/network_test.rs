@@ -0,0 +1,705 @@
+use super::super::render::theme::{ColorSupport, Theme};
+use super::super::render::Renderer;
+use super::super::snake_game::config::Config;
+use super::super::snake_game::game::direction::{Directed, Direction};
+use super::super::snake_game::game::draw_instruction::{DrawInstruction, Symbol};
+use super::super::snake_game::game::power_up::PowerUp;
+use super::super::snake_game::game::vec2::Vec2;
+use super::super::snake_game::high_scores::HighScores;
+use super::super::traits::Event;
+use super::super::update_reason::Control;
+use super::client::Connection;
+use super::protocol::*;
+use super::race::{get_sidebar, Coordinator};
+use super::server::Server;
+use super::stream::Broadcaster;
+use super::telnet::{TelnetDecoder, TelnetServer, GREETING};
+use super::*;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+use termion::event::Key;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn server() -> Server {
+    let config = Config {
+        versus: true,
+        online: true,
+        seed: 5,
+        ..Config::new(30, 12)
+    };
+    Server::bind("127.0.0.1:0", &config).unwrap()
+}
+
+fn race() -> Coordinator {
+    let config = Config {
+        seed: 8,
+        ..Config::new(30, 12)
+    };
+    Coordinator::bind("127.0.0.1:0", &config).unwrap()
+}
+
+/// Connects a client while the server handles the handshake, the client only watches if asked
+fn connect(address: String, watch: bool, mut poll: impl FnMut()) -> Connection {
+    let joining = thread::spawn(move || match watch {
+        true => Connection::watch(&address),
+        false => Connection::connect(&address),
+    });
+    let deadline = Instant::now() + TIMEOUT;
+    while !joining.is_finished() && Instant::now() < deadline {
+        poll();
+        thread::sleep(Duration::from_millis(1));
+    }
+    joining.join().unwrap().unwrap()
+}
+
+fn join(server: &mut Server) -> Connection {
+    connect(server.local_addr().unwrap(), false, || server.poll())
+}
+
+fn join_race(coordinator: &mut Coordinator) -> Connection {
+    connect(coordinator.local_addr().unwrap(), false, || {
+        coordinator.poll()
+    })
+}
+
+/// Returns the next message of the server to the client
+fn receive(server: &mut Server, client: &mut Connection) -> ServerMessage {
+    let deadline = Instant::now() + TIMEOUT;
+    while Instant::now() < deadline {
+        server.poll();
+        if let Some(message) = client.receive(Duration::from_millis(1)).unwrap() {
+            return message;
+        }
+    }
+    panic!("No message from the server");
+}
+
+/// Returns the newest board the client has got
+fn get_board(server: &mut Server, client: &mut Connection) -> Vec<DrawInstruction> {
+    receive_board(client, || server.poll())
+}
+
+fn receive_board(client: &mut Connection, mut poll: impl FnMut()) -> Vec<DrawInstruction> {
+    let mut board = None;
+    let deadline = Instant::now() + TIMEOUT;
+    while Instant::now() < deadline {
+        poll();
+        match client.receive(Duration::from_millis(5)).unwrap() {
+            Some(ServerMessage::Frame { instructions, .. }) => board = Some(instructions),
+            Some(_) => (),
+            None if board.is_some() => break,
+            None => (),
+        }
+    }
+    board.expect("No board from the server")
+}
+
+/// Returns the newest standings the racer has got
+fn get_standings(coordinator: &mut Coordinator, client: &mut Connection) -> Vec<(usize, Standing)> {
+    let mut standings = None;
+    let deadline = Instant::now() + TIMEOUT;
+    while Instant::now() < deadline {
+        coordinator.poll();
+        match client.receive(Duration::from_millis(5)).unwrap() {
+            Some(ServerMessage::Standings(newest)) => standings = Some(newest),
+            Some(_) => (),
+            None if standings.is_some() => break,
+            None => (),
+        }
+    }
+    standings.expect("No standings from the coordinator")
+}
+
+fn get_text(board: &[DrawInstruction]) -> String {
+    board
+        .iter()
+        .flat_map(|instruction| &instruction.shape)
+        .filter_map(|Directed(_, symbol)| match symbol {
+            Symbol::Text(c) => Some(*c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn get_head(board: &[DrawInstruction], player: usize) -> Direction {
+    board
+        .iter()
+        .flat_map(|instruction| &instruction.shape)
+        .find_map(|Directed(direction, symbol)| match symbol {
+            Symbol::SnakeHead(head) if *head == player => Some(direction.clone()),
+            _ => None,
+        })
+        .expect("No head on the board")
+}
+
+#[test]
+fn it_can_encode_and_decode_every_message() {
+    let symbols = vec![
+        Symbol::Empty,
+        Symbol::Wall,
+        Symbol::Apple,
+        Symbol::GoldenApple,
+        Symbol::ShrinkingFood,
+        Symbol::SpeedUp,
+        Symbol::SlowDown,
+        Symbol::BonusFood,
+        Symbol::Poison,
+        Symbol::PowerUp(PowerUp::Magnet),
+        Symbol::Gate { open: true },
+        Symbol::Portal('7'),
+        Symbol::Patrol,
+        Symbol::SnakeBody(1),
+        Symbol::SnakeHead(0),
+        Symbol::Text('é'),
+    ];
+    let server_messages = vec![
+        ServerMessage::Welcome(Role::Player(1)),
+        ServerMessage::Welcome(Role::Spectator),
+        ServerMessage::Refused("full".to_string()),
+        ServerMessage::Frame {
+            tick: 42,
+            instructions: vec![DrawInstruction {
+                pos: Vec2 { x: 3, y: 9 },
+                shape: symbols
+                    .into_iter()
+                    .map(|symbol| Directed(Direction::Left, symbol))
+                    .collect(),
+            }],
+        },
+        ServerMessage::Pong(7),
+        ServerMessage::Race {
+            player: 300,
+            config: Config {
+                wrap: true,
+                food_count: 3,
+                ..Config::new(40, 16)
+            },
+        },
+        ServerMessage::Standings(vec![
+            (0, Standing::default()),
+            (
+                1000,
+                Standing {
+                    points: 120,
+                    length: 14,
+                    dead: true,
+                },
+            ),
+        ]),
+    ];
+    let client_messages = vec![
+        ClientMessage::Hello(VERSION),
+        ClientMessage::Watch(VERSION),
+        ClientMessage::Input(Control::Turn(0, Direction::Down)),
+        ClientMessage::Input(Control::Start),
+        ClientMessage::Ping(u64::MAX),
+        ClientMessage::Bye,
+        ClientMessage::Report(Standing {
+            points: 7,
+            length: 4,
+            dead: false,
+        }),
+    ];
+
+    for message in server_messages {
+        let encoded = message.encode();
+        assert_eq!(ServerMessage::decode(&encoded[4..]), Ok(message));
+    }
+    for message in client_messages {
+        let encoded = message.encode();
+        assert_eq!(ClientMessage::decode(&encoded[4..]), Ok(message));
+    }
+}
+
+#[test]
+fn it_cuts_the_stream_into_frames() {
+    let mut bytes = ClientMessage::Ping(1).encode();
+    bytes.extend(ClientMessage::Bye.encode());
+    let mut framer = Framer::default();
+
+    framer.push(&bytes[..6]);
+    assert_eq!(framer.next_frame(), Ok(None));
+    framer.push(&bytes[6..]);
+    assert_eq!(framer.next_frame(), Ok(Some(bytes[4..13].to_vec())));
+    assert_eq!(framer.next_frame(), Ok(Some(vec![3])));
+    assert_eq!(framer.next_frame(), Ok(None));
+
+    framer.push(&[0, 0, 0, 0]);
+    assert!(framer.next_frame().is_err());
+}
+
+#[test]
+fn it_reports_invalid_messages() {
+    let invalid = |bytes: &[u8]| ClientMessage::decode(bytes).is_err();
+
+    assert!(invalid(&[0, b'S', b'N', b'A', b'X', 1]));
+    assert!(invalid(&[9]));
+    assert!(invalid(&[3, 0]));
+    assert!(invalid(&[2, 0, 0]));
+    assert!(invalid(b"\x01quit"));
+    assert!(invalid(b"\x01time"));
+    assert!(ServerMessage::decode(&[2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1]).is_err());
+}
+
+#[test]
+fn it_refuses_races_that_cant_be_played() {
+    let race = |config: &[u8]| ServerMessage::decode(&[&[4, 0, 0, 0, 0], config].concat());
+
+    assert!(race(b"config 30 20 1 normal off\n").is_ok());
+    assert!(matches!(
+        race(b"config 2 2 1 normal off\n"),
+        Err(NetworkError::Protocol(_))
+    ));
+}
+
+#[test]
+fn it_gives_the_snakes_to_the_first_clients() {
+    let mut server = server();
+
+    let first = join(&mut server);
+    let second = join(&mut server);
+    let mut third = join(&mut server);
+
+    assert_eq!(first.get_role(), Role::Player(0));
+    assert_eq!(second.get_role(), Role::Player(1));
+    assert_eq!(third.get_role(), Role::Spectator);
+    // a late joiner gets the board at once
+    assert!(get_text(&get_board(&mut server, &mut third)).contains("RUST SNAKE"));
+}
+
+#[test]
+fn it_starts_only_when_every_snake_has_a_player() {
+    let mut server = server();
+    let mut first = join(&mut server);
+
+    first.send(&ClientMessage::Input(Control::Start)).unwrap();
+    server.poll();
+    server.tick();
+    assert!(get_text(&get_board(&mut server, &mut first)).contains("RUST SNAKE"));
+
+    let _second = join(&mut server);
+    first.send(&ClientMessage::Input(Control::Start)).unwrap();
+    let board = get_board(&mut server, &mut first);
+    assert!(!get_text(&board).contains("RUST SNAKE"));
+}
+
+#[test]
+fn it_applies_the_turns_of_the_players_on_the_ticks() {
+    let mut server = server();
+    let mut first = join(&mut server);
+    let mut second = join(&mut server);
+    first.send(&ClientMessage::Input(Control::Start)).unwrap();
+    let heading = get_head(&get_board(&mut server, &mut second), 1);
+    let turn = match heading {
+        Direction::Left | Direction::Right => Direction::Up,
+        Direction::Up | Direction::Down => Direction::Left,
+    };
+
+    second
+        .send(&ClientMessage::Input(Control::Turn(0, turn.clone())))
+        .unwrap();
+    // the turn waits for the tick, the board doesn't change until then
+    let deadline = Instant::now() + Duration::from_millis(50);
+    while Instant::now() < deadline {
+        server.poll();
+        assert_eq!(second.receive(Duration::from_millis(1)), Ok(None));
+    }
+
+    server.tick();
+    let board = get_board(&mut server, &mut first);
+    assert_eq!(get_head(&board, 1), turn);
+}
+
+#[test]
+fn it_answers_pings() {
+    let mut server = server();
+    let mut client = join(&mut server);
+    let _board = get_board(&mut server, &mut client);
+
+    client.send(&ClientMessage::Ping(99)).unwrap();
+
+    assert_eq!(receive(&mut server, &mut client), ServerMessage::Pong(99));
+}
+
+#[test]
+fn it_gives_the_snake_of_a_leaving_player_to_a_spectator() {
+    let mut server = server();
+    let mut first = join(&mut server);
+    let second = join(&mut server);
+    let mut third = join(&mut server);
+    first.send(&ClientMessage::Input(Control::Start)).unwrap();
+    let _board = get_board(&mut server, &mut first);
+    let _board = get_board(&mut server, &mut third);
+
+    drop(second);
+
+    assert_eq!(
+        receive(&mut server, &mut third),
+        ServerMessage::Welcome(Role::Player(1))
+    );
+    assert_eq!(third.get_role(), Role::Player(1));
+    assert!(get_text(&get_board(&mut server, &mut first)).contains("PAUSED"));
+    let notices = server.take_notices();
+    assert!(notices
+        .iter()
+        .any(|notice| notice.contains("(player 2) has left")));
+
+    // the pause toggles
+    third.send(&ClientMessage::Input(Control::Pause)).unwrap();
+    assert!(!get_text(&get_board(&mut server, &mut first)).contains("PAUSED"));
+}
+
+#[test]
+fn it_refuses_clients_of_other_versions() {
+    let mut server = server();
+    let address = server.local_addr().unwrap();
+    let talking = thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .write_all(&ClientMessage::Hello(VERSION + 1).encode())
+            .unwrap();
+        let mut answer = Vec::new();
+        stream.read_to_end(&mut answer).unwrap();
+        answer
+    });
+    let deadline = Instant::now() + TIMEOUT;
+    while !talking.is_finished() && Instant::now() < deadline {
+        server.poll();
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let answer = talking.join().unwrap();
+    assert_eq!(
+        ServerMessage::decode(&answer[4..]),
+        Ok(ServerMessage::Refused(format!(
+            "the server speaks version {}",
+            VERSION
+        )))
+    );
+}
+
+#[test]
+fn it_reports_a_closed_connection() {
+    let mut server = server();
+    let mut client = join(&mut server);
+
+    drop(server);
+
+    let deadline = Instant::now() + TIMEOUT;
+    let error = loop {
+        match client.receive(Duration::from_millis(10)) {
+            Err(error) => break error,
+            Ok(_) if Instant::now() < deadline => continue,
+            Ok(_) => panic!("The connection is still open"),
+        }
+    };
+    assert_eq!(error, NetworkError::Disconnected);
+}
+
+#[test]
+fn it_gives_every_racer_the_same_settings() {
+    let mut coordinator = race();
+
+    let first = join_race(&mut coordinator);
+    let second = join_race(&mut coordinator);
+
+    assert_eq!(first.get_role(), Role::Player(0));
+    assert_eq!(second.get_role(), Role::Player(1));
+    assert_eq!(first.get_race().map(|config| config.seed), Some(8));
+    assert_eq!(first.get_race(), second.get_race());
+}
+
+#[test]
+fn it_relays_the_standings_of_the_racers() {
+    let mut coordinator = race();
+    let mut first = join_race(&mut coordinator);
+    let mut second = join_race(&mut coordinator);
+    let standing = Standing {
+        points: 30,
+        length: 6,
+        dead: true,
+    };
+
+    first.send(&ClientMessage::Report(standing)).unwrap();
+
+    let expected = vec![(0, standing), (1, Standing::default())];
+    assert_eq!(get_standings(&mut coordinator, &mut second), expected);
+    assert_eq!(get_standings(&mut coordinator, &mut first), expected);
+}
+
+#[test]
+fn it_drops_the_standing_of_a_leaving_racer() {
+    let mut coordinator = race();
+    let first = join_race(&mut coordinator);
+    let mut second = join_race(&mut coordinator);
+    let _standings = get_standings(&mut coordinator, &mut second);
+
+    drop(first);
+
+    assert_eq!(
+        get_standings(&mut coordinator, &mut second),
+        vec![(1, Standing::default())]
+    );
+    let notices = coordinator.take_notices();
+    assert!(notices
+        .iter()
+        .any(|notice| notice.contains("(racer 1) has left")));
+}
+
+#[test]
+fn it_lists_the_best_racer_first() {
+    let standings = vec![
+        (
+            0,
+            Standing {
+                points: 80,
+                length: 9,
+                dead: true,
+            },
+        ),
+        (
+            2,
+            Standing {
+                points: 120,
+                length: 14,
+                dead: false,
+            },
+        ),
+    ];
+
+    assert_eq!(
+        get_sidebar(&standings, 2),
+        vec![
+            "RACE",
+            "",
+            "1. you         120",
+            "   length 14",
+            "2. racer 1      80",
+            "   length 9   dead",
+        ]
+    );
+}
+
+#[test]
+fn it_streams_the_board_to_every_viewer() {
+    let mut broadcaster = Broadcaster::bind("127.0.0.1:0").unwrap();
+    let address = broadcaster.local_addr().unwrap();
+    let board = vec![DrawInstruction::text(Vec2 { x: 0, y: 0 }, "HELLO")];
+
+    let mut first = connect(address.clone(), true, || broadcaster.publish(&board));
+    let mut second = connect(address, false, || broadcaster.publish(&board));
+    let moved = vec![DrawInstruction::text(Vec2 { x: 1, y: 0 }, "HELLO")];
+
+    assert_eq!(first.get_role(), Role::Spectator);
+    assert_eq!(second.get_role(), Role::Spectator);
+    assert_eq!(
+        receive_board(&mut first, || broadcaster.publish(&moved)),
+        moved
+    );
+    assert_eq!(
+        receive_board(&mut second, || broadcaster.publish(&moved)),
+        moved
+    );
+}
+
+#[test]
+fn it_streams_over_unix_domain_sockets() {
+    let path = std::env::temp_dir().join(format!("rust-snake-test-{}.sock", std::process::id()));
+    let address = path.display().to_string();
+    let mut broadcaster = Broadcaster::bind(&address).unwrap();
+    let board = vec![DrawInstruction::text(Vec2 { x: 2, y: 3 }, "WATCHED")];
+
+    let mut viewer = connect(address, true, || broadcaster.publish(&board));
+
+    assert_eq!(
+        receive_board(&mut viewer, || broadcaster.publish(&board)),
+        board
+    );
+    drop(broadcaster);
+    assert!(!path.exists());
+}
+
+#[test]
+fn it_lets_viewers_only_watch_a_match() {
+    let mut server = server();
+    let address = server.local_addr().unwrap();
+
+    let viewer = connect(address, true, || server.poll());
+    let player = join(&mut server);
+
+    assert_eq!(viewer.get_role(), Role::Spectator);
+    assert_eq!(player.get_role(), Role::Player(0));
+}
+
+#[test]
+fn it_never_gives_the_snake_of_a_leaving_player_to_a_viewer() {
+    let mut server = server();
+    let address = server.local_addr().unwrap();
+    let mut first = join(&mut server);
+    let second = join(&mut server);
+    let mut viewer = connect(address, true, || server.poll());
+    let mut spectator = join(&mut server);
+    first.send(&ClientMessage::Input(Control::Start)).unwrap();
+    let _board = get_board(&mut server, &mut first);
+    let _board = get_board(&mut server, &mut spectator);
+
+    // GIVEN a viewer that has come before a spectator
+    // WHEN a player leaves
+    drop(second);
+
+    // THEN the spectator gets the snake and the viewer keeps watching
+    assert_eq!(
+        receive(&mut server, &mut spectator),
+        ServerMessage::Welcome(Role::Player(1))
+    );
+    let _board = get_board(&mut server, &mut viewer);
+    assert_eq!(viewer.get_role(), Role::Spectator);
+}
+
+#[test]
+fn it_refuses_viewers_of_a_race() {
+    let mut coordinator = race();
+    let address = coordinator.local_addr().unwrap();
+
+    let watching = thread::spawn(move || Connection::watch(&address).err());
+    let deadline = Instant::now() + TIMEOUT;
+    while !watching.is_finished() && Instant::now() < deadline {
+        coordinator.poll();
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(
+        watching.join().unwrap(),
+        Some(NetworkError::Refused(
+            "a race can only be joined".to_string()
+        ))
+    );
+}
+
+#[test]
+fn it_decodes_the_keys_of_a_telnet_client() {
+    let mut decoder = TelnetDecoder::default();
+
+    let events = decoder.push(b"\x1b[Aq\r\0p\r\n");
+
+    assert_eq!(
+        events,
+        vec![
+            Event::Key(Key::Up),
+            Event::Key(Key::Char('q')),
+            Event::Key(Key::Char('\n')),
+            Event::Key(Key::Char('p')),
+            Event::Key(Key::Char('\n')),
+        ]
+    );
+}
+
+#[test]
+fn it_waits_for_the_rest_of_an_escape_sequence() {
+    let mut decoder = TelnetDecoder::default();
+
+    assert_eq!(decoder.push(b"\x1b["), vec![]);
+    assert_eq!(decoder.push(b"D"), vec![Event::Key(Key::Left)]);
+    assert_eq!(decoder.push(b"\x1b"), vec![Event::Key(Key::Esc)]);
+}
+
+#[test]
+fn it_reads_the_window_size_of_a_telnet_client() {
+    let mut decoder = TelnetDecoder::default();
+
+    // a width of 255 is escaped by doubling it, the commands may come in pieces
+    let events = decoder.push(&[b'a', 255, 250, 31, 0, 255]);
+    assert_eq!(events, vec![Event::Key(Key::Char('a'))]);
+    let events = decoder.push(&[255, 0, 40, 255, 240, b'b']);
+
+    assert_eq!(
+        events,
+        vec![Event::Resize(255, 40), Event::Key(Key::Char('b'))]
+    );
+}
+
+#[test]
+fn it_gives_up_on_endless_escape_sequences() {
+    let mut decoder = TelnetDecoder::default();
+    let mut bytes = b"\x1b[".to_vec();
+    bytes.extend(std::iter::repeat_n(b'1', 100));
+
+    decoder.push(&bytes);
+
+    assert_eq!(decoder.push(b"q"), vec![Event::Key(Key::Char('q'))]);
+}
+
+#[test]
+fn it_gives_up_on_endless_subnegotiations() {
+    let mut decoder = TelnetDecoder::default();
+    let mut bytes = vec![255, 250, 24];
+    bytes.extend(std::iter::repeat_n(0, 100));
+
+    decoder.push(&bytes);
+
+    // the rest of the subnegotiation is read as keys
+    assert_eq!(decoder.push(b"q"), vec![Event::Key(Key::Char('q'))]);
+    assert_eq!(
+        decoder.push(&[255, 250, 31, 0, 80, 0, 24, 255, 240]),
+        vec![Event::Resize(80, 24)]
+    );
+}
+
+#[test]
+fn it_refuses_the_options_it_hasnt_asked_for() {
+    let mut decoder = TelnetDecoder::default();
+
+    // agreed: echo, no go-ahead, window size; offered: terminal type, go-ahead suppression
+    let events = decoder.push(&[255, 253, 1, 255, 253, 3, 255, 251, 31, 255, 251, 24]);
+    decoder.push(&[255, 251, 3, 255, 253, 34, 255, 252, 24]);
+
+    assert_eq!(events, vec![]);
+    assert_eq!(
+        decoder.take_replies(),
+        vec![255, 254, 24, 255, 253, 3, 255, 252, 34]
+    );
+    assert_eq!(decoder.take_replies(), vec![]);
+}
+
+#[test]
+fn it_hosts_a_game_for_every_telnet_client() {
+    let config = Config {
+        seed: 3,
+        ..Config::new(30, 12)
+    };
+    let renderer = Renderer::new(Theme::default(), ColorSupport::None);
+    let mut server =
+        TelnetServer::bind("127.0.0.1:0", &config, None, renderer, HighScores::new()).unwrap();
+    let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+    client.set_read_timeout(Some(TIMEOUT)).unwrap();
+    server.poll();
+
+    let mut greeting = [0; GREETING.len()];
+    client.read_exact(&mut greeting).unwrap();
+    assert_eq!(greeting, GREETING);
+    let notices = server.take_notices();
+    assert_eq!(notices.len(), 1);
+    assert!(notices[0].contains("has started a game"));
+
+    // the window is large enough for the board, then the player quits
+    client
+        .write_all(&[255, 250, 31, 0, 80, 0, 24, 255, 240])
+        .unwrap();
+    client.write_all(b"q").unwrap();
+    let deadline = Instant::now() + TIMEOUT;
+    let mut notices = Vec::new();
+    while notices.is_empty() && Instant::now() < deadline {
+        server.poll();
+        notices = server.take_notices();
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    assert_eq!(notices.len(), 1);
+    assert!(notices[0].ends_with("has left: left (score 0)"));
+    let mut screen = Vec::new();
+    client.read_to_end(&mut screen).unwrap();
+    assert!(!screen.is_empty());
+}
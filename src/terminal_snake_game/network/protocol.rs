@@ -0,0 +1,461 @@
+use super::super::replay::{parse_setup, write_setup};
+use super::super::snake_game::config::Config;
+use super::super::snake_game::game::direction::{Directed, Direction};
+use super::super::snake_game::game::draw_instruction::{DrawInstruction, Symbol};
+use super::super::snake_game::game::power_up::PowerUp;
+use super::super::snake_game::game::vec2::Vec2;
+use super::super::update_reason::{Control, UpdateReason};
+use super::NetworkError;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// First bytes of the hello of a client
+const MAGIC: &[u8; 4] = b"SNAK";
+/// Version of the protocol, a server only talks to clients of the same version
+pub const VERSION: u8 = 4;
+/// Longest message that is accepted, a frame of a huge board still fits
+const MAX_LENGTH: usize = 1 << 20;
+
+/// What a client does in the game
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Role {
+    /// Steers the snake of the given player
+    Player(usize),
+    /// Only watches the game
+    Spectator,
+}
+
+/// How a racer is doing on their own board
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Standing {
+    pub points: u32,
+    pub length: usize,
+    /// Whether the round is over for the racer
+    pub dead: bool,
+}
+
+/// Messages sent by the clients
+///
+/// Every message is framed by its length as a big-endian u32, then it starts with a tag byte.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientMessage {
+    /// First message of a client, with the version of the protocol it speaks
+    Hello(u8),
+    /// A control of the player, the player of a turn is chosen by the server; a pause toggles
+    Input(Control),
+    /// Asks for a pong with the same number, to measure the latency
+    Ping(u64),
+    /// The client leaves the game
+    Bye,
+    /// How the racer is doing, sent when it changes
+    Report(Standing),
+    /// First message of a client that only watches, instead of the hello
+    Watch(u8),
+}
+
+/// Messages sent by the server, framed the same way as the ones of the clients
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerMessage {
+    /// Answer to the hello, it is sent again when the role of the client changes
+    Welcome(Role),
+    /// The client can't join, the connection is closed after this
+    Refused(String),
+    /// The whole board of the given tick
+    Frame {
+        tick: u64,
+        instructions: Vec<DrawInstruction>,
+    },
+    Pong(u64),
+    /// Answer to the hello in a race: the number of the racer and the settings every racer plays
+    Race {
+        player: usize,
+        config: Config,
+    },
+    /// How every racer is doing, by the number of the racer
+    Standings(Vec<(usize, Standing)>),
+}
+
+impl ClientMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut payload = Payload::default();
+        match self {
+            Self::Hello(version) | Self::Watch(version) => {
+                payload.put_u8(match self {
+                    Self::Hello(_) => 0,
+                    _ => 5,
+                });
+                payload.0.extend_from_slice(MAGIC);
+                payload.put_u8(*version);
+            }
+            Self::Input(control) => {
+                payload.put_u8(1);
+                let text = UpdateReason::Control(control.clone()).to_string();
+                payload.0.extend_from_slice(text.as_bytes());
+            }
+            Self::Ping(number) => {
+                payload.put_u8(2);
+                payload.put_u64(*number);
+            }
+            Self::Bye => payload.put_u8(3),
+            Self::Report(standing) => {
+                payload.put_u8(4);
+                payload.put_standing(standing);
+            }
+        }
+        payload.into_frame()
+    }
+
+    /// Decodes a message from the content of a frame
+    pub fn decode(bytes: &[u8]) -> Result<ClientMessage, NetworkError> {
+        let mut reader = Reader(bytes);
+        let message = match reader.get_u8()? {
+            tag @ 0 | tag @ 5 => {
+                if reader.get_bytes(MAGIC.len())? != MAGIC {
+                    return Err(NetworkError::protocol("Not a snake client"));
+                }
+                match tag {
+                    0 => Self::Hello(reader.get_u8()?),
+                    _ => Self::Watch(reader.get_u8()?),
+                }
+            }
+            1 => {
+                let text = String::from_utf8(reader.get_rest().to_vec())
+                    .map_err(|_| NetworkError::protocol("Invalid input"))?;
+                match text.parse() {
+                    Ok(UpdateReason::Control(Control::Quit)) | Ok(UpdateReason::Time) => {
+                        return Err(NetworkError::protocol("Invalid input"))
+                    }
+                    Ok(UpdateReason::Control(control)) => Self::Input(control),
+                    Err(error) => return Err(NetworkError::Protocol(error)),
+                }
+            }
+            2 => Self::Ping(reader.get_u64()?),
+            3 => Self::Bye,
+            4 => Self::Report(reader.get_standing()?),
+            tag => return Err(NetworkError::Protocol(format!("Unknown message: {}", tag))),
+        };
+        reader.finish(message)
+    }
+}
+
+impl ServerMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut payload = Payload::default();
+        match self {
+            Self::Welcome(role) => {
+                payload.put_u8(0);
+                payload.put_u8(match role {
+                    Role::Spectator => 0,
+                    Role::Player(player) => *player as u8 + 1,
+                });
+            }
+            Self::Refused(reason) => {
+                payload.put_u8(1);
+                payload.0.extend_from_slice(reason.as_bytes());
+            }
+            Self::Frame { tick, instructions } => {
+                payload.put_u8(2);
+                payload.put_u64(*tick);
+                payload.put_u32(instructions.len() as u32);
+                for instruction in instructions {
+                    payload.put_u16(instruction.pos.x as u16);
+                    payload.put_u16(instruction.pos.y as u16);
+                    payload.put_u16(instruction.shape.len() as u16);
+                    for Directed(direction, symbol) in &instruction.shape {
+                        payload.put_direction(direction);
+                        payload.put_symbol(symbol);
+                    }
+                }
+            }
+            Self::Pong(number) => {
+                payload.put_u8(3);
+                payload.put_u64(*number);
+            }
+            Self::Race { player, config } => {
+                payload.put_u8(4);
+                // the racers aren't limited, their numbers aren't reused
+                payload.put_u32(*player as u32);
+                payload
+                    .0
+                    .extend_from_slice(Setup(config).to_string().as_bytes());
+            }
+            Self::Standings(standings) => {
+                payload.put_u8(5);
+                payload.put_u32(standings.len() as u32);
+                for (player, standing) in standings {
+                    payload.put_u32(*player as u32);
+                    payload.put_standing(standing);
+                }
+            }
+        }
+        payload.into_frame()
+    }
+
+    /// Decodes a message from the content of a frame
+    pub fn decode(bytes: &[u8]) -> Result<ServerMessage, NetworkError> {
+        let mut reader = Reader(bytes);
+        let message = match reader.get_u8()? {
+            0 => Self::Welcome(match reader.get_u8()? {
+                0 => Role::Spectator,
+                player => Role::Player(player as usize - 1),
+            }),
+            1 => Self::Refused(String::from_utf8_lossy(reader.get_rest()).into_owned()),
+            2 => {
+                let tick = reader.get_u64()?;
+                let count = reader.get_u32()?;
+                let mut instructions = Vec::new();
+                for _ in 0..count {
+                    let pos = Vec2 {
+                        x: reader.get_u16()? as usize,
+                        y: reader.get_u16()? as usize,
+                    };
+                    let length = reader.get_u16()?;
+                    let shape = (0..length)
+                        .map(|_| Ok(Directed(reader.get_direction()?, reader.get_symbol()?)))
+                        .collect::<Result<Vec<Directed<Symbol>>, NetworkError>>()?;
+                    instructions.push(DrawInstruction { pos, shape });
+                }
+                Self::Frame { tick, instructions }
+            }
+            3 => Self::Pong(reader.get_u64()?),
+            4 => {
+                let player = reader.get_u32()? as usize;
+                let text = String::from_utf8(reader.get_rest().to_vec())
+                    .map_err(|_| NetworkError::protocol("Invalid settings"))?;
+                let mut lines = text.lines().enumerate().peekable();
+                let config = parse_setup(&mut lines)
+                    .map_err(|error| NetworkError::Protocol(error.to_string()))?;
+                Self::Race { player, config }
+            }
+            5 => {
+                let count = reader.get_u32()?;
+                let standings = (0..count)
+                    .map(|_| Ok((reader.get_u32()? as usize, reader.get_standing()?)))
+                    .collect::<Result<Vec<(usize, Standing)>, NetworkError>>()?;
+                Self::Standings(standings)
+            }
+            tag => return Err(NetworkError::Protocol(format!("Unknown message: {}", tag))),
+        };
+        reader.finish(message)
+    }
+}
+
+/// The settings of a race in the format of the replays
+struct Setup<'a>(&'a Config);
+
+impl Display for Setup<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_setup(f, self.0)
+    }
+}
+
+/// Collects the bytes received from a stream and cuts them into frames
+#[derive(Default)]
+pub struct Framer {
+    buffer: Vec<u8>,
+}
+
+impl Framer {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the content of the next complete frame, if it has arrived
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, NetworkError> {
+        if self.buffer.len() < 4 {
+            return Ok(None);
+        }
+        let mut length = [0; 4];
+        length.copy_from_slice(&self.buffer[..4]);
+        let length = u32::from_be_bytes(length) as usize;
+        if length == 0 || length > MAX_LENGTH {
+            return Err(NetworkError::Protocol(format!(
+                "Invalid length: {}",
+                length
+            )));
+        }
+        if self.buffer.len() < 4 + length {
+            return Ok(None);
+        }
+        let frame = self.buffer[4..4 + length].to_vec();
+        self.buffer.drain(..4 + length);
+        Ok(Some(frame))
+    }
+}
+
+/// Content of a message being encoded
+#[derive(Default)]
+struct Payload(Vec<u8>);
+
+impl Payload {
+    fn put_u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn put_u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_standing(&mut self, standing: &Standing) {
+        self.put_u32(standing.points);
+        self.put_u32(standing.length as u32);
+        self.put_u8(standing.dead as u8);
+    }
+
+    fn put_direction(&mut self, direction: &Direction) {
+        let index = Direction::ALL.iter().position(|each| each == direction);
+        self.put_u8(index.expect("Unknown direction") as u8);
+    }
+
+    fn put_symbol(&mut self, symbol: &Symbol) {
+        match symbol {
+            Symbol::Empty => self.put_u8(0),
+            Symbol::Wall => self.put_u8(1),
+            Symbol::Apple => self.put_u8(2),
+            Symbol::GoldenApple => self.put_u8(3),
+            Symbol::ShrinkingFood => self.put_u8(4),
+            Symbol::SpeedUp => self.put_u8(5),
+            Symbol::SlowDown => self.put_u8(6),
+            Symbol::BonusFood => self.put_u8(7),
+            Symbol::Poison => self.put_u8(8),
+            Symbol::PowerUp(power_up) => {
+                self.put_u8(9);
+                let index = PowerUp::ALL.iter().position(|each| each == power_up);
+                self.put_u8(index.expect("Unknown power-up") as u8);
+            }
+            Symbol::Gate { open } => {
+                self.put_u8(10);
+                self.put_u8(*open as u8);
+            }
+            Symbol::Portal(label) => {
+                self.put_u8(11);
+                self.put_u32(*label as u32);
+            }
+            Symbol::Patrol => self.put_u8(12),
+            Symbol::SnakeBody(player) => {
+                self.put_u8(13);
+                self.put_u8(*player as u8);
+            }
+            Symbol::SnakeHead(player) => {
+                self.put_u8(14);
+                self.put_u8(*player as u8);
+            }
+            Symbol::Text(c) => {
+                self.put_u8(15);
+                self.put_u32(*c as u32);
+            }
+        }
+    }
+
+    /// Returns the payload framed by its length
+    fn into_frame(self) -> Vec<u8> {
+        let mut frame = (self.0.len() as u32).to_be_bytes().to_vec();
+        frame.extend(self.0);
+        frame
+    }
+}
+
+/// Content of a message being decoded, the bytes that haven't been read yet
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn get_bytes(&mut self, count: usize) -> Result<&'a [u8], NetworkError> {
+        if self.0.len() < count {
+            return Err(NetworkError::protocol("The message is cut short"));
+        }
+        let (bytes, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn get_rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.0)
+    }
+
+    fn get_u8(&mut self) -> Result<u8, NetworkError> {
+        Ok(self.get_bytes(1)?[0])
+    }
+
+    fn get_u16(&mut self) -> Result<u16, NetworkError> {
+        let bytes = self.get_bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn get_u32(&mut self) -> Result<u32, NetworkError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.get_bytes(4)?);
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn get_u64(&mut self) -> Result<u64, NetworkError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.get_bytes(8)?);
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    fn get_char(&mut self) -> Result<char, NetworkError> {
+        char::try_from(self.get_u32()?).map_err(|_| NetworkError::protocol("Invalid character"))
+    }
+
+    fn get_standing(&mut self) -> Result<Standing, NetworkError> {
+        Ok(Standing {
+            points: self.get_u32()?,
+            length: self.get_u32()? as usize,
+            dead: self.get_u8()? != 0,
+        })
+    }
+
+    fn get_direction(&mut self) -> Result<Direction, NetworkError> {
+        let index = self.get_u8()? as usize;
+        Direction::ALL
+            .get(index)
+            .cloned()
+            .ok_or_else(|| NetworkError::protocol("Invalid direction"))
+    }
+
+    fn get_symbol(&mut self) -> Result<Symbol, NetworkError> {
+        let symbol = match self.get_u8()? {
+            0 => Symbol::Empty,
+            1 => Symbol::Wall,
+            2 => Symbol::Apple,
+            3 => Symbol::GoldenApple,
+            4 => Symbol::ShrinkingFood,
+            5 => Symbol::SpeedUp,
+            6 => Symbol::SlowDown,
+            7 => Symbol::BonusFood,
+            8 => Symbol::Poison,
+            9 => Symbol::PowerUp(
+                *PowerUp::ALL
+                    .get(self.get_u8()? as usize)
+                    .ok_or_else(|| NetworkError::protocol("Invalid power-up"))?,
+            ),
+            10 => Symbol::Gate {
+                open: self.get_u8()? != 0,
+            },
+            11 => Symbol::Portal(self.get_char()?),
+            12 => Symbol::Patrol,
+            13 => Symbol::SnakeBody(self.get_u8()? as usize),
+            14 => Symbol::SnakeHead(self.get_u8()? as usize),
+            15 => Symbol::Text(self.get_char()?),
+            _ => return Err(NetworkError::protocol("Invalid symbol")),
+        };
+        Ok(symbol)
+    }
+
+    /// Returns the decoded message if every byte has been read
+    fn finish<T>(self, message: T) -> Result<T, NetworkError> {
+        match self.0.is_empty() {
+            true => Ok(message),
+            false => Err(NetworkError::protocol("Unexpected bytes after the message")),
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use super::super::events::TerminalEventStream;
+use super::super::get_terminal_size;
+use super::super::render::Renderer;
+use super::super::snake_game::config::Config;
+use super::super::snake_game::game::direction::Direction;
+use super::super::snake_game::game::draw_instruction::DrawInstruction;
+use super::super::snake_game::game::traits::Draw;
+use super::super::snake_game::game::vec2::Vec2;
+use super::super::snake_game::state::State;
+use super::super::snake_game::SnakeGame;
+use super::super::traits::{Event, EventStream, SnakeGame as SnakeGameTrait, TickInterval};
+use super::super::update_reason::{Control, UpdateReason};
+use super::client::Connection;
+use super::protocol::{ClientMessage, Role, ServerMessage, Standing, VERSION};
+use super::server::{accept, Client, POLL_INTERVAL};
+use super::socket::Listener;
+use super::NetworkError;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io::Stdout;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+use termion::event::Key;
+use termion::raw::{IntoRawMode, RawTerminal};
+
+/// Longest wait for a terminal event before looking at the messages of the coordinator
+const RACE_POLL_INTERVAL: Duration = Duration::from_millis(5);
+/// Width of the standings next to the board
+const SIDEBAR_WIDTH: usize = 18;
+
+/// Relays the standings of a race, every racer plays their own board with the same settings
+///
+/// The coordinator doesn't play, it only gives the settings to the racers who join and sends
+/// everyone's standing to everyone when one of them changes.
+pub struct Coordinator {
+    listener: Listener,
+    config: Config,
+    clients: Vec<Client>,
+    /// The last standing of every racer still in the race, by the number of the racer
+    standings: BTreeMap<usize, Standing>,
+    /// Number of the next racer to join, the numbers aren't reused
+    next_player: usize,
+    /// Whether the standings have changed since they were sent
+    changed: bool,
+    /// What has happened since the last look, for the log of the coordinator
+    notices: Vec<String>,
+}
+
+impl Coordinator {
+    /// Starts listening on the given address, the racers play with the given settings
+    pub fn bind(address: &str, config: &Config) -> Result<Coordinator, NetworkError> {
+        let listener = Listener::bind(address)?;
+        Ok(Coordinator {
+            listener,
+            config: config.clone(),
+            clients: Vec::new(),
+            standings: BTreeMap::new(),
+            next_player: 0,
+            changed: false,
+            notices: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<String, NetworkError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Relays the standings forever, printing who joins and leaves
+    pub fn run(&mut self) {
+        loop {
+            self.poll();
+            for notice in self.take_notices() {
+                println!("{}", notice);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Accepts the new racers, handles the messages that have arrived and sends what is waiting
+    pub fn poll(&mut self) {
+        accept(&self.listener, &mut self.clients, &mut self.notices);
+        for index in 0..self.clients.len() {
+            for message in self.clients[index].receive() {
+                self.handle(index, message);
+            }
+        }
+        self.remove_closed();
+        if self.changed {
+            let standings = ServerMessage::Standings(self.standings.clone().into_iter().collect());
+            for client in self
+                .clients
+                .iter_mut()
+                .filter(|client| client.role.is_some())
+            {
+                client.send(&standings);
+            }
+            self.changed = false;
+        }
+        for client in &mut self.clients {
+            client.flush();
+        }
+        self.remove_closed();
+    }
+
+    /// Returns what has happened since the last call
+    pub fn take_notices(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notices)
+    }
+
+    fn handle(&mut self, index: usize, message: ClientMessage) {
+        let client = &mut self.clients[index];
+        match (client.role, message) {
+            (None, ClientMessage::Hello(version)) if version != VERSION => {
+                let reason = format!("the server speaks version {}", VERSION);
+                client.send(&ServerMessage::Refused(reason));
+                client.close("unsupported version");
+            }
+            (None, ClientMessage::Hello(_)) => {
+                let player = self.next_player;
+                self.next_player += 1;
+                client.role = Some(Role::Player(player));
+                client.send(&ServerMessage::Race {
+                    player,
+                    config: self.config.clone(),
+                });
+                self.standings.insert(player, Standing::default());
+                self.changed = true;
+                self.notices.push(format!(
+                    "{} has joined the race as racer {}",
+                    client.address,
+                    player + 1
+                ));
+            }
+            (None, ClientMessage::Watch(_)) => {
+                let reason = "a race can only be joined".to_string();
+                client.send(&ServerMessage::Refused(reason));
+                client.close("watching a race");
+            }
+            (Some(Role::Player(player)), ClientMessage::Report(standing)) => {
+                self.standings.insert(player, standing);
+                self.changed = true;
+            }
+            (Some(_), ClientMessage::Ping(number)) => client.send(&ServerMessage::Pong(number)),
+            (Some(_), ClientMessage::Bye) => client.close("left"),
+            (_, _) => client.close("unexpected message"),
+        }
+    }
+
+    /// Removes the closed connections and the standings of the racers who have left
+    fn remove_closed(&mut self) {
+        let (closed, open) = std::mem::take(&mut self.clients)
+            .into_iter()
+            .partition(|client| client.closed.is_some());
+        self.clients = open;
+
+        for mut client in closed {
+            // the last words, e.g. why the client is refused
+            client.flush();
+            let reason = client.closed.unwrap_or_default();
+            match client.role {
+                Some(Role::Player(player)) => {
+                    self.standings.remove(&player);
+                    self.changed = true;
+                    self.notices.push(format!(
+                        "{} (racer {}) has left: {}",
+                        client.address,
+                        player + 1,
+                        reason
+                    ));
+                }
+                _ => self.notices.push(format!("{}: {}", client.address, reason)),
+            }
+        }
+    }
+}
+
+/// Plays a race in the terminal: a local game with the standings of the others next to it
+pub struct RaceClient {
+    connection: Connection,
+    renderer: Renderer,
+    snake_game: SnakeGame,
+    /// Number of this racer
+    player: usize,
+    standings: Vec<(usize, Standing)>,
+    /// The last standing sent to the coordinator
+    reported: Option<Standing>,
+}
+
+impl RaceClient {
+    pub fn new(connection: Connection, renderer: Renderer, config: &Config) -> RaceClient {
+        let player = match connection.get_role() {
+            Role::Player(player) => player,
+            Role::Spectator => 0,
+        };
+        RaceClient {
+            connection,
+            renderer,
+            snake_game: SnakeGame::new_unranked(config),
+            player,
+            standings: Vec::new(),
+            reported: None,
+        }
+    }
+
+    /// Plays until the user leaves or the coordinator closes the connection
+    pub fn main(&mut self) -> Result<(), NetworkError> {
+        let tick_interval = TickInterval::new(self.snake_game.get_tick_interval());
+        let event_stream = TerminalEventStream::start(tick_interval.clone());
+
+        let mut stdout = std::io::stdout().into_raw_mode().unwrap();
+        self.renderer.resize(get_terminal_size());
+        self.renderer.enter_screen(&mut stdout);
+        let result = self.play(&event_stream, &tick_interval, &mut stdout);
+        self.renderer.leave_screen(&mut stdout);
+
+        result
+    }
+
+    fn play(
+        &mut self,
+        event_stream: &Receiver<Event>,
+        tick_interval: &TickInterval,
+        stdout: &mut RawTerminal<Stdout>,
+    ) -> Result<(), NetworkError> {
+        loop {
+            match event_stream.recv_timeout(RACE_POLL_INTERVAL) {
+                Ok(Event::Time) => self.snake_game.update(UpdateReason::Time),
+                Ok(Event::Key(key)) => {
+                    if let Some(control) = self.get_control(key) {
+                        self.snake_game.update(UpdateReason::Control(control));
+                    }
+                }
+                Ok(Event::Resize(columns, rows)) => {
+                    self.renderer.resize((columns, rows));
+                    self.renderer.enter_screen(stdout);
+                }
+                Ok(Event::FocusLost) => {
+                    self.snake_game
+                        .update(UpdateReason::Control(Control::Pause));
+                }
+                Ok(Event::Continue) => self.renderer.enter_screen(stdout),
+                Ok(Event::Suspend) | Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => panic!("Channel has stopped."),
+            }
+            if self.snake_game.should_quit() {
+                return self.connection.send(&ClientMessage::Bye);
+            }
+            tick_interval.set(self.snake_game.get_tick_interval());
+
+            self.report()?;
+            while let Some(message) = self.connection.receive(Duration::from_secs(0))? {
+                if let ServerMessage::Standings(standings) = message {
+                    self.standings = standings;
+                }
+            }
+
+            let frame = self.draw();
+            // a round can't go on while it doesn't fit into the terminal
+            let playing = self.snake_game.is_running() && !self.snake_game.is_paused();
+            if playing && !self.renderer.fits(&frame) {
+                self.snake_game
+                    .update(UpdateReason::Control(Control::Pause));
+            }
+            self.renderer.paint(stdout, frame);
+        }
+    }
+
+    /// Sends the standing of this racer to the coordinator if it has changed
+    fn report(&mut self) -> Result<(), NetworkError> {
+        let score = self.snake_game.get_score();
+        let standing = Standing {
+            points: score.points,
+            length: score.length,
+            dead: matches!(self.snake_game.get_state(), State::GameOver(_)),
+        };
+        if self.reported != Some(standing) {
+            self.connection.send(&ClientMessage::Report(standing))?;
+            self.reported = Some(standing);
+        }
+        Ok(())
+    }
+
+    /// Every racer plays at the speed of the race, so the difficulty can't be changed
+    fn get_control(&self, key: Key) -> Option<Control> {
+        match key {
+            Key::Up => Some(Control::Turn(0, Direction::Up)),
+            Key::Down => Some(Control::Turn(0, Direction::Down)),
+            Key::Left => Some(Control::Turn(0, Direction::Left)),
+            Key::Right => Some(Control::Turn(0, Direction::Right)),
+            Key::Char('\n') | Key::Char(' ') => Some(Control::Start),
+            Key::Char('p') if self.snake_game.is_paused() => Some(Control::Resume),
+            Key::Char('p') => Some(Control::Pause),
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => Some(Control::Quit),
+            _ => None,
+        }
+    }
+}
+
+impl Draw for RaceClient {
+    fn draw(&self) -> Vec<DrawInstruction> {
+        let mut paint = self.snake_game.draw();
+
+        // the standings go to the right of the board
+        let x = paint.first().map_or(0, |row| row.shape.len()) + 1;
+        for (y, line) in get_sidebar(&self.standings, self.player).iter().enumerate() {
+            paint.push(DrawInstruction::text(Vec2 { x, y }, line));
+        }
+
+        paint
+    }
+}
+
+/// Returns the lines of the standings, the best racer first
+///
+/// ```text
+/// RACE
+///
+/// 1. you         120
+///    length 14
+/// 2. racer 3      80
+///    length 9   dead
+/// ```
+pub fn get_sidebar(standings: &[(usize, Standing)], player: usize) -> Vec<String> {
+    let mut standings = standings.to_vec();
+    standings.sort_by_key(|(racer, standing)| (Reverse(standing.points), standing.dead, *racer));
+
+    let mut lines = vec!["RACE".to_string(), String::new()];
+    for (rank, (racer, standing)) in standings.iter().enumerate() {
+        let name = match *racer == player {
+            true => "you".to_string(),
+            false => format!("racer {}", racer + 1),
+        };
+        let rank = format!("{}. ", rank + 1);
+        let points = standing.points.to_string();
+        let padding = SIDEBAR_WIDTH.saturating_sub(rank.len() + name.len() + points.len());
+        lines.push(format!("{}{}{}{}", rank, name, " ".repeat(padding), points));
+
+        let length = format!("   length {}", standing.length);
+        lines.push(match standing.dead {
+            true => format!("{:<width$}dead", length, width = SIDEBAR_WIDTH - 4),
+            false => length,
+        });
+    }
+    lines
+}
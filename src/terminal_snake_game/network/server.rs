@@ -0,0 +1,390 @@
+use super::super::snake_game::config::Config;
+use super::super::snake_game::game::direction::Direction;
+use super::super::snake_game::game::draw_instruction::DrawInstruction;
+use super::super::snake_game::game::traits::Draw;
+use super::super::snake_game::SnakeGame;
+use super::super::traits::SnakeGame as SnakeGameTrait;
+use super::super::update_reason::{Control, UpdateReason};
+use super::protocol::{ClientMessage, Framer, Role, ServerMessage, VERSION};
+use super::socket::{Listener, Socket};
+use super::NetworkError;
+use std::collections::VecDeque;
+use std::io;
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+/// Time between two looks at the sockets while waiting for the next tick
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(2);
+/// Turns of a player kept for the next ticks, the ones beyond are dropped
+const MAX_QUEUED_TURNS: usize = 3;
+/// Unsent bytes of a client that doesn't read them, it is disconnected beyond this
+pub(super) const MAX_OUTGOING: usize = 1 << 20;
+
+/// A connection to the server
+pub(super) struct Client {
+    stream: Socket,
+    pub(super) address: String,
+    framer: Framer,
+    /// `None` until the client has said hello
+    pub(super) role: Option<Role>,
+    /// Whether the client has only come to watch, it never gets the place of a leaving player
+    watching: bool,
+    /// Turns that have arrived before they could be applied, one is applied every tick
+    turns: VecDeque<Direction>,
+    /// Bytes that the socket hasn't taken yet
+    outgoing: Vec<u8>,
+    /// Newest frame that hasn't been sent yet, a frame is skipped if a newer one comes
+    /// before the client has read the previous one
+    pub(super) frame: Option<Vec<u8>>,
+    /// Why the connection is closed, `None` while it is open
+    pub(super) closed: Option<String>,
+}
+
+impl Client {
+    fn new(stream: Socket, address: String) -> io::Result<Client> {
+        stream.set_nonblocking(true)?;
+        Ok(Client {
+            stream,
+            address,
+            framer: Framer::default(),
+            role: None,
+            watching: false,
+            turns: VecDeque::new(),
+            outgoing: Vec::new(),
+            frame: None,
+            closed: None,
+        })
+    }
+
+    pub(super) fn send(&mut self, message: &ServerMessage) {
+        self.outgoing.extend(message.encode());
+    }
+
+    pub(super) fn close(&mut self, reason: &str) {
+        if self.closed.is_none() {
+            self.closed = Some(reason.to_string());
+        }
+    }
+
+    /// Reads the messages that have arrived
+    pub(super) fn receive(&mut self) -> Vec<ClientMessage> {
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.close("disconnected");
+                    break;
+                }
+                Ok(count) => self.framer.push(&buffer[..count]),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.close(&error.to_string());
+                    break;
+                }
+            }
+        }
+
+        let mut messages = Vec::new();
+        loop {
+            match self.framer.next_frame() {
+                Ok(Some(frame)) => match ClientMessage::decode(&frame) {
+                    Ok(message) => messages.push(message),
+                    Err(error) => {
+                        self.close(&error.to_string());
+                        break;
+                    }
+                },
+                Ok(None) => break,
+                Err(error) => {
+                    self.close(&error.to_string());
+                    break;
+                }
+            }
+        }
+        messages
+    }
+
+    /// Writes as much of the outgoing bytes as the socket takes
+    pub(super) fn flush(&mut self) {
+        if self.outgoing.is_empty() {
+            if let Some(frame) = self.frame.take() {
+                self.outgoing = frame;
+            }
+        }
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => {
+                    self.close("disconnected");
+                    return;
+                }
+                Ok(count) => {
+                    self.outgoing.drain(..count);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.close(&error.to_string());
+                    return;
+                }
+            }
+        }
+        if self.outgoing.len() > MAX_OUTGOING {
+            self.close("too slow");
+        }
+    }
+}
+
+/// Owns the game of an online match, the clients only send their controls and get the board
+///
+/// The first clients to join play, the ones who join later watch; when a player leaves,
+/// the game is paused and the longest watching spectator takes the place.
+pub struct Server {
+    listener: Listener,
+    snake_game: SnakeGame,
+    clients: Vec<Client>,
+    tick: u64,
+    /// The board of the last frame sent to the clients
+    board: Vec<DrawInstruction>,
+    /// The last frame sent to the clients, a client that joins gets it at once
+    frame: Option<Vec<u8>>,
+    /// What has happened since the last look, for the log of the server
+    notices: Vec<String>,
+}
+
+impl Server {
+    /// Starts listening on the given address, the game is created from the given settings
+    pub fn bind(address: &str, config: &Config) -> Result<Server, NetworkError> {
+        let listener = Listener::bind(address)?;
+        Ok(Server {
+            listener,
+            snake_game: SnakeGame::new_unranked(config),
+            clients: Vec::new(),
+            tick: 0,
+            board: Vec::new(),
+            frame: None,
+            notices: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<String, NetworkError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Plays the game forever, printing who joins and leaves
+    pub fn run(&mut self) {
+        loop {
+            let next_tick = Instant::now() + self.snake_game.get_tick_interval();
+            while Instant::now() < next_tick {
+                self.poll();
+                for notice in self.take_notices() {
+                    println!("{}", notice);
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            self.tick();
+        }
+    }
+
+    /// Accepts the new clients, handles the messages that have arrived and sends what is waiting
+    pub fn poll(&mut self) {
+        accept(&self.listener, &mut self.clients, &mut self.notices);
+        for index in 0..self.clients.len() {
+            for message in self.clients[index].receive() {
+                self.handle(index, message);
+            }
+        }
+        self.remove_closed();
+        self.broadcast();
+        for client in &mut self.clients {
+            client.flush();
+        }
+        self.remove_closed();
+    }
+
+    /// Moves the game on by a tick, every player's next turn is applied before it
+    pub fn tick(&mut self) {
+        for client in &mut self.clients {
+            if let (Some(Role::Player(player)), Some(direction)) =
+                (client.role, client.turns.pop_front())
+            {
+                let turn = Control::Turn(player, direction);
+                self.snake_game.update(UpdateReason::Control(turn));
+            }
+        }
+        self.snake_game.update(UpdateReason::Time);
+        self.tick += 1;
+        self.broadcast();
+    }
+
+    /// Returns what has happened since the last call
+    pub fn take_notices(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notices)
+    }
+
+    fn handle(&mut self, index: usize, message: ClientMessage) {
+        let client = &mut self.clients[index];
+        match (client.role, message) {
+            (None, ClientMessage::Hello(version)) | (None, ClientMessage::Watch(version))
+                if version != VERSION =>
+            {
+                let reason = format!("the server speaks version {}", VERSION);
+                client.send(&ServerMessage::Refused(reason));
+                client.close("unsupported version");
+            }
+            (None, hello @ ClientMessage::Hello(_)) | (None, hello @ ClientMessage::Watch(_)) => {
+                let role = match hello {
+                    ClientMessage::Watch(_) => Role::Spectator,
+                    _ => self.get_free_role(),
+                };
+                let client = &mut self.clients[index];
+                client.role = Some(role);
+                client.watching = matches!(hello, ClientMessage::Watch(_));
+                client.send(&ServerMessage::Welcome(role));
+                // a late joiner gets the board at once
+                client.frame = self.frame.clone();
+                self.notices.push(format!(
+                    "{} has joined as {}",
+                    client.address,
+                    get_name(role)
+                ));
+            }
+            (None, _)
+            | (Some(_), ClientMessage::Hello(_))
+            | (Some(_), ClientMessage::Watch(_))
+            | (Some(_), ClientMessage::Report(_)) => client.close("unexpected message"),
+            (Some(_), ClientMessage::Ping(number)) => client.send(&ServerMessage::Pong(number)),
+            (Some(_), ClientMessage::Bye) => client.close("left"),
+            (Some(Role::Spectator), ClientMessage::Input(_)) => (),
+            (Some(Role::Player(_)), ClientMessage::Input(Control::Turn(_, direction))) => {
+                if client.turns.len() < MAX_QUEUED_TURNS {
+                    client.turns.push_back(direction);
+                }
+            }
+            (Some(Role::Player(_)), ClientMessage::Input(control)) => self.control(control),
+        }
+    }
+
+    /// Applies a control of a player other than a turn
+    fn control(&mut self, control: Control) {
+        let control = match control {
+            Control::Pause if self.snake_game.is_paused() => Control::Resume,
+            // a round starts only when every snake has a player
+            Control::Start if self.get_players().len() < self.snake_game.get_player_count() => {
+                return
+            }
+            control => control,
+        };
+        self.snake_game.update(UpdateReason::Control(control));
+    }
+
+    /// Returns the first free place of a player, or a place of a spectator
+    fn get_free_role(&self) -> Role {
+        let players = self.get_players();
+        (0..self.snake_game.get_player_count())
+            .find(|player| !players.contains(player))
+            .map_or(Role::Spectator, Role::Player)
+    }
+
+    fn get_players(&self) -> Vec<usize> {
+        self.clients
+            .iter()
+            .filter(|client| client.closed.is_none())
+            .filter_map(|client| match client.role {
+                Some(Role::Player(player)) => Some(player),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Removes the closed connections, the place of a player goes to the first spectator that
+    /// hasn't come only to watch
+    fn remove_closed(&mut self) {
+        let (closed, open) = std::mem::take(&mut self.clients)
+            .into_iter()
+            .partition(|client| client.closed.is_some());
+        self.clients = open;
+
+        for mut client in closed {
+            // the last words, e.g. why the client is refused
+            client.flush();
+            let reason = client.closed.unwrap_or_default();
+            let role = match client.role {
+                Some(role) => role,
+                None => {
+                    self.notices.push(format!("{}: {}", client.address, reason));
+                    continue;
+                }
+            };
+            self.notices.push(format!(
+                "{} ({}) has left: {}",
+                client.address,
+                get_name(role),
+                reason
+            ));
+            if let Role::Player(player) = role {
+                self.snake_game
+                    .update(UpdateReason::Control(Control::Pause));
+                let spectator = self
+                    .clients
+                    .iter_mut()
+                    .find(|client| client.role == Some(Role::Spectator) && !client.watching);
+                if let Some(spectator) = spectator {
+                    spectator.role = Some(Role::Player(player));
+                    spectator.send(&ServerMessage::Welcome(Role::Player(player)));
+                    self.notices.push(format!(
+                        "{} plays as player {}",
+                        spectator.address,
+                        player + 1
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Sends the board to every client if it has changed since the last one
+    fn broadcast(&mut self) {
+        let board = self.snake_game.draw();
+        if self.frame.is_some() && board == self.board {
+            return;
+        }
+        let frame = ServerMessage::Frame {
+            tick: self.tick,
+            instructions: board.clone(),
+        }
+        .encode();
+        self.board = board;
+        for client in self
+            .clients
+            .iter_mut()
+            .filter(|client| client.role.is_some())
+        {
+            client.frame = Some(frame.clone());
+        }
+        self.frame = Some(frame);
+    }
+}
+
+/// Accepts the clients waiting on the listener
+pub(super) fn accept(listener: &Listener, clients: &mut Vec<Client>, notices: &mut Vec<String>) {
+    loop {
+        match listener.accept() {
+            Ok((stream, address)) => match Client::new(stream, address.clone()) {
+                Ok(client) => clients.push(client),
+                Err(error) => notices.push(format!("{}: {}", address, error)),
+            },
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+            Err(error) => {
+                notices.push(format!("Couldn't accept a client: {}", error));
+                break;
+            }
+        }
+    }
+}
+
+fn get_name(role: Role) -> String {
+    match role {
+        Role::Player(player) => format!("player {}", player + 1),
+        Role::Spectator => "spectator".to_string(),
+    }
+}
@@ -0,0 +1,128 @@
+use std::io;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+/// A connection over TCP or a Unix domain socket
+pub enum Socket {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Socket {
+    /// Connects to the given address, an address with a slash is the path of a Unix domain socket
+    pub fn connect(address: &str) -> io::Result<Socket> {
+        if is_path(address) {
+            return Ok(Socket::Unix(UnixStream::connect(address)?));
+        }
+        let stream = TcpStream::connect(address)?;
+        // the messages are small and late ones are useless
+        stream.set_nodelay(true)?;
+        Ok(Socket::Tcp(stream))
+    }
+
+    pub fn try_clone(&self) -> io::Result<Socket> {
+        match self {
+            Self::Tcp(stream) => Ok(Self::Tcp(stream.try_clone()?)),
+            Self::Unix(stream) => Ok(Self::Unix(stream.try_clone()?)),
+        }
+    }
+
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.set_nonblocking(nonblocking),
+            Self::Unix(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    /// Closes both directions, the clones of the socket are closed too
+    pub fn shutdown(&self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.shutdown(Shutdown::Both),
+            Self::Unix(stream) => stream.shutdown(Shutdown::Both),
+        }
+    }
+}
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A nonblocking listener on TCP or a Unix domain socket
+pub enum Listener {
+    Tcp(TcpListener),
+    /// The file of the socket is removed when the listener is dropped
+    Unix(UnixListener, PathBuf),
+}
+
+impl Listener {
+    /// Listens on the given address, an address with a slash is the path of a Unix domain socket
+    pub fn bind(address: &str) -> io::Result<Listener> {
+        let listener = match is_path(address) {
+            true => Listener::Unix(UnixListener::bind(address)?, PathBuf::from(address)),
+            false => Listener::Tcp(TcpListener::bind(address)?),
+        };
+        match &listener {
+            Self::Tcp(listener) => listener.set_nonblocking(true)?,
+            Self::Unix(listener, _) => listener.set_nonblocking(true)?,
+        }
+        Ok(listener)
+    }
+
+    /// Returns the next waiting connection and the address of the other side
+    pub fn accept(&self) -> io::Result<(Socket, String)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, address) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                Ok((Socket::Tcp(stream), address.to_string()))
+            }
+            // the clients of a Unix domain socket have no address of their own
+            Self::Unix(listener, _) => {
+                Ok((Socket::Unix(listener.accept()?.0), "local".to_string()))
+            }
+        }
+    }
+
+    /// Returns the address to connect to, the port is the chosen one if 0 has been given
+    pub fn local_addr(&self) -> io::Result<String> {
+        match self {
+            Self::Tcp(listener) => Ok(listener.local_addr()?.to_string()),
+            Self::Unix(_, path) => Ok(path.display().to_string()),
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Self::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn is_path(address: &str) -> bool {
+    address.contains('/')
+}
@@ -0,0 +1,94 @@
+use super::super::snake_game::game::draw_instruction::DrawInstruction;
+use super::protocol::{ClientMessage, Role, ServerMessage, VERSION};
+use super::server::{accept, Client};
+use super::socket::Listener;
+use super::NetworkError;
+
+/// Streams the board of a game played in this terminal to the viewers who watch it
+///
+/// The viewers are handled whenever a board is published, so a game publishes every tick
+/// even if its board hasn't changed.
+pub struct Broadcaster {
+    listener: Listener,
+    clients: Vec<Client>,
+    /// Number of the boards published
+    tick: u64,
+    /// The last board sent to the viewers
+    board: Vec<DrawInstruction>,
+    /// The last frame sent to the viewers, a viewer who joins gets it at once
+    frame: Option<Vec<u8>>,
+}
+
+impl Broadcaster {
+    /// Starts listening for viewers on the given address
+    pub fn bind(address: &str) -> Result<Broadcaster, NetworkError> {
+        Ok(Broadcaster {
+            listener: Listener::bind(address)?,
+            clients: Vec::new(),
+            tick: 0,
+            board: Vec::new(),
+            frame: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<String, NetworkError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accepts the new viewers, answers them and sends them the board if it has changed
+    pub fn publish(&mut self, board: &[DrawInstruction]) {
+        // nobody reads who joins and leaves, the game owns the terminal
+        accept(&self.listener, &mut self.clients, &mut Vec::new());
+        for client in &mut self.clients {
+            for message in client.receive() {
+                Self::handle(client, message, &self.frame);
+            }
+        }
+
+        self.tick += 1;
+        if self.frame.is_none() || board != self.board.as_slice() {
+            let frame = ServerMessage::Frame {
+                tick: self.tick,
+                instructions: board.to_vec(),
+            }
+            .encode();
+            self.board = board.to_vec();
+            for client in self
+                .clients
+                .iter_mut()
+                .filter(|client| client.role.is_some())
+            {
+                client.frame = Some(frame.clone());
+            }
+            self.frame = Some(frame);
+        }
+
+        for client in &mut self.clients {
+            client.flush();
+        }
+        self.clients.retain(|client| client.closed.is_none());
+    }
+
+    /// Every viewer is a spectator, even one that asks to play
+    fn handle(client: &mut Client, message: ClientMessage, frame: &Option<Vec<u8>>) {
+        match (client.role, message) {
+            (None, ClientMessage::Hello(version)) | (None, ClientMessage::Watch(version))
+                if version != VERSION =>
+            {
+                let reason = format!("the server speaks version {}", VERSION);
+                client.send(&ServerMessage::Refused(reason));
+                client.close("unsupported version");
+            }
+            (None, ClientMessage::Hello(_)) | (None, ClientMessage::Watch(_)) => {
+                client.role = Some(Role::Spectator);
+                client.send(&ServerMessage::Welcome(Role::Spectator));
+                client.frame = frame.clone();
+            }
+            (Some(_), ClientMessage::Ping(number)) => client.send(&ServerMessage::Pong(number)),
+            (Some(_), ClientMessage::Bye) => client.close("left"),
+            // a viewer can't steer the game
+            (Some(_), ClientMessage::Input(_)) => (),
+            (_, _) => client.close("unexpected message"),
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use super::super::events::FOCUS_OUT;
+use super::super::get_control;
+use super::super::render::Renderer;
+use super::super::snake_game::config::Config;
+use super::super::snake_game::game::traits::Draw;
+use super::super::snake_game::high_scores::HighScores;
+use super::super::snake_game::SnakeGame;
+use super::super::traits::{Event, SnakeGame as SnakeGameTrait};
+use super::super::update_reason::{Control, UpdateReason};
+use super::server::{MAX_OUTGOING, POLL_INTERVAL};
+use super::socket::{Listener, Socket};
+use super::NetworkError;
+use std::cell::RefCell;
+use std::io;
+use std::io::{Read, Write};
+use std::rc::Rc;
+use std::time::Instant;
+use termion::event as term_event;
+use termion::input::TermRead;
+
+// Commands and options of the telnet protocol (RFC 854, 857, 858, 1073)
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const ECHO: u8 = 1;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const NAWS: u8 = 31;
+
+/// Asks the client to send every key at once without echoing it, and to report its window size
+pub const GREETING: [u8; 9] = [IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD, IAC, DO, NAWS];
+/// Longest subnegotiation kept, a window size only takes 5 bytes
+const MAX_SUBNEGOTIATION: usize = 64;
+/// Longest escape sequence waited for, a longer one is dropped
+const MAX_ESCAPE: usize = 16;
+
+/// Where the decoder is in the commands of the client
+#[derive(Default)]
+enum State {
+    #[default]
+    Data,
+    /// After an IAC
+    Command,
+    /// After a WILL, WONT, DO or DONT, waiting for the option
+    Option(u8),
+    /// Inside a subnegotiation, with its content so far
+    Subnegotiation(Vec<u8>),
+    /// After an IAC inside a subnegotiation
+    SubnegotiationCommand(Vec<u8>),
+}
+
+/// Reads the keys and the window size from the bytes sent by a telnet client
+///
+/// The options that the server has asked for are only acknowledged by the client, every other
+/// option the client offers or asks for is refused.
+#[derive(Default)]
+pub struct TelnetDecoder {
+    state: State,
+    /// Bytes of the keys that haven't been decoded yet, an unfinished escape sequence waits here
+    keys: Vec<u8>,
+    /// Whether the last key was a carriage return, the NUL or line feed after it is dropped
+    after_return: bool,
+    /// Answers to the commands of the client
+    replies: Vec<u8>,
+}
+
+impl TelnetDecoder {
+    /// Returns the events in the given bytes
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+        for &byte in bytes {
+            self.state = match (std::mem::take(&mut self.state), byte) {
+                (State::Data, IAC) => State::Command,
+                (State::Data, byte) | (State::Command, byte @ IAC) => {
+                    self.push_key(byte);
+                    State::Data
+                }
+                (State::Command, SB) => State::Subnegotiation(Vec::new()),
+                (State::Command, command @ WILL..=DONT) => State::Option(command),
+                // go ahead, no operation and the like don't matter
+                (State::Command, _) => State::Data,
+                (State::Option(command), option) => {
+                    self.answer(command, option);
+                    State::Data
+                }
+                (State::Subnegotiation(content), IAC) => State::SubnegotiationCommand(content),
+                // a client sending an endless subnegotiation isn't followed
+                (State::Subnegotiation(content), _)
+                | (State::SubnegotiationCommand(content), IAC)
+                    if content.len() >= MAX_SUBNEGOTIATION =>
+                {
+                    State::Data
+                }
+                (State::Subnegotiation(mut content), byte)
+                | (State::SubnegotiationCommand(mut content), byte @ IAC) => {
+                    content.push(byte);
+                    State::Subnegotiation(content)
+                }
+                (State::SubnegotiationCommand(content), SE) => {
+                    // the keys pressed before the resize come first
+                    events.extend(self.decode_keys());
+                    events.extend(get_window_size(&content));
+                    State::Data
+                }
+                (State::SubnegotiationCommand(_), _) => State::Data,
+            };
+        }
+        events.extend(self.decode_keys());
+        events
+    }
+
+    /// Returns the answers to the commands of the client since the last call
+    pub fn take_replies(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.replies)
+    }
+
+    fn push_key(&mut self, byte: u8) {
+        // a telnet client sends Enter as CR NUL or CR LF
+        if self.after_return && (byte == 0 || byte == b'\n') {
+            self.after_return = false;
+            return;
+        }
+        self.after_return = byte == b'\r';
+        self.keys.push(byte);
+    }
+
+    fn answer(&mut self, command: u8, option: u8) {
+        let reply = match (command, option) {
+            // the client agrees to what the server has asked for
+            (DO, ECHO) | (DO, SUPPRESS_GO_AHEAD) | (WILL, NAWS) => return,
+            (WILL, SUPPRESS_GO_AHEAD) => DO,
+            (WILL, _) => DONT,
+            (DO, _) => WONT,
+            // refusals need no answer
+            _ => return,
+        };
+        self.replies.extend_from_slice(&[IAC, reply, option]);
+    }
+
+    /// Decodes the complete keys, an unfinished escape sequence is kept for the next bytes
+    fn decode_keys(&mut self) -> Vec<Event> {
+        let unfinished = self.keys.len() - get_unfinished_length(&self.keys);
+        let mut rest = self.keys.split_off(unfinished);
+        // a client sending an endless escape sequence isn't waited for
+        if rest.len() > MAX_ESCAPE {
+            rest.clear();
+        }
+        let keys = std::mem::replace(&mut self.keys, rest);
+
+        keys.as_slice()
+            .events()
+            .flatten()
+            .filter_map(|event| match event {
+                term_event::Event::Key(key) => Some(Event::Key(key)),
+                term_event::Event::Unsupported(ref bytes) if bytes == FOCUS_OUT => {
+                    Some(Event::FocusLost)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Returns the length of the escape sequence at the end of the keys that hasn't been finished,
+/// a single escape is the Esc key
+fn get_unfinished_length(keys: &[u8]) -> usize {
+    let start = match keys.iter().rposition(|byte| *byte == 0x1b) {
+        Some(start) => start,
+        None => return 0,
+    };
+    match &keys[start + 1..] {
+        [b'[', parameters @ ..] if parameters.iter().all(|byte| (0x30..=0x3f).contains(byte)) => {
+            keys.len() - start
+        }
+        [b'O'] => keys.len() - start,
+        _ => 0,
+    }
+}
+
+/// Returns the resize in a NAWS subnegotiation, an unknown size is 0
+fn get_window_size(content: &[u8]) -> Option<Event> {
+    match *content {
+        [NAWS, width_high, width_low, height_high, height_low] => {
+            let columns = u16::from_be_bytes([width_high, width_low]) as usize;
+            let rows = u16::from_be_bytes([height_high, height_low]) as usize;
+            match columns > 0 && rows > 0 {
+                true => Some(Event::Resize(columns, rows)),
+                false => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A game played by a telnet client, with its own timer
+struct Session {
+    socket: Socket,
+    address: String,
+    decoder: TelnetDecoder,
+    snake_game: SnakeGame,
+    renderer: Renderer,
+    /// Whether the WASD keys steer the second player
+    versus: bool,
+    next_tick: Instant,
+    /// Bytes that the socket hasn't taken yet
+    outgoing: Vec<u8>,
+    /// Why the connection is closed, `None` while it is open
+    closed: Option<String>,
+}
+
+impl Session {
+    fn new(
+        socket: Socket,
+        address: String,
+        snake_game: SnakeGame,
+        mut renderer: Renderer,
+        versus: bool,
+    ) -> io::Result<Session> {
+        socket.set_nonblocking(true)?;
+        // the bytes of the terminal never contain an IAC, UTF-8 has no 255
+        let mut outgoing = GREETING.to_vec();
+        renderer.enter_screen(&mut outgoing);
+        Ok(Session {
+            socket,
+            address,
+            decoder: TelnetDecoder::default(),
+            next_tick: Instant::now() + snake_game.get_tick_interval(),
+            snake_game,
+            renderer,
+            versus,
+            outgoing,
+            closed: None,
+        })
+    }
+
+    /// Handles the keys of the player, moves the game on if its tick has come and sends the
+    /// cells that have changed
+    fn poll(&mut self, now: Instant) {
+        for event in self.receive() {
+            self.handle(event);
+        }
+        self.outgoing.extend(self.decoder.take_replies());
+        if now >= self.next_tick {
+            self.snake_game.update(UpdateReason::Time);
+            self.next_tick = now + self.snake_game.get_tick_interval();
+        }
+
+        if self.snake_game.should_quit() {
+            self.renderer.leave_screen(&mut self.outgoing);
+            self.flush();
+            self.close("left");
+            return;
+        }
+        let frame = self.snake_game.draw();
+        // a round can't go on while it doesn't fit into the window
+        let playing = self.snake_game.is_running() && !self.snake_game.is_paused();
+        if playing && !self.renderer.fits(&frame) {
+            self.snake_game
+                .update(UpdateReason::Control(Control::Pause));
+        }
+        self.renderer.paint(&mut self.outgoing, frame);
+        self.flush();
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Key(key) => {
+                let paused = self.snake_game.is_paused();
+                if let Some(control) = get_control(key, self.versus, paused) {
+                    self.snake_game.update(UpdateReason::Control(control));
+                }
+            }
+            Event::Resize(columns, rows) => {
+                self.renderer.resize((columns, rows));
+                self.renderer.enter_screen(&mut self.outgoing);
+            }
+            Event::FocusLost => self
+                .snake_game
+                .update(UpdateReason::Control(Control::Pause)),
+            Event::Time | Event::Suspend | Event::Continue => (),
+        }
+    }
+
+    fn close(&mut self, reason: &str) {
+        if self.closed.is_none() {
+            self.closed = Some(reason.to_string());
+        }
+    }
+
+    /// Reads the events that have arrived
+    fn receive(&mut self) -> Vec<Event> {
+        let mut buffer = [0; 1024];
+        let mut events = Vec::new();
+        loop {
+            match self.socket.read(&mut buffer) {
+                Ok(0) => {
+                    self.close("disconnected");
+                    break;
+                }
+                Ok(count) => events.extend(self.decoder.push(&buffer[..count])),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.close(&error.to_string());
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    /// Writes as much of the outgoing bytes as the socket takes
+    fn flush(&mut self) {
+        while !self.outgoing.is_empty() {
+            match self.socket.write(&self.outgoing) {
+                Ok(0) => {
+                    self.close("disconnected");
+                    return;
+                }
+                Ok(count) => {
+                    self.outgoing.drain(..count);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.close(&error.to_string());
+                    return;
+                }
+            }
+        }
+        if self.outgoing.len() > MAX_OUTGOING {
+            self.close("too slow");
+        }
+    }
+}
+
+/// Hosts a game of its own for every telnet client, the results go into one high-score table
+pub struct TelnetServer {
+    listener: Listener,
+    config: Config,
+    /// Seed of every game, each game gets a random one if missing
+    seed: Option<u64>,
+    /// The renderer every session starts with
+    renderer: Renderer,
+    high_scores: Rc<RefCell<HighScores>>,
+    sessions: Vec<Session>,
+    /// What has happened since the last look, for the log of the server
+    notices: Vec<String>,
+}
+
+impl TelnetServer {
+    /// Starts listening on the given address, the games are created from the given settings
+    pub fn bind(
+        address: &str,
+        config: &Config,
+        seed: Option<u64>,
+        renderer: Renderer,
+        high_scores: HighScores,
+    ) -> Result<TelnetServer, NetworkError> {
+        Ok(TelnetServer {
+            listener: Listener::bind(address)?,
+            config: config.clone(),
+            seed,
+            renderer,
+            high_scores: Rc::new(RefCell::new(high_scores)),
+            sessions: Vec::new(),
+            notices: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<String, NetworkError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Hosts the games forever, printing who comes and goes
+    pub fn run(&mut self) {
+        loop {
+            self.poll();
+            for notice in self.take_notices() {
+                println!("{}", notice);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Starts a game for every new client and moves every game on
+    pub fn poll(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((socket, address)) => self.start(socket, address),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    self.notices
+                        .push(format!("Couldn't accept a client: {}", error));
+                    break;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        for session in &mut self.sessions {
+            session.poll(now);
+        }
+
+        let (closed, open) = std::mem::take(&mut self.sessions)
+            .into_iter()
+            .partition(|session| session.closed.is_some());
+        self.sessions = open;
+        for session in closed {
+            self.notices.push(format!(
+                "{} has left: {} (score {})",
+                session.address,
+                session.closed.unwrap_or_default(),
+                session.snake_game.get_score().points
+            ));
+        }
+    }
+
+    /// Returns what has happened since the last call
+    pub fn take_notices(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notices)
+    }
+
+    fn start(&mut self, socket: Socket, address: String) {
+        let config = Config {
+            seed: self.seed.unwrap_or_else(rand::random),
+            ..self.config.clone()
+        };
+        let snake_game = SnakeGame::new_sharing_scores(&config, Box::new(self.high_scores.clone()));
+        let versus = config.versus && config.opponent.is_none();
+        match Session::new(
+            socket,
+            address.clone(),
+            snake_game,
+            self.renderer.clone(),
+            versus,
+        ) {
+            Ok(session) => {
+                self.notices.push(format!(
+                    "{} has started a game with seed {}",
+                    address, config.seed
+                ));
+                self.sessions.push(session);
+            }
+            Err(error) => self.notices.push(format!("{}: {}", address, error)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use super::events::TerminalEventStream;
+use super::get_terminal_size;
+use super::render::Renderer;
+use super::replay::{FinalScore, Player, ReplayError};
+use super::snake_game::game::{draw_instruction::DrawInstruction, traits::Draw, vec2::Vec2};
+use super::traits::*;
+use std::time::Duration;
+use termion::event::Key;
+use termion::raw::IntoRawMode;
+
+/// Number of ticks a seek jumps
+const SEEK_TICKS: usize = 100;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 16.0;
+
+/// Shows a replay in the terminal with seek and step controls
+pub struct TerminalPlayback {
+    player: Player,
+    renderer: Renderer,
+    speed: f64,
+    paused: bool,
+    quit: bool,
+}
+
+impl TerminalPlayback {
+    pub fn new(player: Player, renderer: Renderer, speed: f64) -> TerminalPlayback {
+        TerminalPlayback {
+            player,
+            renderer,
+            speed: speed.clamp(MIN_SPEED, MAX_SPEED),
+            paused: false,
+            quit: false,
+        }
+    }
+
+    /// Plays the replay until the user quits, then checks the outcome of the whole replay
+    pub fn main(&mut self) -> Result<FinalScore, ReplayError> {
+        let tick_interval = TickInterval::new(self.get_tick_interval());
+        let event_stream = TerminalEventStream::start(tick_interval.clone());
+
+        let mut stdout = std::io::stdout().into_raw_mode().unwrap();
+        self.renderer.resize(get_terminal_size());
+        self.renderer.enter_screen(&mut stdout);
+        self.renderer.paint(&mut stdout, self.draw());
+        loop {
+            match event_stream.recv().expect("Channel has stopped.") {
+                Event::Time if !self.paused => {
+                    self.player.step();
+                }
+                Event::Key(key) => self.control(key),
+                Event::Continue => self.renderer.enter_screen(&mut stdout),
+                Event::Resize(columns, rows) => {
+                    self.renderer.resize((columns, rows));
+                    self.renderer.enter_screen(&mut stdout);
+                    self.paused |= !self.renderer.fits(&self.draw());
+                }
+                Event::Time | Event::FocusLost | Event::Suspend => continue,
+            };
+            if self.quit {
+                break;
+            }
+            tick_interval.set(self.get_tick_interval());
+            self.renderer.paint(&mut stdout, self.draw());
+        }
+
+        self.renderer.leave_screen(&mut stdout);
+        drop(stdout);
+
+        self.player.verify()
+    }
+
+    fn control(&mut self, key: Key) {
+        match key {
+            Key::Char(' ') => self.paused = !self.paused,
+            Key::Right | Key::Char('.') if self.paused => {
+                self.player.step();
+            }
+            Key::Char('+') => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+            Key::Char('-') => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+            Key::Char(']') => self.player.seek(self.player.get_tick() + SEEK_TICKS),
+            Key::Char('[') => self
+                .player
+                .seek(self.player.get_tick().saturating_sub(SEEK_TICKS)),
+            Key::Home => self.player.seek(0),
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => self.quit = true,
+            _ => (),
+        }
+    }
+
+    fn get_tick_interval(&self) -> Duration {
+        self.player
+            .get_snake_game()
+            .get_tick_interval()
+            .div_f64(self.speed)
+    }
+
+    fn get_status(&self) -> String {
+        let state = match (self.player.is_finished(), self.paused) {
+            (true, _) => " END",
+            (false, true) => " PAUSED",
+            (false, false) => "",
+        };
+        format!(
+            " REPLAY {}/{} x{}{} ",
+            self.player.get_tick(),
+            self.player.get_total_ticks(),
+            self.speed,
+            state
+        )
+    }
+}
+
+impl Draw for TerminalPlayback {
+    fn draw(&self) -> Vec<DrawInstruction> {
+        let mut paint = self.player.get_snake_game().draw();
+
+        // the status goes over the top border, cut to the width of the board
+        let width = paint.first().map_or(0, |row| row.shape.len());
+        let status: String = self.get_status().chars().take(width).collect();
+        paint.push(DrawInstruction::text(Vec2 { x: 0, y: 0 }, &status));
+
+        paint
+    }
+}
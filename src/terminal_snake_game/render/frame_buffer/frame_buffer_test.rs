@@ -0,0 +1,112 @@
+use super::*;
+
+fn cell(glyph: char) -> Cell {
+    Cell {
+        glyph,
+        fg: Color::Default,
+        bg: Color::Default,
+    }
+}
+
+fn row(y: usize, text: &str) -> FrameBuffer {
+    let mut frame = FrameBuffer::default();
+    for (x, glyph) in text.chars().enumerate() {
+        frame.set(x, y, cell(glyph));
+    }
+    frame
+}
+
+#[test]
+fn it_keeps_the_last_painted_cell() {
+    let mut frame = row(1, "ab");
+    frame.set(1, 1, cell('c'));
+
+    assert_eq!(frame.get(1, 1), Some(cell('c')));
+    assert_eq!(frame.get(0, 0), None);
+    assert_eq!(frame.get(5, 1), None);
+}
+
+#[test]
+fn it_writes_only_the_changed_cells() {
+    let mut screen = Screen::default();
+    assert_eq!(
+        screen.update(row(0, "abcd"), ColorSupport::None),
+        "\x1b[1;1Habcd\x1b[1;1H"
+    );
+
+    assert_eq!(screen.update(row(0, "abcd"), ColorSupport::None), "");
+    assert_eq!(
+        screen.update(row(0, "abcX"), ColorSupport::None),
+        "abcX\x1b[1;1H"
+    );
+}
+
+#[test]
+fn it_writes_short_gaps_again_instead_of_moving_the_cursor() {
+    let mut screen = Screen::default();
+    screen.update(row(0, "abcdefghij"), ColorSupport::None);
+
+    // GIVEN a gap of 2 cells, writing them is shorter than moving the cursor
+    assert_eq!(
+        screen.update(row(0, "XbcYefghiZ"), ColorSupport::None),
+        "XbcY\x1b[5CZ\x1b[1;1H"
+    );
+}
+
+#[test]
+fn it_moves_the_cursor_over_cells_it_cant_write_again() {
+    let mut screen = Screen::default();
+    let mut frame = row(0, "ab");
+    frame.set(3, 0, cell('d'));
+    screen.update(frame, ColorSupport::None);
+
+    let mut changed = row(0, "Xb");
+    changed.set(3, 0, cell('Y'));
+
+    assert_eq!(
+        screen.update(changed, ColorSupport::None),
+        "X\x1b[2CY\x1b[1;1H"
+    );
+}
+
+#[test]
+fn it_blanks_the_cells_that_arent_painted_anymore() {
+    let mut screen = Screen::default();
+    screen.update(row(2, "abc"), ColorSupport::None);
+
+    assert_eq!(
+        screen.update(row(2, "a"), ColorSupport::None),
+        "\x1b[3;2H  \x1b[1;1H"
+    );
+}
+
+#[test]
+fn it_keeps_the_colors_between_cells() {
+    let red = Cell {
+        fg: Color::Ansi(9),
+        ..cell('r')
+    };
+    let mut frame = FrameBuffer::default();
+    frame.set(0, 0, red);
+    frame.set(1, 0, red);
+    frame.set(2, 0, cell('.'));
+    frame.set(0, 1, red);
+
+    assert_eq!(
+        Screen::default().update(frame, ColorSupport::Basic),
+        "\x1b[1;1H\x1b[91mrr\x1b[39m.\x1b[2;1H\x1b[91mr\x1b[39m\x1b[1;1H"
+    );
+}
+
+#[test]
+fn it_paints_everything_after_being_invalidated() {
+    let mut screen = Screen::default();
+    screen.update(row(0, "ab"), ColorSupport::None);
+
+    screen.invalidate();
+
+    assert_eq!(
+        screen.update(row(0, "ab"), ColorSupport::None),
+        "\x1b[1;1Hab\x1b[1;1H"
+    );
+}
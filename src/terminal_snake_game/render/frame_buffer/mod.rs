@@ -0,0 +1,168 @@
+use super::theme::{Color, ColorSupport};
+
+/// A character cell of the terminal with the colors it is painted with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub glyph: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    /// What is left of a cell that isn't painted anymore
+    const BLANK: Cell = Cell {
+        glyph: ' ',
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+}
+
+/// The cells of a frame row by row, the ones that aren't painted are `None`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameBuffer {
+    rows: Vec<Vec<Option<Cell>>>,
+}
+
+impl FrameBuffer {
+    /// Paints the cell, over the one painted there before
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        if self.rows.len() <= y {
+            self.rows.resize(y + 1, Vec::new());
+        }
+        let row = &mut self.rows[y];
+        if row.len() <= x {
+            row.resize(x + 1, None);
+        }
+        row[x] = Some(cell);
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Cell> {
+        self.rows
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .flatten()
+    }
+
+    fn get_width(&self, y: usize) -> usize {
+        self.rows.get(y).map_or(0, |row| row.len())
+    }
+}
+
+/// Where the terminal writes the next character and with which colors
+#[derive(Clone, Copy)]
+struct Pen {
+    /// `None` if it isn't known
+    cursor: Option<(usize, usize)>,
+    fg: Color,
+    bg: Color,
+}
+
+impl Pen {
+    fn write(&mut self, output: &mut String, cell: Cell, colors: ColorSupport) {
+        if cell.fg != self.fg {
+            output.push_str(&colors.get_escape(cell.fg, false));
+            self.fg = cell.fg;
+        }
+        if cell.bg != self.bg {
+            output.push_str(&colors.get_escape(cell.bg, true));
+            self.bg = cell.bg;
+        }
+        output.push(cell.glyph);
+        self.cursor = self.cursor.map(|(x, y)| (x + 1, y));
+    }
+
+    /// Moves the cursor the cheapest way: a few unchanged cells of the frame are written again
+    /// when that is shorter than a cursor movement
+    fn move_to(
+        &mut self,
+        output: &mut String,
+        (x, y): (usize, usize),
+        frame: &FrameBuffer,
+        colors: ColorSupport,
+    ) {
+        match self.cursor {
+            Some(cursor) if cursor == (x, y) => return,
+            Some((from, row)) if row == y && from < x => {
+                let forward = format!("\x1b[{}C", x - from);
+                let skipped: Option<Vec<Cell>> = (from..x).map(|gap| frame.get(gap, y)).collect();
+                let mut pen = *self;
+                let mut rewritten = String::new();
+                for cell in skipped.unwrap_or_default() {
+                    pen.write(&mut rewritten, cell, colors);
+                }
+                if pen.cursor == Some((x, y)) && rewritten.len() < forward.len() {
+                    output.push_str(&rewritten);
+                    *self = pen;
+                    return;
+                }
+                output.push_str(&forward);
+            }
+            _ => output.push_str(&termion::cursor::Goto(x as u16 + 1, y as u16 + 1).to_string()),
+        }
+        self.cursor = Some((x, y));
+    }
+
+    /// Goes back to the colors of the terminal
+    fn reset(&mut self, output: &mut String, colors: ColorSupport) {
+        if self.fg != Color::Default {
+            output.push_str(&colors.get_escape(Color::Default, false));
+            self.fg = Color::Default;
+        }
+        if self.bg != Color::Default {
+            output.push_str(&colors.get_escape(Color::Default, true));
+            self.bg = Color::Default;
+        }
+    }
+}
+
+/// What the terminal shows, so a frame can be painted by writing only the cells that have changed
+#[derive(Clone, Debug, Default)]
+pub struct Screen {
+    painted: FrameBuffer,
+    /// Position of the cursor after the last frame, `None` if it isn't known
+    cursor: Option<(usize, usize)>,
+}
+
+impl Screen {
+    /// Forgets what the terminal shows, the next frame is painted whole
+    pub fn invalidate(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the output that turns the painted frame into the given one, cells that aren't
+    /// painted anymore are blanked; the cursor is left in the top left corner
+    pub fn update(&mut self, frame: FrameBuffer, colors: ColorSupport) -> String {
+        let mut output = String::new();
+        let mut pen = Pen {
+            cursor: self.cursor,
+            fg: Color::Default,
+            bg: Color::Default,
+        };
+
+        let height = frame.rows.len().max(self.painted.rows.len());
+        for y in 0..height {
+            let width = frame.get_width(y).max(self.painted.get_width(y));
+            for x in 0..width {
+                let cell = match (frame.get(x, y), self.painted.get(x, y)) {
+                    (Some(cell), painted) if painted != Some(cell) => cell,
+                    (None, Some(_)) => Cell::BLANK,
+                    _ => continue,
+                };
+                pen.move_to(&mut output, (x, y), &frame, colors);
+                pen.write(&mut output, cell, colors);
+            }
+        }
+
+        if !output.is_empty() {
+            pen.reset(&mut output, colors);
+            output.push_str(&termion::cursor::Goto(1, 1).to_string());
+            self.cursor = Some((0, 0));
+        }
+        self.painted = frame;
+        output
+    }
+}
+
+#[cfg(test)]
+mod frame_buffer_test;
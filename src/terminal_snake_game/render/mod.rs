@@ -0,0 +1,194 @@
+mod frame_buffer;
+pub mod theme;
+use super::snake_game::game::draw_instruction::DrawInstruction;
+use super::snake_game::game::vec2::Vec2;
+use frame_buffer::{Cell, FrameBuffer, Screen};
+use std::io::Write;
+use theme::{ColorSupport, Theme};
+
+/// Asks the terminal to report focus changes
+const FOCUS_REPORTING_ON: &str = "\x1b[?1004h";
+const FOCUS_REPORTING_OFF: &str = "\x1b[?1004l";
+
+/// Writes draw instructions to a terminal, only the cells that have changed since the last frame
+#[derive(Clone)]
+pub struct Renderer {
+    theme: Theme,
+    colors: ColorSupport,
+    screen: Screen,
+    /// Columns and rows of the terminal, frames are painted from the top left corner if unknown
+    terminal_size: Option<(usize, usize)>,
+}
+
+impl Renderer {
+    pub fn new(theme: Theme, colors: ColorSupport) -> Renderer {
+        Renderer {
+            theme,
+            colors,
+            screen: Screen::default(),
+            terminal_size: None,
+        }
+    }
+
+    /// Prepares the terminal for the game, the next frame is painted whole
+    pub fn enter_screen(&mut self, stdout: &mut impl Write) {
+        self.invalidate();
+        write!(
+            stdout,
+            "{}{}{}",
+            termion::cursor::Hide,
+            termion::clear::All,
+            FOCUS_REPORTING_ON
+        )
+        .expect("Couldn't write stdout");
+    }
+
+    /// Gives back the terminal in a usable state
+    pub fn leave_screen(&mut self, stdout: &mut impl Write) {
+        self.invalidate();
+        write!(
+            stdout,
+            "{}{}{}{}",
+            FOCUS_REPORTING_OFF,
+            termion::cursor::Show,
+            termion::clear::All,
+            termion::cursor::Goto(1, 1)
+        )
+        .expect("Couldn't write stdout");
+        stdout.flush().expect("Couldn't flush stdout");
+    }
+
+    /// Sets the size of the terminal, frames are centered in it; the screen has to be entered
+    /// again to clear what the terminal shows
+    pub fn resize(&mut self, terminal_size: (usize, usize)) {
+        self.terminal_size = Some(terminal_size);
+        self.invalidate();
+    }
+
+    /// Returns whether the frame fits into the terminal
+    pub fn fits(&self, instructions: &[DrawInstruction]) -> bool {
+        let (width, height) = get_size(instructions);
+        self.terminal_size
+            .is_none_or(|(columns, rows)| width <= columns && height <= rows)
+    }
+
+    /// Forgets what the terminal shows, so the next frame is painted whole
+    pub fn invalidate(&mut self) {
+        self.screen.invalidate();
+    }
+
+    /// Paints a frame, later instructions paint over the earlier ones
+    pub fn paint(&mut self, stdout: &mut impl Write, instructions: Vec<DrawInstruction>) {
+        let mut frame = FrameBuffer::default();
+        for instruction in self.place(instructions) {
+            let (x, y) = (instruction.pos.x, instruction.pos.y);
+            for (offset, directed_symbol) in instruction.shape.iter().enumerate() {
+                let style = self.theme.get_style(directed_symbol);
+                let cell = Cell {
+                    glyph: style.glyph,
+                    fg: self.colors.convert(style.fg),
+                    bg: self.colors.convert(style.bg),
+                };
+                frame.set(x + offset, y, cell);
+            }
+        }
+
+        let output = self.screen.update(frame, self.colors);
+        if !output.is_empty() {
+            stdout
+                .write_all(output.as_bytes())
+                .expect("Couldn't write stdout");
+            stdout.flush().expect("Couldn't flush stdout");
+        }
+    }
+
+    /// Moves the frame into the middle of the terminal with a border around it if there is room,
+    /// a frame that doesn't fit is replaced by a message
+    fn place(&self, instructions: Vec<DrawInstruction>) -> Vec<DrawInstruction> {
+        let (columns, rows) = match self.terminal_size {
+            Some(terminal_size) => terminal_size,
+            None => return instructions,
+        };
+        let (width, height) = get_size(&instructions);
+        if width > columns || height > rows {
+            let message = [
+                "The terminal is too small".to_string(),
+                format!("The game needs {}x{} cells", width, height),
+                format!("The terminal has {}x{}", columns, rows),
+            ];
+            return message
+                .iter()
+                .take(rows)
+                .enumerate()
+                .map(|(y, line)| {
+                    let line: String = line.chars().take(columns).collect();
+                    DrawInstruction::text(Vec2 { x: 0, y }, &line)
+                })
+                .collect();
+        }
+
+        let offset = Vec2 {
+            x: (columns - width) / 2,
+            y: (rows - height) / 2,
+        };
+        let mut placed = Vec::new();
+        if width > 0 && columns >= width + 2 && rows >= height + 2 {
+            placed.extend(get_border(&offset, width, height));
+        }
+        placed.extend(instructions.into_iter().map(|instruction| DrawInstruction {
+            pos: Vec2 {
+                x: instruction.pos.x + offset.x,
+                y: instruction.pos.y + offset.y,
+            },
+            ..instruction
+        }));
+        placed
+    }
+}
+
+/// Returns the columns and rows the frame covers from the top left corner
+fn get_size(instructions: &[DrawInstruction]) -> (usize, usize) {
+    instructions
+        .iter()
+        .fold((0, 0), |(width, height), instruction| {
+            (
+                width.max(instruction.pos.x + instruction.shape.len()),
+                height.max(instruction.pos.y + 1),
+            )
+        })
+}
+
+/// Returns the border around the area of the given size at the given position
+fn get_border(pos: &Vec2, width: usize, height: usize) -> Vec<DrawInstruction> {
+    let edge = format!("+{}+", "-".repeat(width));
+    let mut border = vec![
+        DrawInstruction::text(
+            Vec2 {
+                x: pos.x - 1,
+                y: pos.y - 1,
+            },
+            &edge,
+        ),
+        DrawInstruction::text(
+            Vec2 {
+                x: pos.x - 1,
+                y: pos.y + height,
+            },
+            &edge,
+        ),
+    ];
+    for y in pos.y..pos.y + height {
+        border.push(DrawInstruction::text(Vec2 { x: pos.x - 1, y }, "|"));
+        border.push(DrawInstruction::text(
+            Vec2 {
+                x: pos.x + width,
+                y,
+            },
+            "|",
+        ));
+    }
+    border
+}
+
+#[cfg(test)]
+mod render_test;
@@ -0,0 +1,197 @@
+use super::super::snake_game::game::direction::{Directed, Direction};
+use super::super::snake_game::game::draw_instruction::Symbol;
+use super::super::snake_game::game::traits::Draw;
+use super::super::snake_game::game::{draw_instruction::DrawInstruction, vec2::Vec2};
+use super::*;
+
+struct Frame(Vec<DrawInstruction>);
+
+impl Draw for Frame {
+    fn draw(&self) -> Vec<DrawInstruction> {
+        self.0.clone()
+    }
+}
+
+fn unicode() -> Renderer {
+    Renderer::new(Theme::classic(), ColorSupport::None)
+}
+
+fn ascii() -> Renderer {
+    Renderer::new(Theme::bundled("ascii").unwrap(), ColorSupport::None)
+}
+
+fn paint(renderer: &mut Renderer, frame: &Frame) -> String {
+    let mut output = Vec::new();
+    renderer.paint(&mut output, frame.draw());
+    String::from_utf8(output).unwrap()
+}
+
+fn frame() -> Frame {
+    Frame(vec![
+        DrawInstruction {
+            pos: Vec2 { x: 0, y: 0 },
+            shape: vec![
+                Directed(Direction::Left, Symbol::SnakeHead(0)),
+                Directed(Direction::Up, Symbol::Apple),
+            ],
+        },
+        DrawInstruction::text(Vec2 { x: 2, y: 1 }, "hi"),
+    ])
+}
+
+#[test]
+fn it_can_paint_unicode_glyphs() {
+    let output = paint(&mut unicode(), &frame());
+
+    assert_eq!(output, "\x1b[1;1H◄♥\x1b[2;3Hhi\x1b[1;1H");
+}
+
+#[test]
+fn it_can_paint_ascii_glyphs() {
+    let output = paint(&mut ascii(), &frame());
+
+    assert_eq!(output, "\x1b[1;1H<@\x1b[2;3Hhi\x1b[1;1H");
+}
+
+#[test]
+fn it_paints_the_snakes_in_the_color_of_their_player() {
+    let frame = Frame(vec![DrawInstruction {
+        pos: Vec2 { x: 0, y: 0 },
+        shape: vec![
+            Directed(Direction::Up, Symbol::SnakeBody(0)),
+            Directed(Direction::Up, Symbol::SnakeHead(0)),
+            Directed(Direction::Up, Symbol::SnakeHead(1)),
+            Directed(Direction::Up, Symbol::Empty),
+        ],
+    }]);
+
+    let mut renderer = Renderer::new(Theme::bundled("ascii").unwrap(), ColorSupport::Extended);
+    let output = paint(&mut renderer, &frame);
+
+    assert_eq!(
+        output,
+        "\x1b[1;1H\x1b[38;5;10m#^\x1b[38;5;12m^\x1b[39m \x1b[1;1H"
+    );
+}
+
+#[test]
+fn it_paints_every_kind_of_food() {
+    let frame = Frame(vec![DrawInstruction {
+        pos: Vec2 { x: 0, y: 0 },
+        shape: vec![
+            Directed(Direction::Up, Symbol::GoldenApple),
+            Directed(Direction::Up, Symbol::ShrinkingFood),
+            Directed(Direction::Up, Symbol::SpeedUp),
+            Directed(Direction::Up, Symbol::SlowDown),
+            Directed(Direction::Up, Symbol::BonusFood),
+            Directed(Direction::Up, Symbol::Poison),
+        ],
+    }]);
+
+    assert_eq!(paint(&mut unicode(), &frame), "\x1b[1;1H♦♣»«☼×\x1b[1;1H");
+    assert_eq!(paint(&mut ascii(), &frame), "\x1b[1;1H$%!~*x\x1b[1;1H");
+}
+
+#[test]
+fn it_paints_the_hazards() {
+    let frame = Frame(vec![DrawInstruction {
+        pos: Vec2 { x: 0, y: 0 },
+        shape: vec![
+            Directed(Direction::Up, Symbol::Gate { open: false }),
+            Directed(Direction::Up, Symbol::Gate { open: true }),
+            Directed(Direction::Up, Symbol::Portal('3')),
+            Directed(Direction::Up, Symbol::Patrol),
+        ],
+    }]);
+
+    assert_eq!(paint(&mut unicode(), &frame), "\x1b[1;1H▒░3■\x1b[1;1H");
+    assert_eq!(paint(&mut ascii(), &frame), "\x1b[1;1H+-3O\x1b[1;1H");
+}
+
+#[test]
+fn it_paints_the_background_of_the_theme() {
+    let theme = Theme::parse("empty: bg=blue\napple: fg=#ff0000 bg=blue").unwrap();
+    let frame = Frame(vec![DrawInstruction {
+        pos: Vec2 { x: 0, y: 0 },
+        shape: vec![
+            Directed(Direction::Up, Symbol::Empty),
+            Directed(Direction::Up, Symbol::Apple),
+            Directed(Direction::Up, Symbol::Wall),
+        ],
+    }]);
+
+    assert_eq!(
+        paint(
+            &mut Renderer::new(theme.clone(), ColorSupport::TrueColor),
+            &frame
+        ),
+        "\x1b[1;1H\x1b[48;5;4m \x1b[38;2;255;0;0m♥\x1b[39m\x1b[49m#\x1b[1;1H"
+    );
+    assert_eq!(
+        paint(&mut Renderer::new(theme, ColorSupport::Basic), &frame),
+        "\x1b[1;1H\x1b[44m \x1b[91m♥\x1b[39m\x1b[49m#\x1b[1;1H"
+    );
+}
+
+#[test]
+fn it_paints_only_what_has_changed() {
+    let mut renderer = unicode();
+    paint(&mut renderer, &frame());
+    assert_eq!(paint(&mut renderer, &frame()), "");
+
+    let moved = Frame(vec![
+        DrawInstruction {
+            pos: Vec2 { x: 0, y: 0 },
+            shape: vec![
+                Directed(Direction::Left, Symbol::SnakeHead(0)),
+                Directed(Direction::Up, Symbol::SnakeBody(0)),
+            ],
+        },
+        DrawInstruction::text(Vec2 { x: 2, y: 1 }, "hi"),
+    ]);
+    assert_eq!(paint(&mut renderer, &moved), "◄#\x1b[1;1H");
+}
+
+#[test]
+fn it_paints_everything_again_after_entering_the_screen() {
+    let mut renderer = unicode();
+    paint(&mut renderer, &frame());
+
+    renderer.enter_screen(&mut Vec::new());
+
+    assert_eq!(
+        paint(&mut renderer, &frame()),
+        paint(&mut unicode(), &frame())
+    );
+}
+
+#[test]
+fn it_centers_the_frame_in_a_larger_terminal() {
+    let mut renderer = unicode();
+    renderer.resize((7, 5));
+    let frame = Frame(vec![DrawInstruction::text(Vec2 { x: 0, y: 0 }, "ab")]);
+
+    // GIVEN room for a border around the frame
+    assert!(renderer.fits(&frame.draw()));
+    assert_eq!(
+        paint(&mut renderer, &frame),
+        "\x1b[2;2H+--+\x1b[3;2H|ab|\x1b[4;2H+--+\x1b[1;1H"
+    );
+
+    // GIVEN no room for a border
+    renderer.resize((3, 1));
+    assert_eq!(paint(&mut renderer, &frame), "\x1b[1;1Hab\x1b[1;1H");
+}
+
+#[test]
+fn it_tells_if_the_terminal_is_too_small() {
+    let mut renderer = unicode();
+    renderer.resize((30, 2));
+    let frame = Frame(vec![DrawInstruction::text(Vec2 { x: 0, y: 2 }, "ab")]);
+
+    assert!(!renderer.fits(&frame.draw()));
+    assert_eq!(
+        paint(&mut renderer, &frame),
+        "\x1b[1;1HThe terminal is too small\x1b[2;1HThe game needs 2x3 cells\x1b[1;1H"
+    );
+}
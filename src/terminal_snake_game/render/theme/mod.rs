@@ -0,0 +1,580 @@
+use super::super::snake_game::game::{
+    direction::{Directed, Direction},
+    draw_instruction::Symbol,
+    power_up::PowerUp,
+};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Themes shipped with the game besides the classic one, they are based on the classic theme
+const BUNDLED: [(&str, &str); 3] = [
+    ("ascii", include_str!("../../../../themes/ascii.txt")),
+    ("mono", include_str!("../../../../themes/mono.txt")),
+    ("neon", include_str!("../../../../themes/neon.txt")),
+];
+
+/// Names of the 16 basic terminal colors in the order of their numbers
+const COLOR_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// The usual RGB values of the 16 basic terminal colors
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Intensities of a channel in the 6x6x6 color cube of 256-color terminals
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Color of a glyph or of the cell behind it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    /// The color the terminal uses when nothing is set
+    Default,
+    /// Color of the terminal's palette, 0-15 are the basic colors
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Parses `default`, a basic color name like `bright-red`, a palette number or `#rrggbb`
+    pub fn parse(text: &str) -> Option<Color> {
+        if text == "default" {
+            return Some(Self::Default);
+        }
+        if let Some(index) = COLOR_NAMES.iter().position(|name| *name == text) {
+            return Some(Self::Ansi(index as u8));
+        }
+        if let Some(hex) = text.strip_prefix('#') {
+            let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+            return match hex.len() {
+                6 => Some(Self::Rgb(channel(0)?, channel(2)?, channel(4)?)),
+                _ => None,
+            };
+        }
+        text.parse().ok().map(Self::Ansi)
+    }
+
+    /// Returns the RGB value of the color, None for the default one
+    fn get_rgb(&self) -> Option<(u8, u8, u8)> {
+        match *self {
+            Self::Default => None,
+            Self::Ansi(index) if index < 16 => Some(BASIC_RGB[index as usize]),
+            Self::Ansi(index) if index >= 232 => {
+                let gray = 8 + 10 * (index - 232);
+                Some((gray, gray, gray))
+            }
+            Self::Ansi(index) => {
+                let index = index - 16;
+                Some((
+                    CUBE_LEVELS[(index / 36) as usize],
+                    CUBE_LEVELS[(index / 6 % 6) as usize],
+                    CUBE_LEVELS[(index % 6) as usize],
+                ))
+            }
+            Self::Rgb(r, g, b) => Some((r, g, b)),
+        }
+    }
+}
+
+/// Returns the palette color closest to the RGB value among the given palette numbers
+fn get_closest(rgb: (u8, u8, u8), palette: impl Iterator<Item = u8>) -> Color {
+    let distance = |index: &u8| {
+        let (r, g, b) = Color::Ansi(*index).get_rgb().unwrap_or_default();
+        let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        channel(r, rgb.0) + channel(g, rgb.1) + channel(b, rgb.2)
+    };
+    palette
+        .min_by_key(distance)
+        .map_or(Color::Default, Color::Ansi)
+}
+
+/// The colors the terminal can show
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorSupport {
+    /// Nothing is colored
+    None,
+    /// The 16 basic colors
+    Basic,
+    /// The 256 colors of the palette
+    Extended,
+    /// Any RGB color
+    TrueColor,
+}
+
+impl ColorSupport {
+    pub const ALL: [ColorSupport; 4] = [Self::None, Self::Basic, Self::Extended, Self::TrueColor];
+
+    pub fn from_name(name: &str) -> Option<ColorSupport> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|colors| colors.get_name() == name)
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Basic => "16",
+            Self::Extended => "256",
+            Self::TrueColor => "truecolor",
+        }
+    }
+
+    /// Guesses the colors of the terminal from the environment
+    pub fn detect() -> ColorSupport {
+        let var = |name: &str| std::env::var(name).ok();
+        Self::from_env(var("NO_COLOR"), var("COLORTERM"), var("TERM"))
+    }
+
+    /// Guesses the colors from the values of `NO_COLOR`, `COLORTERM` and `TERM`
+    pub fn from_env(
+        no_color: Option<String>,
+        colorterm: Option<String>,
+        term: Option<String>,
+    ) -> ColorSupport {
+        if no_color.is_some_and(|value| !value.is_empty()) {
+            return Self::None;
+        }
+        if let Some("truecolor" | "24bit") = colorterm.as_deref() {
+            return Self::TrueColor;
+        }
+        match term.as_deref() {
+            None | Some("dumb") => Self::None,
+            Some(term) if term.contains("256color") => Self::Extended,
+            Some(_) => Self::Basic,
+        }
+    }
+
+    /// Returns the closest color the terminal can show
+    pub fn convert(self, color: Color) -> Color {
+        let rgb = match (self, color.get_rgb()) {
+            (Self::None, _) | (_, None) => return Color::Default,
+            (_, Some(rgb)) => rgb,
+        };
+        match (self, color) {
+            (Self::Basic, Color::Ansi(index)) if index < 16 => color,
+            (Self::Basic, _) => get_closest(rgb, 0..16),
+            (Self::Extended, Color::Rgb(..)) => get_closest(rgb, 16..=255),
+            _ => color,
+        }
+    }
+
+    /// Returns the escape sequence that sets the color of the glyphs or of the cells behind them
+    ///
+    /// It is empty if nothing is colored.
+    pub fn get_escape(self, color: Color, background: bool) -> String {
+        let (basic, extended) = match background {
+            true => (40, 48),
+            false => (30, 38),
+        };
+        match (self, self.convert(color)) {
+            (Self::None, _) => String::new(),
+            (_, Color::Default) => format!("\x1b[{}m", basic + 9),
+            (Self::Basic, Color::Ansi(index)) if index < 8 => {
+                format!("\x1b[{}m", basic + index as u32)
+            }
+            (Self::Basic, Color::Ansi(index)) => format!("\x1b[{}m", basic + 60 + index as u32 - 8),
+            (_, Color::Ansi(index)) => format!("\x1b[{};5;{}m", extended, index),
+            (_, Color::Rgb(r, g, b)) => format!("\x1b[{};2;{};{};{}m", extended, r, g, b),
+        }
+    }
+}
+
+/// Parts of the board that a theme can style
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Element {
+    Empty,
+    Wall,
+    Apple,
+    Golden,
+    Shrinking,
+    SpeedUp,
+    SlowDown,
+    Bonus,
+    Poison,
+    PowerUp(PowerUp),
+    GateClosed,
+    GateOpen,
+    Portal,
+    Patrol,
+    Body,
+    Head(Direction),
+    Text,
+}
+
+impl Element {
+    pub const ALL: [Element; 23] = [
+        Self::Empty,
+        Self::Wall,
+        Self::Apple,
+        Self::Golden,
+        Self::Shrinking,
+        Self::SpeedUp,
+        Self::SlowDown,
+        Self::Bonus,
+        Self::Poison,
+        Self::PowerUp(PowerUp::Ghost),
+        Self::PowerUp(PowerUp::Invincible),
+        Self::PowerUp(PowerUp::Magnet),
+        Self::PowerUp(PowerUp::Multiplier),
+        Self::GateClosed,
+        Self::GateOpen,
+        Self::Portal,
+        Self::Patrol,
+        Self::Body,
+        Self::Head(Direction::Up),
+        Self::Head(Direction::Down),
+        Self::Head(Direction::Left),
+        Self::Head(Direction::Right),
+        Self::Text,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Element> {
+        Self::ALL
+            .iter()
+            .find(|element| element.get_name() == name)
+            .cloned()
+    }
+
+    pub fn get_name(&self) -> String {
+        match self {
+            Self::Empty => "empty".to_string(),
+            Self::Wall => "wall".to_string(),
+            Self::Apple => "apple".to_string(),
+            Self::Golden => "golden".to_string(),
+            Self::Shrinking => "shrinking".to_string(),
+            Self::SpeedUp => "speed-up".to_string(),
+            Self::SlowDown => "slow-down".to_string(),
+            Self::Bonus => "bonus".to_string(),
+            Self::Poison => "poison".to_string(),
+            Self::PowerUp(power_up) => power_up.get_name().to_string(),
+            Self::GateClosed => "gate-closed".to_string(),
+            Self::GateOpen => "gate-open".to_string(),
+            Self::Portal => "portal".to_string(),
+            Self::Patrol => "patrol".to_string(),
+            Self::Body => "body".to_string(),
+            Self::Head(direction) => format!("head-{}", direction.get_name()),
+            Self::Text => "text".to_string(),
+        }
+    }
+
+    /// Returns the element the symbol is drawn as
+    fn of(directed_symbol: &Directed<Symbol>) -> Element {
+        match directed_symbol {
+            Directed(_, Symbol::Empty) => Self::Empty,
+            Directed(_, Symbol::Wall) => Self::Wall,
+            Directed(_, Symbol::Apple) => Self::Apple,
+            Directed(_, Symbol::GoldenApple) => Self::Golden,
+            Directed(_, Symbol::ShrinkingFood) => Self::Shrinking,
+            Directed(_, Symbol::SpeedUp) => Self::SpeedUp,
+            Directed(_, Symbol::SlowDown) => Self::SlowDown,
+            Directed(_, Symbol::BonusFood) => Self::Bonus,
+            Directed(_, Symbol::Poison) => Self::Poison,
+            Directed(_, Symbol::PowerUp(power_up)) => Self::PowerUp(*power_up),
+            Directed(_, Symbol::Gate { open: false }) => Self::GateClosed,
+            Directed(_, Symbol::Gate { open: true }) => Self::GateOpen,
+            Directed(_, Symbol::Portal(_)) => Self::Portal,
+            Directed(_, Symbol::Patrol) => Self::Patrol,
+            Directed(_, Symbol::SnakeBody(_)) => Self::Body,
+            Directed(direction, Symbol::SnakeHead(_)) => Self::Head(direction.clone()),
+            Directed(_, Symbol::Text(_)) => Self::Text,
+        }
+    }
+
+    /// Returns whether the element shows its own character instead of a glyph of the theme
+    fn has_own_glyph(&self) -> bool {
+        matches!(self, Self::Portal | Self::Text)
+    }
+}
+
+/// Looks of an element
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Style {
+    pub glyph: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Style {
+    fn new(glyph: char, fg: Color) -> Style {
+        Style {
+            glyph,
+            fg,
+            bg: Color::Default,
+        }
+    }
+}
+
+/// Glyphs and colors of the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    styles: HashMap<Element, Style>,
+    /// Colors of the snakes, one for each player; they are used again if there are more players
+    players: Vec<Color>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl Theme {
+    /// Returns the theme with Unicode glyphs and the 16 basic colors, every other theme builds on it
+    pub fn classic() -> Theme {
+        let styles = Element::ALL
+            .iter()
+            .map(|element| {
+                let style = match element {
+                    Element::Empty => Style::new(' ', Color::Default),
+                    Element::Wall => Style::new('#', Color::Default),
+                    Element::Apple => Style::new('♥', Color::Default),
+                    Element::Golden => Style::new('♦', Color::Ansi(11)),
+                    Element::Shrinking => Style::new('♣', Color::Ansi(13)),
+                    Element::SpeedUp => Style::new('»', Color::Ansi(14)),
+                    Element::SlowDown => Style::new('«', Color::Ansi(14)),
+                    Element::Bonus => Style::new('☼', Color::Ansi(11)),
+                    Element::Poison => Style::new('×', Color::Ansi(9)),
+                    Element::PowerUp(PowerUp::Ghost) => Style::new('◌', Color::Ansi(5)),
+                    Element::PowerUp(PowerUp::Invincible) => Style::new('▣', Color::Ansi(5)),
+                    Element::PowerUp(PowerUp::Magnet) => Style::new('∩', Color::Ansi(5)),
+                    Element::PowerUp(PowerUp::Multiplier) => Style::new('²', Color::Ansi(5)),
+                    Element::GateClosed => Style::new('▒', Color::Ansi(3)),
+                    Element::GateOpen => Style::new('░', Color::Ansi(3)),
+                    Element::Portal => Style::new(' ', Color::Ansi(14)),
+                    Element::Patrol => Style::new('■', Color::Ansi(3)),
+                    Element::Body => Style::new('#', Color::Default),
+                    Element::Head(Direction::Up) => Style::new('▲', Color::Default),
+                    Element::Head(Direction::Down) => Style::new('▼', Color::Default),
+                    Element::Head(Direction::Left) => Style::new('◄', Color::Default),
+                    Element::Head(Direction::Right) => Style::new('►', Color::Default),
+                    Element::Text => Style::new(' ', Color::Default),
+                };
+                (element.clone(), style)
+            })
+            .collect();
+
+        Theme {
+            name: "classic".to_string(),
+            styles,
+            players: vec![Color::Ansi(10), Color::Ansi(12)],
+        }
+    }
+
+    /// Returns the theme shipped with the game with the given name
+    pub fn bundled(name: &str) -> Option<Theme> {
+        if name == "classic" {
+            return Some(Self::classic());
+        }
+        BUNDLED
+            .iter()
+            .find(|(bundled, _)| *bundled == name)
+            .map(|(_, content)| Self::parse(content).expect("Invalid bundled theme"))
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, ThemeError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a theme file, every line changes the theme it is based on
+    ///
+    /// ```text
+    /// name: <name of the theme>
+    /// base: <name of a bundled theme, classic if missing>
+    /// player<number>: <color of the snake of the player>
+    /// <element>: [glyph=<character>] [fg=<color>] [bg=<color>]
+    /// ```
+    ///
+    /// The base has to come before the other lines. Colors are `default`, the basic color names
+    /// like `red` or `bright-red`, palette numbers from 0 to 255, or `#rrggbb`. The glyph `space`
+    /// is an empty cell. The snakes are painted with the color of their player.
+    pub fn parse(content: &str) -> Result<Theme, ThemeError> {
+        let mut theme = Self::classic();
+        theme.name = "custom".to_string();
+        let mut styled = false;
+        for (number, line) in content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line))
+        {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(ThemeError::parse(number, "Expected a 'key: value' line")),
+            };
+            match key {
+                "name" => theme.name = value.to_string(),
+                "base" if styled => {
+                    return Err(ThemeError::parse(number, "The base has to come first"))
+                }
+                "base" => {
+                    let name = theme.name.clone();
+                    theme = Self::bundled(value)
+                        .ok_or_else(|| ThemeError::parse(number, "Unknown base theme"))?;
+                    theme.name = name;
+                }
+                _ => {
+                    styled = true;
+                    if let Some(player) = key.strip_prefix("player") {
+                        theme.set_player(player, value, number)?;
+                    } else {
+                        let element = Element::from_name(key)
+                            .ok_or_else(|| ThemeError::parse(number, "Unknown element"))?;
+                        theme.set_style(element, value, number)?;
+                    }
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    fn set_player(&mut self, player: &str, color: &str, number: usize) -> Result<(), ThemeError> {
+        let player: usize = player
+            .parse()
+            .ok()
+            .filter(|player| (1..=self.players.len() + 1).contains(player))
+            .ok_or_else(|| ThemeError::parse(number, "Players are numbered from 1, in order"))?;
+        let color =
+            Color::parse(color).ok_or_else(|| ThemeError::parse(number, "Invalid color"))?;
+        match self.players.get_mut(player - 1) {
+            Some(current) => *current = color,
+            None => self.players.push(color),
+        }
+        Ok(())
+    }
+
+    fn set_style(
+        &mut self,
+        element: Element,
+        value: &str,
+        number: usize,
+    ) -> Result<(), ThemeError> {
+        let own_glyph = element.has_own_glyph();
+        let style = self
+            .styles
+            .entry(element)
+            .or_insert(Style::new(' ', Color::Default));
+        for setting in value.split_whitespace() {
+            match setting.split_once('=') {
+                Some(("glyph", _)) if own_glyph => {
+                    return Err(ThemeError::parse(
+                        number,
+                        "The element shows its own character",
+                    ))
+                }
+                Some(("glyph", "space")) => style.glyph = ' ',
+                Some(("glyph", glyph)) => {
+                    let mut chars = glyph.chars();
+                    style.glyph = match (chars.next(), chars.next()) {
+                        (Some(glyph), None) => glyph,
+                        _ => return Err(ThemeError::parse(number, "A glyph is one character")),
+                    }
+                }
+                Some(("fg", color)) => {
+                    style.fg = Color::parse(color)
+                        .ok_or_else(|| ThemeError::parse(number, "Invalid color"))?
+                }
+                Some(("bg", color)) => {
+                    style.bg = Color::parse(color)
+                        .ok_or_else(|| ThemeError::parse(number, "Invalid color"))?
+                }
+                _ => return Err(ThemeError::parse(number, "Expected glyph=, fg= or bg=")),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns how the symbol looks, the snakes get the color of their player
+    pub fn get_style(&self, directed_symbol: &Directed<Symbol>) -> Style {
+        let element = Element::of(directed_symbol);
+        let mut style = self
+            .styles
+            .get(&element)
+            .copied()
+            .unwrap_or(Style::new(' ', Color::Default));
+        match &directed_symbol.1 {
+            Symbol::SnakeBody(player) | Symbol::SnakeHead(player) => {
+                style.fg = self.players[player % self.players.len()];
+            }
+            Symbol::Portal(label) | Symbol::Text(label) => style.glyph = *label,
+            _ => (),
+        }
+        style
+    }
+}
+
+/// # ThemeError
+#[derive(Debug, PartialEq)]
+pub enum ThemeError {
+    Io(io::ErrorKind),
+    Parse { line: usize, message: String },
+}
+
+impl ThemeError {
+    fn parse(line: usize, message: &str) -> ThemeError {
+        ThemeError::Parse {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for ThemeError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.kind())
+    }
+}
+
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(kind) => write!(f, "Couldn't read the theme: {}", kind),
+            Self::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for ThemeError {}
+
+#[cfg(test)]
+mod theme_test;
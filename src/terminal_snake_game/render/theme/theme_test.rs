@@ -0,0 +1,176 @@
+use super::*;
+
+fn style(theme: &Theme, symbol: Symbol) -> Style {
+    theme.get_style(&Directed(Direction::Up, symbol))
+}
+
+#[test]
+fn it_can_parse_colors() {
+    assert_eq!(Color::parse("default"), Some(Color::Default));
+    assert_eq!(Color::parse("red"), Some(Color::Ansi(1)));
+    assert_eq!(Color::parse("bright-white"), Some(Color::Ansi(15)));
+    assert_eq!(Color::parse("208"), Some(Color::Ansi(208)));
+    assert_eq!(Color::parse("#ff8000"), Some(Color::Rgb(255, 128, 0)));
+    assert_eq!(Color::parse("#ff80"), None);
+    assert_eq!(Color::parse("256"), None);
+    assert_eq!(Color::parse("purple"), None);
+}
+
+#[test]
+fn it_detects_the_colors_of_the_terminal() {
+    let detect = |no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>| {
+        ColorSupport::from_env(
+            no_color.map(String::from),
+            colorterm.map(String::from),
+            term.map(String::from),
+        )
+    };
+
+    assert_eq!(
+        detect(None, Some("truecolor"), Some("xterm-256color")),
+        ColorSupport::TrueColor
+    );
+    assert_eq!(
+        detect(None, None, Some("xterm-256color")),
+        ColorSupport::Extended
+    );
+    assert_eq!(detect(None, None, Some("xterm")), ColorSupport::Basic);
+    assert_eq!(detect(None, None, Some("dumb")), ColorSupport::None);
+    assert_eq!(detect(None, None, None), ColorSupport::None);
+    assert_eq!(
+        detect(Some("1"), Some("truecolor"), Some("xterm")),
+        ColorSupport::None
+    );
+}
+
+#[test]
+fn it_converts_colors_the_terminal_cant_show() {
+    let orange = Color::Rgb(255, 135, 0);
+
+    assert_eq!(ColorSupport::TrueColor.convert(orange), orange);
+    assert_eq!(ColorSupport::Extended.convert(orange), Color::Ansi(208));
+    assert_eq!(ColorSupport::Basic.convert(orange), Color::Ansi(3));
+    assert_eq!(
+        ColorSupport::Basic.convert(Color::Ansi(196)),
+        Color::Ansi(9)
+    );
+    assert_eq!(ColorSupport::Basic.convert(Color::Ansi(3)), Color::Ansi(3));
+    assert_eq!(ColorSupport::None.convert(orange), Color::Default);
+}
+
+#[test]
+fn it_writes_the_escapes_of_the_color_depth() {
+    let red = Color::Ansi(9);
+
+    assert_eq!(ColorSupport::Basic.get_escape(red, false), "\x1b[91m");
+    assert_eq!(
+        ColorSupport::Basic.get_escape(Color::Ansi(1), true),
+        "\x1b[41m"
+    );
+    assert_eq!(
+        ColorSupport::Extended.get_escape(red, false),
+        "\x1b[38;5;9m"
+    );
+    assert_eq!(
+        ColorSupport::TrueColor.get_escape(Color::Rgb(1, 2, 3), true),
+        "\x1b[48;2;1;2;3m"
+    );
+    assert_eq!(
+        ColorSupport::TrueColor.get_escape(Color::Default, true),
+        "\x1b[49m"
+    );
+    assert_eq!(ColorSupport::None.get_escape(red, false), "");
+}
+
+#[test]
+fn it_gives_the_snakes_the_color_of_their_player() {
+    let theme = Theme::parse("player2: #00ff00\nplayer3: blue").unwrap();
+
+    assert_eq!(style(&theme, Symbol::SnakeBody(0)).fg, Color::Ansi(10));
+    assert_eq!(
+        style(&theme, Symbol::SnakeHead(1)).fg,
+        Color::Rgb(0, 255, 0)
+    );
+    assert_eq!(style(&theme, Symbol::SnakeHead(2)).fg, Color::Ansi(4));
+    assert_eq!(style(&theme, Symbol::SnakeHead(3)).fg, Color::Ansi(10));
+    assert_eq!(style(&theme, Symbol::SnakeHead(0)).glyph, '▲');
+}
+
+#[test]
+fn it_can_parse_a_theme() {
+    let theme = Theme::parse(
+        "name: Dusk\n\
+         base: ascii\n\
+         \n\
+         wall: glyph=█ fg=8 bg=#202020\n\
+         empty: glyph=space bg=#202020\n\
+         portal: fg=magenta",
+    )
+    .unwrap();
+
+    assert_eq!(theme.name, "Dusk");
+    assert_eq!(
+        style(&theme, Symbol::Wall),
+        Style {
+            glyph: '█',
+            fg: Color::Ansi(8),
+            bg: Color::Rgb(32, 32, 32),
+        }
+    );
+    assert_eq!(style(&theme, Symbol::Empty).glyph, ' ');
+    assert_eq!(style(&theme, Symbol::Apple).glyph, '@');
+    assert_eq!(
+        style(&theme, Symbol::Portal('4')),
+        Style::new('4', Color::Ansi(5))
+    );
+    assert_eq!(style(&theme, Symbol::Text('x')).glyph, 'x');
+}
+
+#[test]
+fn it_reports_invalid_themes() {
+    let error = |content: &str, line: usize, message: &str| {
+        assert_eq!(
+            Theme::parse(content),
+            Err(ThemeError::parse(line, message)),
+            "{}",
+            content
+        );
+    };
+
+    error("apple", 1, "Expected a 'key: value' line");
+    error("\nbanana: fg=red", 2, "Unknown element");
+    error("apple: fg=purple", 1, "Invalid color");
+    error("apple: glyph=ab", 1, "A glyph is one character");
+    error("apple: size=2", 1, "Expected glyph=, fg= or bg=");
+    error("portal: glyph=o", 1, "The element shows its own character");
+    error("player4: red", 1, "Players are numbered from 1, in order");
+    error("player0: red", 1, "Players are numbered from 1, in order");
+    error("base: sepia", 1, "Unknown base theme");
+    error(
+        "apple: fg=red\nbase: ascii",
+        2,
+        "The base has to come first",
+    );
+}
+
+#[test]
+fn it_ships_valid_themes() {
+    for name in ["classic", "ascii", "mono", "neon"] {
+        let theme = Theme::bundled(name).unwrap();
+        assert_eq!(theme.name, name);
+        for element in Element::ALL.iter() {
+            assert!(theme.styles.contains_key(element), "{}", element.get_name());
+        }
+    }
+    assert_eq!(Theme::bundled("sepia"), None);
+}
+
+#[test]
+fn it_paints_only_ascii_with_the_ascii_theme() {
+    let theme = Theme::bundled("ascii").unwrap();
+
+    for element in Element::ALL.iter() {
+        let glyph = theme.styles[element].glyph;
+        assert!(glyph.is_ascii(), "{}: {}", element.get_name(), glyph);
+    }
+}
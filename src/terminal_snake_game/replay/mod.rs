@@ -0,0 +1,462 @@
+use super::snake_game::ai::Strategy;
+use super::snake_game::campaign::Campaign;
+use super::snake_game::config::Config;
+use super::snake_game::game::food::FoodWeights;
+use super::snake_game::game::score::Score;
+use super::snake_game::level::Level;
+use super::snake_game::speed::Difficulty;
+use super::snake_game::SnakeGame as LocalSnakeGame;
+use super::traits::SnakeGame;
+use super::update_reason::{Control, UpdateReason};
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::iter::Peekable;
+use std::path::Path;
+
+/// First line of every replay file, the number is the version of the format
+const HEADER: &str = "rust-snake-replay";
+const VERSION: u32 = 1;
+/// Most ticks a replay can have, more than a day of playing even at the fastest speed
+const MAX_TICKS: usize = 10_000_000;
+
+/// Result of a round that a replay has to reproduce
+#[derive(Clone, Debug, PartialEq)]
+pub struct FinalScore {
+    pub points: u32,
+    pub ticks: u64,
+}
+
+impl From<&Score> for FinalScore {
+    fn from(score: &Score) -> Self {
+        FinalScore {
+            points: score.points,
+            ticks: score.ticks,
+        }
+    }
+}
+
+/// A complete game: its settings and every update in order
+///
+/// The seed in the settings makes the game deterministic, so this is enough to play it again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub config: Config,
+    pub reasons: Vec<UpdateReason>,
+    /// Missing if the recording has been interrupted
+    pub score: Option<FinalScore>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Replay, ReplayError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Parses the text form of a replay
+    ///
+    /// ```text
+    /// rust-snake-replay 1
+    /// config <width> <height> <seed> <difficulty> <on|off> [wrap] [versus] [opponent <strategy>]
+    ///        [food <kind=weight,...>] [food-count <number>]
+    /// level <number of lines>
+    /// <the level file, see Level::parse>
+    /// campaign <index of the first level>
+    /// start
+    /// time 12
+    /// turn left
+    /// score <points> <ticks>
+    /// ```
+    ///
+    /// The level is present only if the game wasn't played on the default map,
+    /// a campaign is given by its first level instead.
+    pub fn parse(content: &str) -> Result<Replay, ReplayError> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+
+        match lines.next() {
+            Some((_, line)) if line == format!("{} {}", HEADER, VERSION) => (),
+            Some((_, line)) if line.starts_with(HEADER) => {
+                return Err(ReplayError::UnsupportedVersion(line.to_string()))
+            }
+            _ => return Err(ReplayError::parse(1, "Not a replay file")),
+        }
+
+        let mut replay = Replay {
+            config: parse_setup(&mut lines)?,
+            reasons: Vec::new(),
+            score: None,
+        };
+        let mut ticks = 0;
+        for (number, line) in lines {
+            if replay.score.is_some() {
+                return Err(ReplayError::parse(number, "Update after the final score"));
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["time", count] => {
+                    let count: usize = count
+                        .parse()
+                        .map_err(|_| ReplayError::parse(number, "Invalid number of ticks"))?;
+                    if count > MAX_TICKS - ticks {
+                        return Err(ReplayError::parse(number, "Too many ticks"));
+                    }
+                    ticks += count;
+                    replay
+                        .reasons
+                        .extend(std::iter::repeat_n(UpdateReason::Time, count));
+                }
+                ["score", points, ticks] => {
+                    replay.score = match (points.parse(), ticks.parse()) {
+                        (Ok(points), Ok(ticks)) => Some(FinalScore { points, ticks }),
+                        _ => return Err(ReplayError::parse(number, "Invalid score")),
+                    }
+                }
+                _ => replay.reasons.push(
+                    line.parse()
+                        .map_err(|message: String| ReplayError::parse(number, &message))?,
+                ),
+            }
+        }
+
+        Ok(replay)
+    }
+}
+
+impl Display for Replay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", HEADER, VERSION)?;
+        write_setup(f, &self.config)?;
+
+        // consecutive ticks are merged, they make up the most of a replay
+        let mut ticks = 0;
+        for reason in &self.reasons {
+            match reason {
+                UpdateReason::Time => ticks += 1,
+                _ => {
+                    if ticks > 0 {
+                        writeln!(f, "time {}", ticks)?;
+                        ticks = 0;
+                    }
+                    writeln!(f, "{}", reason)?;
+                }
+            }
+        }
+        if ticks > 0 {
+            writeln!(f, "time {}", ticks)?;
+        }
+
+        if let Some(score) = &self.score {
+            writeln!(f, "score {} {}", score.points, score.ticks)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the settings of a game: the config line and the level or the campaign
+pub(super) fn write_setup(f: &mut Formatter<'_>, config: &Config) -> fmt::Result {
+    writeln!(
+        f,
+        "config {} {} {} {} {}{}{}{}{}{}",
+        config.width,
+        config.height,
+        config.seed,
+        config.difficulty.get_name(),
+        if config.accelerate { "on" } else { "off" },
+        if config.wrap { " wrap" } else { "" },
+        if config.versus { " versus" } else { "" },
+        match config.opponent {
+            Some(strategy) => format!(" opponent {}", strategy.get_name()),
+            None => String::new(),
+        },
+        match config.food == FoodWeights::default() {
+            true => String::new(),
+            false => format!(" food {}", config.food),
+        },
+        match config.food_count {
+            1 => String::new(),
+            count => format!(" food-count {}", count),
+        }
+    )?;
+    // the levels of the campaign are bundled, only custom ones get saved
+    match (config.campaign, &config.level) {
+        (Some(start), _) => writeln!(f, "campaign {}", start)?,
+        (None, Some(level)) => {
+            let level = level.to_string();
+            writeln!(f, "level {}", level.lines().count())?;
+            write!(f, "{}", level)?;
+        }
+        (None, None) => (),
+    }
+    Ok(())
+}
+
+/// Parses the settings written by `write_setup` from the next lines
+pub(super) fn parse_setup<'a>(
+    lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+) -> Result<Config, ReplayError> {
+    let (config_number, mut config) = match lines.next() {
+        Some((number, line)) => (
+            number,
+            parse_config(line).ok_or_else(|| ReplayError::parse(number, "Invalid config line"))?,
+        ),
+        None => return Err(ReplayError::parse(2, "Missing config line")),
+    };
+
+    let (number, words): (usize, Vec<&str>) = match lines.peek() {
+        Some(&(number, line)) => (number, line.split_whitespace().collect()),
+        None => (config_number, Vec::new()),
+    };
+    match words.as_slice() {
+        ["level", count] => {
+            lines.next();
+            let count: usize = count
+                .parse()
+                .map_err(|_| ReplayError::parse(number, "Invalid number of lines"))?;
+            let content: Vec<&str> = lines.by_ref().take(count).map(|(_, line)| line).collect();
+            let level = Level::parse(&content.join("\n"))
+                .map_err(|error| ReplayError::parse(number, &error.to_string()))?;
+            set_level(&mut config, level, number)?;
+        }
+        ["campaign", start] => {
+            lines.next();
+            let mut levels = Campaign::get_levels();
+            let start = start
+                .parse()
+                .ok()
+                .filter(|start| *start < levels.len())
+                .ok_or_else(|| ReplayError::parse(number, "Unknown campaign level"))?;
+            set_level(&mut config, levels.swap_remove(start), number)?;
+            config.campaign = Some(start);
+        }
+        _ => (),
+    }
+    config
+        .validate()
+        .map_err(|error| ReplayError::parse(config_number, &error.to_string()))?;
+    Ok(config)
+}
+
+fn set_level(config: &mut Config, level: Level, number: usize) -> Result<(), ReplayError> {
+    let size = level.get_size();
+    if (size.x, size.y) != (config.width, config.height) {
+        return Err(ReplayError::parse(
+            number,
+            "The level doesn't match the board size",
+        ));
+    }
+    config.level = Some(level);
+    Ok(())
+}
+
+fn parse_config(line: &str) -> Option<Config> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (words, flags) = words.split_at(words.len().min(6));
+    let mut config = match words {
+        ["config", width, height, seed, difficulty, accelerate] => Config {
+            difficulty: Difficulty::from_name(difficulty)?,
+            accelerate: match *accelerate {
+                "on" => true,
+                "off" => false,
+                _ => return None,
+            },
+            seed: seed.parse().ok()?,
+            ..Config::new(width.parse().ok()?, height.parse().ok()?)
+        },
+        _ => return None,
+    };
+
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match *flag {
+            "wrap" => config.wrap = true,
+            "versus" => config.versus = true,
+            "opponent" => config.opponent = Some(Strategy::from_name(flags.next()?)?),
+            "food" => config.food = FoodWeights::parse(flags.next()?)?,
+            "food-count" => match flags.next()?.parse().ok()? {
+                0 => return None,
+                count => config.food_count = count,
+            },
+            _ => return None,
+        }
+    }
+    Some(config)
+}
+
+/// Collects the updates of a game while it is played
+pub struct Recorder {
+    replay: Replay,
+}
+
+impl Recorder {
+    pub fn new(config: &Config) -> Recorder {
+        Recorder {
+            replay: Replay {
+                config: config.clone(),
+                reasons: Vec::new(),
+                score: None,
+            },
+        }
+    }
+
+    pub fn record(&mut self, reason: &UpdateReason) {
+        // quitting is not part of the game
+        if let UpdateReason::Control(Control::Quit) = reason {
+            return;
+        }
+        self.replay.reasons.push(reason.clone());
+    }
+
+    /// Closes the recording with the score the game has ended with
+    pub fn finish(self, score: &Score) -> Replay {
+        Replay {
+            score: Some(FinalScore::from(score)),
+            ..self.replay
+        }
+    }
+}
+
+/// Plays a replay back tick by tick
+pub struct Player {
+    replay: Replay,
+    snake_game: Box<dyn SnakeGame>,
+    /// Index of the next update to apply
+    position: usize,
+    tick: usize,
+}
+
+impl Player {
+    pub fn new(replay: Replay) -> Player {
+        let snake_game = Box::new(LocalSnakeGame::new_unranked(&replay.config));
+        Player {
+            replay,
+            snake_game,
+            position: 0,
+            tick: 0,
+        }
+    }
+
+    /// Applies the updates until the next tick, returns false if the replay has already ended
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        while let Some(reason) = self.replay.reasons.get(self.position) {
+            self.position += 1;
+            self.snake_game.update(reason.clone());
+            if let UpdateReason::Time = reason {
+                self.tick += 1;
+                break;
+            }
+        }
+        true
+    }
+
+    /// Jumps to the given tick, backward jumps play the replay again from the beginning
+    pub fn seek(&mut self, tick: usize) {
+        if tick < self.tick {
+            *self = Player::new(self.replay.clone());
+        }
+        while self.tick < tick && self.step() {}
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.reasons.len()
+    }
+
+    /// Returns the number of ticks played so far
+    pub fn get_tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn get_total_ticks(&self) -> usize {
+        self.replay
+            .reasons
+            .iter()
+            .filter(|reason| matches!(reason, UpdateReason::Time))
+            .count()
+    }
+
+    pub fn get_snake_game(&self) -> &dyn SnakeGame {
+        self.snake_game.as_ref()
+    }
+
+    /// Plays the rest of the replay and checks whether it reproduces the recorded score
+    pub fn verify(&mut self) -> Result<FinalScore, ReplayError> {
+        while self.step() {}
+
+        let actual = FinalScore::from(self.snake_game.get_score());
+        match &self.replay.score {
+            None => Err(ReplayError::MissingScore),
+            Some(expected) if expected != &actual => Err(ReplayError::ScoreMismatch {
+                expected: expected.clone(),
+                actual,
+            }),
+            Some(_) => Ok(actual),
+        }
+    }
+}
+
+/// # ReplayError
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse {
+        line: usize,
+        message: String,
+    },
+    UnsupportedVersion(String),
+    MissingScore,
+    ScoreMismatch {
+        expected: FinalScore,
+        actual: FinalScore,
+    },
+}
+
+impl ReplayError {
+    fn parse(line: usize, message: &str) -> ReplayError {
+        ReplayError::Parse {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+            Self::UnsupportedVersion(header) => {
+                write!(f, "Unsupported replay version: {}", header)
+            }
+            Self::MissingScore => write!(f, "The replay has no final score to check"),
+            Self::ScoreMismatch { expected, actual } => write!(
+                f,
+                "The replay ended with {} points in {} ticks, but {} points in {} ticks were recorded",
+                actual.points, actual.ticks, expected.points, expected.ticks
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+#[cfg(test)]
+mod replay_test;
@@ -0,0 +1,255 @@
+use super::super::snake_game::game::direction::Direction;
+use super::*;
+
+fn config() -> Config {
+    Config {
+        seed: 99,
+        ..Config::new(30, 20)
+    }
+}
+
+/// Plays a short game and records it
+fn record(reasons: &[UpdateReason]) -> Replay {
+    let config = config();
+    let mut snake_game = LocalSnakeGame::new_unranked(&config);
+    let mut recorder = Recorder::new(&config);
+
+    for reason in reasons {
+        recorder.record(reason);
+        snake_game.update(reason.clone());
+    }
+
+    recorder.finish(snake_game.get_score())
+}
+
+fn inputs() -> Vec<UpdateReason> {
+    let mut reasons = vec![UpdateReason::Control(Control::Start)];
+    reasons.extend(std::iter::repeat_n(UpdateReason::Time, 3));
+    reasons.push(UpdateReason::Control(Control::Turn(0, Direction::Right)));
+    reasons.extend(std::iter::repeat_n(UpdateReason::Time, 5));
+    reasons.push(UpdateReason::Control(Control::Turn(0, Direction::Up)));
+    reasons.extend(std::iter::repeat_n(UpdateReason::Time, 2));
+    reasons.push(UpdateReason::Control(Control::Quit));
+    reasons
+}
+
+#[test]
+fn it_does_not_record_quitting() {
+    let replay = record(&inputs());
+
+    assert_eq!(replay.reasons.len(), inputs().len() - 1);
+    assert_eq!(
+        replay.score,
+        Some(FinalScore {
+            points: 0,
+            ticks: 10
+        })
+    );
+}
+
+#[test]
+fn it_can_serialize_itself() {
+    let replay = record(&inputs());
+
+    assert_eq!(
+        replay.to_string(),
+        "rust-snake-replay 1\n\
+         config 30 20 99 normal off\n\
+         start\n\
+         time 3\n\
+         turn right\n\
+         time 5\n\
+         turn up\n\
+         time 2\n\
+         score 0 10\n"
+    );
+}
+
+#[test]
+fn it_can_parse_what_it_has_written() {
+    let replay = record(&inputs());
+
+    assert_eq!(Replay::parse(&replay.to_string()).unwrap(), replay);
+}
+
+#[test]
+fn it_reports_the_line_of_the_error() {
+    let content = "rust-snake-replay 1\nconfig 30 20 99 normal off\nstart\ntime x\n";
+
+    match Replay::parse(content) {
+        Err(ReplayError::Parse { line, .. }) => assert_eq!(line, 4),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn it_refuses_unknown_versions() {
+    assert!(matches!(
+        Replay::parse("rust-snake-replay 2\n"),
+        Err(ReplayError::UnsupportedVersion(_))
+    ));
+}
+
+#[test]
+fn it_refuses_too_many_ticks() {
+    let content = "rust-snake-replay 1\nconfig 30 20 99 normal off\nstart\ntime 100000000000\n";
+
+    match Replay::parse(content) {
+        Err(ReplayError::Parse { line, .. }) => assert_eq!(line, 4),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn it_refuses_boards_too_small_for_the_snake() {
+    let content = "rust-snake-replay 1\nconfig 2 2 1 normal off\nstart\n";
+
+    match Replay::parse(content) {
+        Err(ReplayError::Parse { line, .. }) => assert_eq!(line, 2),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn it_reproduces_the_recorded_score() {
+    let mut player = Player::new(record(&inputs()));
+
+    assert_eq!(
+        player.verify().unwrap(),
+        FinalScore {
+            points: 0,
+            ticks: 10
+        }
+    );
+}
+
+#[test]
+fn it_detects_a_different_outcome() {
+    let mut replay = record(&inputs());
+    replay.score = Some(FinalScore {
+        points: 3,
+        ticks: 10,
+    });
+
+    let mut player = Player::new(replay);
+
+    assert!(matches!(
+        player.verify(),
+        Err(ReplayError::ScoreMismatch { .. })
+    ));
+}
+
+#[test]
+fn it_can_step_tick_by_tick() {
+    let mut player = Player::new(record(&inputs()));
+
+    assert_eq!(player.get_total_ticks(), 10);
+    assert!(player.step());
+    assert_eq!(player.get_tick(), 1);
+    assert_eq!(player.get_snake_game().get_score().ticks, 1);
+}
+
+#[test]
+fn it_can_seek_both_ways() {
+    let mut player = Player::new(record(&inputs()));
+    let mut reference = Player::new(record(&inputs()));
+    for _ in 0..4 {
+        reference.step();
+    }
+
+    player.seek(8);
+    assert_eq!(player.get_tick(), 8);
+
+    player.seek(4);
+    assert_eq!(player.get_tick(), 4);
+    assert_eq!(
+        player.get_snake_game().draw(),
+        reference.get_snake_game().draw()
+    );
+
+    player.seek(100);
+    assert!(player.is_finished());
+    assert_eq!(player.get_tick(), 10);
+}
+
+#[test]
+fn it_remembers_the_wrap_mode() {
+    let content = "rust-snake-replay 1\nconfig 30 20 99 normal off wrap\nscore 0 0\n";
+
+    let replay = Replay::parse(content).unwrap();
+
+    assert!(replay.config.wrap);
+    assert_eq!(replay.to_string(), content);
+}
+
+#[test]
+fn it_keeps_the_level_in_the_replay() {
+    let config = Config {
+        seed: 5,
+        ..Config::with_level(Level::bundled("pillars").unwrap())
+    };
+    let replay = Recorder::new(&config).finish(&Score::new(0));
+
+    let parsed = Replay::parse(&replay.to_string()).unwrap();
+
+    assert_eq!(parsed.config, config);
+}
+
+#[test]
+fn it_refers_to_the_campaign_by_its_first_level() {
+    let config = Config {
+        seed: 5,
+        ..Config::with_campaign(2)
+    };
+    let replay = Recorder::new(&config).finish(&Score::new(0));
+
+    assert!(replay.to_string().contains("\ncampaign 2\n"));
+    assert_eq!(Replay::parse(&replay.to_string()).unwrap().config, config);
+}
+
+#[test]
+fn it_numbers_the_turns_of_the_second_player() {
+    let content =
+        "rust-snake-replay 1\nconfig 30 20 99 normal off versus\nturn up\nturn 2 left\nscore 0 0\n";
+
+    let replay = Replay::parse(content).unwrap();
+
+    assert!(replay.config.versus);
+    assert_eq!(
+        replay.reasons,
+        vec![
+            UpdateReason::Control(Control::Turn(0, Direction::Up)),
+            UpdateReason::Control(Control::Turn(1, Direction::Left)),
+        ]
+    );
+    assert_eq!(replay.to_string(), content);
+    assert!(Replay::parse(&content.replace("turn 2", "turn 0")).is_err());
+}
+
+#[test]
+fn it_remembers_the_computer_opponent() {
+    let content =
+        "rust-snake-replay 1\nconfig 30 20 99 normal off versus opponent hamiltonian\nscore 0 0\n";
+
+    let replay = Replay::parse(content).unwrap();
+
+    assert_eq!(replay.config.opponent, Some(Strategy::Hamiltonian));
+    assert_eq!(replay.to_string(), content);
+    assert!(Replay::parse(&content.replace(" hamiltonian", "")).is_err());
+}
+
+#[test]
+fn it_remembers_the_kinds_of_food() {
+    let content =
+        "rust-snake-replay 1\nconfig 30 20 99 normal off food apple=5,bonus=1 food-count 3\nscore 0 0\n";
+
+    let replay = Replay::parse(content).unwrap();
+
+    assert_eq!(
+        replay.config.food,
+        FoodWeights::parse("apple=5,bonus=1").unwrap()
+    );
+    assert_eq!(replay.config.food_count, 3);
+    assert_eq!(replay.to_string(), content);
+    assert!(Replay::parse(&content.replace("food-count 3", "food-count 0")).is_err());
+}
@@ -0,0 +1,180 @@
+use super::replay::{parse_setup, write_setup, ReplayError};
+use super::snake_game::config::Config;
+use super::snake_game::game::traits::Snapshot;
+use super::snake_game::high_scores::data_dir;
+use super::snake_game::SnakeGame as LocalSnakeGame;
+use super::traits::SnakeGame;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// First line of every save file, the number is the version of the format
+const HEADER: &str = "rust-snake-save";
+const VERSION: u32 = 1;
+
+/// A game interrupted in the middle of a round: its settings and everything that has changed since
+#[derive(Clone, Debug, PartialEq)]
+pub struct Save {
+    pub config: Config,
+    /// The state of the game, see `SnakeGame::save_state`
+    pub state: String,
+}
+
+impl Save {
+    /// Takes a snapshot of the game that has been created with the given settings
+    pub fn new(config: &Config, snake_game: &dyn SnakeGame) -> Save {
+        Save {
+            config: config.clone(),
+            state: snake_game.save_state(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Save, SaveError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// Returns the default location of the save in the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("save"))
+    }
+
+    /// Parses the text form of a save
+    ///
+    /// ```text
+    /// rust-snake-save 1
+    /// <the settings of the game, the same as in a replay>
+    /// state
+    /// <the state of the game, see SnakeGame>
+    /// ```
+    pub fn parse(content: &str) -> Result<Save, SaveError> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+
+        match lines.next() {
+            Some((_, line)) if line == format!("{} {}", HEADER, VERSION) => (),
+            Some((_, line)) if line.starts_with(HEADER) => {
+                return Err(SaveError::UnsupportedVersion(line.to_string()))
+            }
+            _ => return Err(SaveError::parse(1, "Not a save file")),
+        }
+
+        let config = parse_setup(&mut lines)?;
+        match lines.next() {
+            Some((_, "state")) => (),
+            Some((number, _)) => return Err(SaveError::parse(number, "Expected the state")),
+            None => return Err(SaveError::MissingState),
+        }
+        let state: Vec<&str> = lines.map(|(_, line)| line).collect();
+
+        Ok(Save {
+            config,
+            state: state.join("\n"),
+        })
+    }
+
+    /// Creates the game with the settings and puts it into the saved state, it starts paused
+    pub fn restore(&self) -> Result<LocalSnakeGame, SaveError> {
+        self.load_into(LocalSnakeGame::new(&self.config))
+    }
+
+    /// Puts the given game, created with the settings of the save, into the saved state
+    fn load_into(&self, mut snake_game: LocalSnakeGame) -> Result<LocalSnakeGame, SaveError> {
+        match snake_game.load_state(&self.state) {
+            Some(()) => Ok(snake_game),
+            None => Err(SaveError::InvalidState),
+        }
+    }
+}
+
+impl Display for Save {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", HEADER, VERSION)?;
+        write_setup(f, &self.config)?;
+        writeln!(f, "state")?;
+        writeln!(f, "{}", self.state)
+    }
+}
+
+/// # SaveError
+#[derive(Debug, PartialEq)]
+pub enum SaveError {
+    Io(io::ErrorKind),
+    /// There is no data directory to keep the save in
+    NoLocation,
+    Parse {
+        line: usize,
+        message: String,
+    },
+    UnsupportedVersion(String),
+    MissingState,
+    /// The state doesn't fit the settings or the game
+    InvalidState,
+    /// A bot has played the game, it can't be continued by a player
+    BotGame,
+}
+
+impl SaveError {
+    fn parse(line: usize, message: &str) -> SaveError {
+        SaveError::Parse {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.kind())
+    }
+}
+
+/// Only the settings are shared with replays, so only their parse errors can get here
+impl From<ReplayError> for SaveError {
+    fn from(err: ReplayError) -> Self {
+        match err {
+            ReplayError::Io(err) => Self::from(err),
+            ReplayError::Parse { line, message } => Self::Parse { line, message },
+            err => Self::Parse {
+                line: 0,
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(io::ErrorKind::NotFound) => write!(f, "There is no saved game"),
+            Self::Io(kind) => write!(f, "{}", io::Error::from(*kind)),
+            Self::NoLocation => write!(f, "There is no data directory to keep the save in"),
+            Self::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+            Self::UnsupportedVersion(header) => {
+                write!(f, "Unsupported save version: {}", header)
+            }
+            Self::MissingState => write!(f, "The save has no game state"),
+            Self::InvalidState => write!(f, "The saved game state is invalid"),
+            Self::BotGame => write!(f, "The games of a bot aren't saved"),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+#[cfg(test)]
+mod save_test;
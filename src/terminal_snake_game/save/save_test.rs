@@ -0,0 +1,203 @@
+use super::super::snake_game::game::direction::Direction;
+use super::super::snake_game::game::traits::Draw;
+use super::super::snake_game::level::Level;
+use super::super::update_reason::{Control, UpdateReason};
+use super::*;
+
+fn config() -> Config {
+    Config {
+        seed: 42,
+        food_count: 2,
+        ..Config::new(30, 20)
+    }
+}
+
+/// Plays a few ticks of a game and pauses it
+fn play(config: &Config) -> LocalSnakeGame {
+    let mut snake_game = LocalSnakeGame::new_unranked(config);
+    snake_game.update(UpdateReason::Control(Control::Start));
+    for _ in 0..3 {
+        snake_game.update(UpdateReason::Time);
+    }
+    snake_game.update(UpdateReason::Control(Control::Turn(0, Direction::Right)));
+    snake_game.update(UpdateReason::Time);
+    snake_game.update(UpdateReason::Control(Control::Pause));
+    snake_game
+}
+
+fn restore(save: &Save) -> LocalSnakeGame {
+    save.load_into(LocalSnakeGame::new_unranked(&save.config))
+        .unwrap()
+}
+
+#[test]
+fn it_can_parse_its_own_output() {
+    let save = Save::new(&config(), &play(&config()));
+
+    assert_eq!(Save::parse(&save.to_string()), Ok(save));
+}
+
+#[test]
+fn it_keeps_custom_levels() {
+    let level = Level::bundled("pillars").unwrap();
+    let config = Config::with_level(level);
+    let save = Save::new(&config, &play(&config));
+
+    let parsed = Save::parse(&save.to_string()).unwrap();
+
+    assert_eq!(parsed.config.level, config.level);
+    assert_eq!(restore(&parsed).draw(), play(&config).draw());
+}
+
+#[test]
+fn it_continues_the_game_exactly() {
+    // GIVEN
+    let mut snake_game = play(&config());
+    let save = Save::parse(&Save::new(&config(), &snake_game).to_string()).unwrap();
+
+    // WHEN
+    let mut restored = restore(&save);
+
+    // THEN
+    assert_eq!(restored.draw(), snake_game.draw());
+    for snake_game in [&mut snake_game, &mut restored] {
+        snake_game.update(UpdateReason::Control(Control::Resume));
+        snake_game.update(UpdateReason::Control(Control::Turn(0, Direction::Down)));
+        for _ in 0..5 {
+            snake_game.update(UpdateReason::Time);
+        }
+    }
+    assert_eq!(restored.draw(), snake_game.draw());
+    assert_eq!(restored.get_score(), snake_game.get_score());
+}
+
+#[test]
+fn it_can_be_written_and_read_back() {
+    let path = std::env::temp_dir().join(format!("rust-snake-test-{}-save", std::process::id()));
+    let save = Save::new(&config(), &play(&config()));
+
+    save.save(&path).unwrap();
+    let loaded = Save::load(&path);
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(loaded, Ok(save));
+    assert_eq!(
+        Save::load(&path),
+        Err(SaveError::Io(io::ErrorKind::NotFound))
+    );
+}
+
+#[test]
+fn it_refuses_other_versions() {
+    assert_eq!(
+        Save::parse("rust-snake-save 2\nconfig 30 20 1 normal off\nstate\n"),
+        Err(SaveError::UnsupportedVersion(
+            "rust-snake-save 2".to_string()
+        ))
+    );
+}
+
+#[test]
+fn it_reports_broken_saves() {
+    assert_eq!(
+        Save::parse("rust-snake-replay 1\n"),
+        Err(SaveError::parse(1, "Not a save file"))
+    );
+    assert_eq!(
+        Save::parse("rust-snake-save 1\nconfig 30 20 x normal off\n"),
+        Err(SaveError::parse(2, "Invalid config line"))
+    );
+    assert!(matches!(
+        Save::parse("rust-snake-save 1\nconfig 2 2 1 normal off\nstate\n"),
+        Err(SaveError::Parse { line: 2, .. })
+    ));
+    assert_eq!(
+        Save::parse("rust-snake-save 1\nconfig 30 20 1 normal off\n"),
+        Err(SaveError::MissingState)
+    );
+    assert_eq!(
+        Save::parse("rust-snake-save 1\nconfig 30 20 1 normal off\nstart\n"),
+        Err(SaveError::parse(3, "Expected the state"))
+    );
+
+    let save =
+        Save::parse("rust-snake-save 1\nconfig 30 20 1 normal off\nstate\nwins 0\n").unwrap();
+    assert_eq!(
+        save.load_into(LocalSnakeGame::new_unranked(&save.config))
+            .err(),
+        Some(SaveError::InvalidState)
+    );
+}
+
+/// Returns the state with the first food line replaced
+fn with_food(state: &str, food: &str) -> String {
+    let mut replaced = false;
+    state
+        .lines()
+        .map(|line| match line.starts_with("food ") && !replaced {
+            true => {
+                replaced = true;
+                food.to_string()
+            }
+            false => line.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[test]
+fn it_refuses_food_outside_the_board() {
+    let save = Save::new(&config(), &play(&config()));
+    let save = Save {
+        state: with_food(&save.state, "food 30 5 apple -"),
+        ..save
+    };
+
+    assert_eq!(
+        save.load_into(LocalSnakeGame::new_unranked(&save.config))
+            .err(),
+        Some(SaveError::InvalidState)
+    );
+}
+
+#[test]
+fn it_leaves_the_game_as_it_was_when_the_state_is_invalid() {
+    // GIVEN a game and a later state of it whose food is invalid
+    let mut snake_game = play(&config());
+    let before = snake_game.save_state();
+    let mut later = play(&config());
+    later.update(UpdateReason::Control(Control::Resume));
+    for _ in 0..3 {
+        later.update(UpdateReason::Time);
+    }
+    let state = with_food(&later.save_state(), "food 1 1 banana -");
+
+    // WHEN it is loaded
+    let loaded = snake_game.load_state(&state);
+
+    // THEN nothing has changed
+    assert_eq!(loaded, None);
+    assert_eq!(snake_game.save_state(), before);
+}
+
+#[test]
+fn it_stays_on_the_level_when_the_state_of_another_is_invalid() {
+    // GIVEN a campaign on its first level and the state of its second level
+    let config = Config {
+        seed: 42,
+        ..Config::with_campaign(1)
+    };
+    let mut snake_game = play(&config);
+    let before = snake_game.save_state();
+    let state = with_food(
+        &before.replace("campaign 1 0", "campaign 2 0"),
+        "food 1 1 banana -",
+    );
+
+    // WHEN an invalid state of the second level is loaded
+    let loaded = snake_game.load_state(&state);
+
+    // THEN the game is still on the first level
+    assert_eq!(loaded, None);
+    assert_eq!(snake_game.save_state(), before);
+}
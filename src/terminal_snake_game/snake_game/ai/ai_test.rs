@@ -0,0 +1,315 @@
+use super::super::game::food::FoodKind;
+use super::super::game::traits::{Character as _, Map as _};
+use super::super::level::Level;
+use super::super::map::Map;
+use super::super::snake::Snake;
+use super::bot::{get_position, Bot, BotError};
+use super::hamiltonian::Cycle;
+use super::*;
+use std::time::Duration;
+
+fn view<'a>(map: &'a Map, snake: &'a Snake, head: Directed<Vec2>, food: Vec2) -> BoardView<'a> {
+    BoardView::new(map.get_size(), head, food, vec![], map, vec![snake])
+        .with_wrapping(map.is_wrapping())
+}
+
+/// The snake is in front of a pocket, the food is behind it
+const POCKET: &str = "\
+name: Pocket
+spawn: 5 2 right
+---
+############
+#.....#....#
+#......#...#
+#.....#....#
+#..........#
+############
+";
+
+#[test]
+fn it_finds_strategies_by_name() {
+    for strategy in Strategy::ALL.iter() {
+        assert_eq!(Strategy::from_name(strategy.get_name()), Some(*strategy));
+    }
+    assert_eq!(Strategy::from_name("random"), None);
+}
+
+#[test]
+fn it_sees_the_board_wrapping_around() {
+    let map = Map::new_wrapping(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let board = view(
+        &map,
+        &snake,
+        Directed(Direction::Down, Vec2 { x: 5, y: 4 }),
+        Vec2 { x: 1, y: 1 },
+    );
+
+    assert_eq!(
+        board.get_neighbour(&Vec2 { x: 0, y: 0 }, &Direction::Left),
+        Vec2 { x: 9, y: 0 }
+    );
+    assert_eq!(
+        board.get_distance(&Vec2 { x: 1, y: 1 }, &Vec2 { x: 9, y: 8 }),
+        5
+    );
+    assert_eq!(
+        board.get_safe_directions(),
+        vec![Direction::Down, Direction::Left, Direction::Right]
+    );
+}
+
+#[test]
+fn it_measures_the_distances_inside_the_walls() {
+    // GIVEN
+    let map = Map::new(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let head = Directed(Direction::Up, Vec2 { x: 2, y: 4 });
+
+    // WHEN
+    let board = view(&map, &snake, head, Vec2 { x: 8, y: 4 });
+
+    // THEN
+    assert_eq!(
+        board.get_distance(&Vec2 { x: 1, y: 1 }, &Vec2 { x: 8, y: 8 }),
+        14
+    );
+    // the way over the left wall would be shorter
+    assert_eq!(
+        Strategy::Greedy.new_controller().decide(&board),
+        Direction::Right
+    );
+}
+
+#[test]
+fn it_heads_greedily_to_the_food() {
+    let map = Map::new(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let head = Directed(Direction::Down, Vec2 { x: 5, y: 4 });
+
+    let mut greedy = Strategy::Greedy.new_controller();
+
+    let board = view(&map, &snake, head.clone(), Vec2 { x: 8, y: 4 });
+    assert_eq!(greedy.decide(&board), Direction::Right);
+    // the way up is blocked by its own body
+    let board = view(&map, &snake, head, Vec2 { x: 5, y: 1 });
+    assert_ne!(greedy.decide(&board), Direction::Up);
+}
+
+#[test]
+fn it_keeps_away_from_other_heads() {
+    let map = Map::new(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let head = Directed(Direction::Down, Vec2 { x: 5, y: 4 });
+    let board = BoardView::new(
+        map.get_size(),
+        head,
+        Vec2 { x: 5, y: 8 },
+        vec![Vec2 { x: 5, y: 6 }],
+        &map,
+        vec![&snake],
+    );
+
+    assert!(board.is_risky(&Vec2 { x: 5, y: 5 }));
+    assert_ne!(
+        Strategy::Greedy.new_controller().decide(&board),
+        Direction::Down
+    );
+    assert_ne!(
+        Strategy::Bfs.new_controller().decide(&board),
+        Direction::Down
+    );
+}
+
+#[test]
+fn it_finds_the_way_around_walls() {
+    // GIVEN
+    let level = Level::parse(POCKET).unwrap();
+    let map = Map::from_level(&level);
+    let snake = Snake::with_spawn(level.get_size(), &level.spawn);
+    let board = view(&map, &snake, level.spawn.clone(), Vec2 { x: 9, y: 2 });
+
+    // WHEN
+    let greedy = Strategy::Greedy.new_controller().decide(&board);
+    let bfs = Strategy::Bfs.new_controller().decide(&board);
+
+    // THEN
+    assert_eq!(greedy, Direction::Right);
+    assert_eq!(bfs, Direction::Down);
+}
+
+#[test]
+fn it_builds_a_cycle_through_the_inside_of_the_walls() {
+    let map = Map::new(10, 8);
+    let snake = Snake::new(Vec2 { x: 10, y: 8 });
+    let board = view(
+        &map,
+        &snake,
+        Directed(Direction::Down, Vec2 { x: 5, y: 4 }),
+        Vec2 { x: 1, y: 1 },
+    );
+    let cycle = Cycle::find(&board).unwrap();
+
+    let start = Vec2 { x: 1, y: 1 };
+    let mut pos = start.clone();
+    let mut visited = vec![];
+    loop {
+        visited.push(pos.clone());
+        pos = board.get_neighbour(&pos, &cycle.get_direction(&pos).unwrap());
+        if pos == start {
+            break;
+        }
+    }
+
+    assert_eq!(visited.len(), 8 * 6);
+    assert!(visited.iter().all(|pos| !board.is_wall(pos)));
+    assert_eq!(cycle.get_direction(&Vec2 { x: 0, y: 0 }), None);
+}
+
+#[test]
+fn it_has_no_cycle_on_odd_boards() {
+    let map = Map::new(9, 7);
+    let snake = Snake::new(Vec2 { x: 9, y: 7 });
+    let board = view(
+        &map,
+        &snake,
+        Directed(Direction::Down, Vec2 { x: 5, y: 4 }),
+        Vec2 { x: 1, y: 1 },
+    );
+
+    assert!(Cycle::find(&board).is_none());
+}
+
+/// Starts a bot that answers every position with the given move after the given delay
+fn bot(direction: &str, delay: &str) -> Bot {
+    let script = format!(
+        "read version; echo ready; while read line; do case \"$line\" in \
+         tick*) tick=${{line#tick }};; \
+         go) sleep {}; echo \"move $tick {}\";; \
+         esac; done",
+        delay, direction
+    );
+    Bot::spawn(&script, Duration::from_millis(500)).unwrap()
+}
+
+#[test]
+fn it_describes_the_board_to_a_bot() {
+    let map = Map::new(7, 6);
+    let snake = Snake::new(Vec2 { x: 7, y: 6 });
+    let head = Directed(Direction::Down, Vec2 { x: 5, y: 4 });
+    let board = view(&map, &snake, head, Vec2 { x: 2, y: 1 })
+        .with_foods(vec![(FoodKind::Apple, Vec2 { x: 2, y: 1 })])
+        .with_bodies(0, vec![snake.get_body()]);
+
+    assert_eq!(
+        get_position(&board, 3),
+        "tick 3\n\
+         board 7 6\n\
+         #######\n\
+         #.....#\n\
+         #.....#\n\
+         #.....#\n\
+         #.....#\n\
+         #######\n\
+         food apple 2 1\n\
+         snake 0 5 4 5 3 4 3 3 3\n\
+         you 0 down\n\
+         go\n"
+    );
+}
+
+#[test]
+fn it_plays_the_moves_of_a_bot() {
+    let map = Map::new(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let head = Directed(Direction::Down, Vec2 { x: 5, y: 4 });
+    let board = view(&map, &snake, head, Vec2 { x: 1, y: 1 });
+
+    let mut bot = bot("left", "0");
+
+    assert_eq!(bot.decide(&board), Direction::Left);
+    assert_eq!(bot.decide(&board), Direction::Left);
+    assert_eq!(bot.get_problem(), None);
+}
+
+#[test]
+fn it_goes_straight_on_when_a_bot_misbehaves() {
+    let map = Map::new(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let head = Directed(Direction::Down, Vec2 { x: 5, y: 4 });
+    let board = view(&map, &snake, head, Vec2 { x: 1, y: 1 });
+
+    let mut slow = bot("left", "1");
+    let mut confused = bot("sideways", "0");
+    let mut crashing = Bot::spawn("read version; echo ready", Duration::from_millis(500)).unwrap();
+
+    assert_eq!(slow.decide(&board), Direction::Down);
+    assert_eq!(slow.get_problem(), Some("no move in 500 ms"));
+    assert_eq!(confused.decide(&board), Direction::Down);
+    assert_eq!(
+        confused.get_problem(),
+        Some("invalid move \"move 1 sideways\"")
+    );
+    assert_eq!(crashing.decide(&board), Direction::Down);
+    assert_eq!(crashing.get_problem(), Some("exited"));
+}
+
+#[test]
+fn it_catches_up_with_a_bot_that_has_skipped_a_position() {
+    let map = Map::new(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let head = Directed(Direction::Down, Vec2 { x: 5, y: 4 });
+    let board = view(&map, &snake, head, Vec2 { x: 1, y: 1 });
+
+    // GIVEN a bot that doesn't answer the first position
+    let script = "read version; echo ready; while read line; do case \"$line\" in \
+                  tick*) tick=${line#tick };; \
+                  go) [ $tick = 1 ] || echo \"move $tick left\";; \
+                  esac; done";
+    let mut bot = Bot::spawn(script, Duration::from_millis(200)).unwrap();
+
+    // WHEN it is given the next positions
+    let first = bot.decide(&board);
+    let next: Vec<Direction> = (0..3).map(|_| bot.decide(&board)).collect();
+
+    // THEN its moves are played again
+    assert_eq!(first, Direction::Down);
+    assert_eq!(next, vec![Direction::Left; 3]);
+}
+
+#[test]
+fn it_drops_the_late_moves_of_a_bot() {
+    let map = Map::new(10, 10);
+    let snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let head = Directed(Direction::Down, Vec2 { x: 5, y: 4 });
+    let board = view(&map, &snake, head, Vec2 { x: 1, y: 1 });
+
+    // GIVEN a bot that answers the first position late and turns the other way
+    let script = "read version; echo ready; while read line; do case \"$line\" in \
+                  tick*) tick=${line#tick };; \
+                  go) if [ $tick = 1 ]; then sleep 0.3; echo 'move 1 right'; \
+                  else echo \"move $tick left\"; fi;; \
+                  esac; done";
+    let mut bot = Bot::spawn(script, Duration::from_millis(200)).unwrap();
+
+    // WHEN the late move arrives while the next position is waited for
+    let first = bot.decide(&board);
+    std::thread::sleep(Duration::from_millis(200));
+    let second = bot.decide(&board);
+
+    // THEN only the move for the current position is played
+    assert_eq!(first, Direction::Down);
+    assert_eq!(second, Direction::Left);
+    assert_eq!(bot.get_problem(), Some("no move in 200 ms"));
+}
+
+#[test]
+fn it_refuses_bots_that_dont_get_ready() {
+    let spawn = |command| Bot::spawn(command, Duration::from_millis(500)).err();
+
+    assert_eq!(spawn("true"), Some(BotError::Exited));
+    assert_eq!(
+        spawn("echo hello; read line"),
+        Some(BotError::Invalid("hello".to_string()))
+    );
+}
@@ -0,0 +1,276 @@
+use super::super::game::direction::{Directed, Direction};
+use super::super::game::vec2::Vec2;
+use super::{BoardView, Controller};
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Version of the protocol, sent to the bot when it starts
+pub const VERSION: u8 = 1;
+/// Time of the bot to get ready
+const STARTUP_TIME: Duration = Duration::from_secs(5);
+/// Time of the bot for a move if it isn't given
+pub const DEFAULT_MOVE_TIME: Duration = Duration::from_millis(50);
+
+/// A computer player in a process of its own, written in any language
+///
+/// The game talks to the bot over its standard input and output, a line at a time. It starts
+/// with the version of the protocol, the bot answers with `ready`:
+///
+/// ```text
+/// > snake 1
+/// < ready
+/// ```
+///
+/// Every tick the game sends the board, the walls as `#` and the free cells as `.`, every food
+/// with its kind and position, every snake with the number of its player and its cells head
+/// first, then the number and the direction of the bot's snake; the bot answers with the tick
+/// and its move:
+///
+/// ```text
+/// > tick 12
+/// > board 6 4
+/// > ######
+/// > #....#
+/// > #....#
+/// > ######
+/// > food apple 4 2
+/// > snake 0 2 1 1 1
+/// > you 0 right
+/// > go
+/// < move 12 down
+/// ```
+///
+/// Lines of the bot starting with `info` are skipped, e.g. for its own logging. A move that
+/// doesn't arrive in time keeps the snake's direction, it is told apart by its tick and dropped
+/// when it comes later.
+pub struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines of the bot, read on a thread of their own so a silent bot can't block the game
+    lines: Receiver<String>,
+    move_time: Duration,
+    /// Number of the positions sent
+    tick: u64,
+    /// Whether the bot can't play anymore, e.g. it has exited
+    gone: bool,
+    /// What has last gone wrong with the bot
+    problem: Option<String>,
+}
+
+impl Bot {
+    /// Starts the command in a shell and waits for the bot to get ready
+    pub fn spawn(command: &str, move_time: Duration) -> Result<Bot, BotError> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            // the game owns the terminal
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("Missing stdin of the bot");
+        let stdout = child.stdout.take().expect("Missing stdout of the bot");
+
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = Bot {
+            child,
+            stdin,
+            lines,
+            move_time,
+            tick: 0,
+            gone: false,
+            problem: None,
+        };
+        bot.start()?;
+        Ok(bot)
+    }
+
+    fn start(&mut self) -> Result<(), BotError> {
+        match writeln!(self.stdin, "snake {}", VERSION) {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => {
+                return Err(BotError::Exited)
+            }
+            result => result?,
+        }
+        let deadline = Instant::now() + STARTUP_TIME;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(timeout) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return Err(BotError::NotReady),
+                Err(RecvTimeoutError::Disconnected) => return Err(BotError::Exited),
+            };
+            match line.trim() {
+                _ if line.starts_with("info") => (),
+                "ready" => return Ok(()),
+                _ => return Err(BotError::Invalid(line)),
+            }
+        }
+    }
+
+    /// Returns the next move of the bot, None if it isn't given in time or isn't a move
+    fn receive_move(&mut self) -> Option<Direction> {
+        let deadline = Instant::now() + self.move_time;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(timeout) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.problem = Some(format!("no move in {} ms", self.move_time.as_millis()));
+                    return None;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.leave("exited");
+                    return None;
+                }
+            };
+            if line.starts_with("info") {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            return match words.as_slice() {
+                ["move", tick, direction] => match tick.parse::<u64>() {
+                    // the answer to an earlier position
+                    Ok(tick) if tick < self.tick => continue,
+                    Ok(tick) if tick == self.tick => match Direction::from_name(direction) {
+                        Some(direction) => Some(direction),
+                        None => {
+                            self.problem = Some(format!("invalid move \"{}\"", line));
+                            None
+                        }
+                    },
+                    _ => {
+                        self.problem = Some(format!("invalid tick \"{}\"", line));
+                        None
+                    }
+                },
+                _ => {
+                    self.problem = Some(format!("invalid answer \"{}\"", line));
+                    None
+                }
+            };
+        }
+    }
+
+    fn leave(&mut self, problem: &str) {
+        self.gone = true;
+        self.problem = Some(problem.to_string());
+        let _ = self.child.kill();
+    }
+}
+
+impl Controller for Bot {
+    fn decide(&mut self, board: &BoardView) -> Direction {
+        let Directed(current, _) = &board.head;
+        if self.gone {
+            return current.clone();
+        }
+
+        self.tick += 1;
+        let position = get_position(board, self.tick);
+        if self.stdin.write_all(position.as_bytes()).is_err() || self.stdin.flush().is_err() {
+            self.leave("exited");
+            return current.clone();
+        }
+        self.receive_move().unwrap_or_else(|| current.clone())
+    }
+
+    fn get_problem(&self) -> Option<&str> {
+        self.problem.as_deref()
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Returns the lines sent to the bot about the board, see `Bot`
+pub fn get_position(board: &BoardView, tick: u64) -> String {
+    let Vec2 {
+        x: width,
+        y: height,
+    } = board.size;
+    let mut lines = vec![
+        format!("tick {}", tick),
+        format!("board {} {}", width, height),
+    ];
+    for y in 0..height {
+        lines.push(
+            (0..width)
+                .map(|x| match board.is_wall(&Vec2 { x, y }) {
+                    true => '#',
+                    false => '.',
+                })
+                .collect(),
+        );
+    }
+    for (kind, pos) in &board.foods {
+        lines.push(format!("food {} {} {}", kind.get_name(), pos.x, pos.y));
+    }
+    for (player, body) in board.bodies.iter().enumerate() {
+        let cells: Vec<String> = body
+            .iter()
+            .map(|pos| format!("{} {}", pos.x, pos.y))
+            .collect();
+        lines.push(format!("snake {} {}", player, cells.join(" ")));
+    }
+    lines.push(format!("you {} {}", board.player, board.head.0.get_name()));
+    lines.push("go".to_string());
+
+    let mut position = lines.join("\n");
+    position.push('\n');
+    position
+}
+
+/// # BotError
+#[derive(Debug, PartialEq)]
+pub enum BotError {
+    Io(io::ErrorKind),
+    /// The bot has exited before getting ready
+    Exited,
+    /// The bot hasn't got ready in time
+    NotReady,
+    /// The bot has answered something else than the protocol
+    Invalid(String),
+}
+
+impl Display for BotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(kind) => write!(f, "{}", io::Error::from(*kind)),
+            Self::Exited => write!(f, "The bot has exited before getting ready"),
+            Self::NotReady => write!(
+                f,
+                "The bot hasn't got ready in {} seconds",
+                STARTUP_TIME.as_secs()
+            ),
+            Self::Invalid(line) => write!(f, "The bot has answered \"{}\"", line),
+        }
+    }
+}
+
+impl Error for BotError {}
+
+impl From<io::Error> for BotError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error.kind())
+    }
+}
@@ -0,0 +1,19 @@
+use super::super::game::direction::{Directed, Direction};
+use super::{BoardView, Controller};
+
+/// Steps to the free neighbour that is the closest to the food
+pub struct Greedy;
+
+impl Controller for Greedy {
+    fn decide(&mut self, board: &BoardView) -> Direction {
+        let Directed(current, head) = &board.head;
+        board
+            .get_safe_directions()
+            .into_iter()
+            // the first one wins a tie, so the snake doesn't zigzag
+            .min_by_key(|direction| {
+                board.get_distance(&board.get_neighbour(head, direction), &board.food)
+            })
+            .unwrap_or_else(|| current.clone())
+    }
+}
@@ -0,0 +1,139 @@
+use super::super::game::direction::{Directed, Direction};
+use super::super::game::vec2::Vec2;
+use super::shortest_path::ShortestPath;
+use super::{BoardView, Controller};
+
+/// Walks around a cycle that goes through every free cell once
+///
+/// Once the whole snake is on the cycle it can't run into itself. Boards without such a cycle,
+/// e.g. levels with inner walls, are played like `ShortestPath`.
+#[derive(Default)]
+pub struct Hamiltonian {
+    /// The cycle of the board it has been built for
+    cycle: Option<(Vec2, Option<Cycle>)>,
+}
+
+impl Hamiltonian {
+    fn get_cycle(&mut self, board: &BoardView) -> Option<&Cycle> {
+        let outdated = match &self.cycle {
+            Some((size, _)) => *size != board.size,
+            None => true,
+        };
+        if outdated {
+            self.cycle = Some((board.size.clone(), Cycle::find(board)));
+        }
+        self.cycle.as_ref().and_then(|(_, cycle)| cycle.as_ref())
+    }
+}
+
+impl Controller for Hamiltonian {
+    fn decide(&mut self, board: &BoardView) -> Direction {
+        let Directed(_, head) = &board.head;
+        self.get_cycle(board)
+            .and_then(|cycle| cycle.get_direction(head))
+            // the snake isn't on the cycle yet right after the start
+            .filter(|direction| {
+                let next = board.get_neighbour(head, direction);
+                board.is_free(&next) && !board.is_risky(&next)
+            })
+            .unwrap_or_else(|| ShortestPath.decide(board))
+    }
+}
+
+/// Directions leading each cell of a rectangle to the next cell of a cycle through all of them
+pub struct Cycle {
+    width: usize,
+    directions: Vec<Option<Direction>>,
+}
+
+impl Cycle {
+    /// Finds a cycle through the whole board or, if it has walls on its border, through the
+    /// inside of it
+    pub fn find(board: &BoardView) -> Option<Cycle> {
+        let Vec2 {
+            x: width,
+            y: height,
+        } = board.size;
+        let whole = (Vec2 { x: 0, y: 0 }, board.size.clone());
+        let inside = (
+            Vec2 { x: 1, y: 1 },
+            Vec2 {
+                x: width.saturating_sub(2),
+                y: height.saturating_sub(2),
+            },
+        );
+
+        [whole, inside]
+            .iter()
+            .filter(|(origin, size)| {
+                (0..size.y).all(|y| {
+                    (0..size.x).all(|x| {
+                        !board.is_wall(&Vec2 {
+                            x: origin.x + x,
+                            y: origin.y + y,
+                        })
+                    })
+                })
+            })
+            .find_map(|(origin, size)| Self::in_rectangle(&board.size, origin, size))
+    }
+
+    /// Builds a cycle through a rectangle of the board, one of its sides has to be even
+    fn in_rectangle(board_size: &Vec2, origin: &Vec2, size: &Vec2) -> Option<Cycle> {
+        if size.x < 2 || size.y < 2 {
+            return None;
+        }
+        // it zigzags through the rows leaving out the first column, then comes back along it
+        let cells: Vec<Vec2> = match (size.x % 2, size.y % 2) {
+            (_, 0) => Self::zigzag(size.x, size.y),
+            (0, _) => Self::zigzag(size.y, size.x)
+                .into_iter()
+                .map(|pos| Vec2 { x: pos.y, y: pos.x })
+                .collect(),
+            _ => return None,
+        };
+
+        let mut cycle = Cycle {
+            width: board_size.x,
+            directions: vec![None; board_size.x * board_size.y],
+        };
+        for (pos, next) in cells.iter().zip(cells.iter().cycle().skip(1)) {
+            let index = (origin.y + pos.y) * cycle.width + origin.x + pos.x;
+            cycle.directions[index] = Some(Self::get_step(pos, next));
+        }
+        Some(cycle)
+    }
+
+    fn zigzag(width: usize, height: usize) -> Vec<Vec2> {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row: Vec<usize> = match y % 2 {
+                0 => (1..width).collect(),
+                _ => (1..width).rev().collect(),
+            };
+            cells.extend(row.into_iter().map(|x| Vec2 { x, y }));
+        }
+        cells.extend((0..height).rev().map(|y| Vec2 { x: 0, y }));
+        cells
+    }
+
+    fn get_step(from: &Vec2, to: &Vec2) -> Direction {
+        match (
+            to.x as isize - from.x as isize,
+            to.y as isize - from.y as isize,
+        ) {
+            (1, _) => Direction::Right,
+            (-1, _) => Direction::Left,
+            (_, 1) => Direction::Down,
+            _ => Direction::Up,
+        }
+    }
+
+    /// Returns where the cycle goes from the position, None if the position isn't on it
+    pub fn get_direction(&self, pos: &Vec2) -> Option<Direction> {
+        self.directions
+            .get(pos.y * self.width + pos.x)
+            .cloned()
+            .flatten()
+    }
+}
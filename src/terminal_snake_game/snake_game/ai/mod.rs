@@ -0,0 +1,218 @@
+pub mod bot;
+mod greedy;
+mod hamiltonian;
+mod shortest_path;
+use super::game::direction::{Directed, Direction};
+use super::game::food::FoodKind;
+use super::game::traits::IsFreePos;
+use super::game::vec2::Vec2;
+use greedy::Greedy;
+use hamiltonian::Hamiltonian;
+use shortest_path::ShortestPath;
+
+/// Decides the moves of a computer player
+pub trait Controller {
+    /// Returns the direction the character should head to in the next tick
+    fn decide(&mut self, board: &BoardView) -> Direction;
+
+    /// Returns what has last gone wrong with the player, e.g. a bot that has crashed
+    fn get_problem(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Built-in computer players
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strategy {
+    /// Heads to the food on the shortest route it sees, avoiding only the next cell's obstacles
+    Greedy,
+    /// Searches the shortest path to the food around the obstacles
+    Bfs,
+    /// Follows a cycle through every cell of the board, so it never runs into itself
+    Hamiltonian,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [Self::Greedy, Self::Bfs, Self::Hamiltonian];
+
+    /// Returns the strategy with the given name
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        Self::ALL
+            .iter()
+            .find(|strategy| strategy.get_name() == name)
+            .copied()
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Greedy => "greedy",
+            Self::Bfs => "bfs",
+            Self::Hamiltonian => "hamiltonian",
+        }
+    }
+
+    pub fn new_controller(&self) -> Box<dyn Controller> {
+        match self {
+            Self::Greedy => Box::new(Greedy),
+            Self::Bfs => Box::new(ShortestPath),
+            Self::Hamiltonian => Box::new(Hamiltonian::default()),
+        }
+    }
+}
+
+/// What a computer player can see of the board
+pub struct BoardView<'a> {
+    /// Size of the board, a step over an edge leads to the opposite edge
+    pub size: Vec2,
+    /// Whether the edges are open, so the shortest way to a cell can lead over them
+    wrapping: bool,
+    /// Position and direction of the player's head
+    pub head: Directed<Vec2>,
+    pub food: Vec2,
+    /// Heads of the other players
+    pub others: Vec<Vec2>,
+    /// Food that must not be eaten
+    poison: Vec<Vec2>,
+    map: &'a dyn IsFreePos,
+    characters: Vec<&'a dyn IsFreePos>,
+    /// Every food on the board with its kind, poison too
+    foods: Vec<(FoodKind, Vec2)>,
+    /// Number of the player who decides
+    player: usize,
+    /// Cells of every character by the number of its player, head first
+    bodies: Vec<Vec<Vec2>>,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(
+        size: Vec2,
+        head: Directed<Vec2>,
+        food: Vec2,
+        others: Vec<Vec2>,
+        map: &'a dyn IsFreePos,
+        characters: Vec<&'a dyn IsFreePos>,
+    ) -> BoardView<'a> {
+        BoardView {
+            size,
+            wrapping: false,
+            head,
+            food,
+            others,
+            poison: Vec::new(),
+            map,
+            characters,
+            foods: Vec::new(),
+            player: 0,
+            bodies: Vec::new(),
+        }
+    }
+
+    /// Lets the distances lead over the edges of the board
+    pub fn with_wrapping(self, wrapping: bool) -> BoardView<'a> {
+        BoardView { wrapping, ..self }
+    }
+
+    /// Makes the given positions count as obstacles
+    pub fn with_poison(self, poison: Vec<Vec2>) -> BoardView<'a> {
+        BoardView { poison, ..self }
+    }
+
+    /// Adds every food with its kind, the built-in players only look for the nearest one
+    pub fn with_foods(self, foods: Vec<(FoodKind, Vec2)>) -> BoardView<'a> {
+        BoardView { foods, ..self }
+    }
+
+    /// Adds the bodies of the characters, the deciding player's one is at the given index
+    pub fn with_bodies(self, player: usize, bodies: Vec<Vec<Vec2>>) -> BoardView<'a> {
+        BoardView {
+            player,
+            bodies,
+            ..self
+        }
+    }
+
+    /// Returns whether neither a wall, a character nor poison is on the position
+    pub fn is_free(&self, pos: &Vec2) -> bool {
+        self.map.is_free_pos(pos)
+            && !self.poison.contains(pos)
+            && self
+                .characters
+                .iter()
+                .all(|character| character.is_free_pos(pos))
+    }
+
+    /// Returns whether another player's head can get to the position in the next step
+    pub fn is_risky(&self, pos: &Vec2) -> bool {
+        self.others.iter().any(|head| {
+            Direction::ALL
+                .iter()
+                .any(|direction| self.get_neighbour(head, direction) == *pos)
+        })
+    }
+
+    /// Returns whether there is a wall on the position, characters don't count
+    pub fn is_wall(&self, pos: &Vec2) -> bool {
+        !self.map.is_free_pos(pos)
+    }
+
+    /// Returns the cell next to the position in the given direction
+    pub fn get_neighbour(&self, pos: &Vec2, direction: &Direction) -> Vec2 {
+        let Vec2 {
+            x: width,
+            y: height,
+        } = self.size;
+        match direction {
+            Direction::Up => Vec2 {
+                x: pos.x,
+                y: (pos.y + height - 1) % height,
+            },
+            Direction::Left => Vec2 {
+                x: (pos.x + width - 1) % width,
+                y: pos.y,
+            },
+            Direction::Right => Vec2 {
+                x: (pos.x + 1) % width,
+                y: pos.y,
+            },
+            Direction::Down => Vec2 {
+                x: pos.x,
+                y: (pos.y + 1) % height,
+            },
+        }
+    }
+
+    /// Returns the directions that don't lead into an obstacle, the current one first
+    ///
+    /// Cells where another head may arrive are left out unless there is no other way.
+    pub fn get_safe_directions(&self) -> Vec<Direction> {
+        let Directed(current, head) = &self.head;
+        let (safe, risky): (Vec<Direction>, Vec<Direction>) = std::iter::once(current)
+            .chain(
+                Direction::ALL
+                    .iter()
+                    .filter(|direction| *direction != current),
+            )
+            .filter(|direction| self.is_free(&self.get_neighbour(head, direction)))
+            .cloned()
+            .partition(|direction| !self.is_risky(&self.get_neighbour(head, direction)));
+        match safe.is_empty() {
+            true => risky,
+            false => safe,
+        }
+    }
+
+    /// Returns the number of steps between the positions on an empty board
+    pub fn get_distance(&self, from: &Vec2, to: &Vec2) -> usize {
+        let distance = |a: usize, b: usize, size: usize| {
+            let straight = a.max(b) - a.min(b);
+            match self.wrapping {
+                true => straight.min(size - straight),
+                false => straight,
+            }
+        };
+        distance(from.x, to.x, self.size.x) + distance(from.y, to.y, self.size.y)
+    }
+}
+
+#[cfg(test)]
+mod ai_test;
@@ -0,0 +1,57 @@
+use super::super::game::direction::{Directed, Direction};
+use super::super::game::vec2::Vec2;
+use super::greedy::Greedy;
+use super::{BoardView, Controller};
+use std::collections::VecDeque;
+
+/// Follows the shortest path to the food found by a breadth-first search
+///
+/// The path doesn't start where another head may arrive. When the food can't be reached
+/// it moves like `Greedy`.
+pub struct ShortestPath;
+
+impl ShortestPath {
+    /// Returns the first step of the shortest path from the head to the food
+    fn find_first_step(board: &BoardView) -> Option<Direction> {
+        let Directed(current, head) = &board.head;
+        let index = |pos: &Vec2| pos.y * board.size.x + pos.x;
+        // the first step of the path that has reached a cell
+        let mut first_steps: Vec<Option<Direction>> = vec![None; board.size.x * board.size.y];
+        let mut queue = VecDeque::new();
+
+        let directions = std::iter::once(current).chain(
+            Direction::ALL
+                .iter()
+                .filter(|direction| *direction != current),
+        );
+        for direction in directions {
+            let next = board.get_neighbour(head, direction);
+            if board.is_free(&next) && !board.is_risky(&next) && first_steps[index(&next)].is_none()
+            {
+                first_steps[index(&next)] = Some(direction.clone());
+                queue.push_back(next);
+            }
+        }
+
+        while let Some(pos) = queue.pop_front() {
+            let first_step = first_steps[index(&pos)].clone();
+            if pos == board.food {
+                return first_step;
+            }
+            for direction in Direction::ALL.iter() {
+                let next = board.get_neighbour(&pos, direction);
+                if board.is_free(&next) && first_steps[index(&next)].is_none() {
+                    first_steps[index(&next)] = first_step.clone();
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Controller for ShortestPath {
+    fn decide(&mut self, board: &BoardView) -> Direction {
+        Self::find_first_step(board).unwrap_or_else(|| Greedy.decide(board))
+    }
+}
@@ -1,33 +1,165 @@
 use super::*;
 use super::super::game::traits::*;
+use super::super::game::food::BONUS_TICKS;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+fn new_apple() -> Apple {
+    Apple::with_weights(FoodWeights::default())
+}
 
 #[test]
 fn it_can_spawn_apple() {
     // GIVEN
-    let mut one = Apple::new();
-    let mut two = Apple::new();
+    let mut one = new_apple();
+    let mut two = new_apple();
     let mut mock_is_free_pos = MockIsFreePos::new();
     let size = Vec2 { x: 10, y: 10 };
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
 
     mock_is_free_pos.expect_is_free_pos().returning(|_| true);
 
     // WHEN
-    one.spawn(&size, &mock_is_free_pos);
+    one.spawn(&size, &mock_is_free_pos, &mut rng);
 
     while one.get_pos() == two.get_pos() {
-        two.spawn(&size, &mock_is_free_pos);
+        two.spawn(&size, &mock_is_free_pos, &mut rng);
     }
 
     // THEN
     assert_ne!(one.get_pos(), two.get_pos());
 }
 
+#[test]
+fn it_spawns_to_the_same_positions_with_the_same_seed() {
+    let mut one = new_apple();
+    let mut two = new_apple();
+    let mut one_rng = ChaCha8Rng::seed_from_u64(42);
+    let mut two_rng = ChaCha8Rng::seed_from_u64(42);
+    let mut mock_is_free_pos = MockIsFreePos::new();
+    let size = Vec2 { x: 20, y: 20 };
+
+    mock_is_free_pos.expect_is_free_pos().returning(|_| true);
+
+    for _ in 0..10 {
+        one.spawn(&size, &mock_is_free_pos, &mut one_rng);
+        two.spawn(&size, &mock_is_free_pos, &mut two_rng);
+        assert_eq!(one.get_pos(), two.get_pos());
+    }
+}
+
+#[test]
+fn it_spawns_only_to_free_positions() {
+    let mut apple = new_apple();
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut mock_is_free_pos = MockIsFreePos::new();
+    let size = Vec2 { x: 10, y: 10 };
+
+    mock_is_free_pos
+        .expect_is_free_pos()
+        .returning(|pos| pos == &Vec2 { x: 3, y: 4 });
+
+    apple.spawn(&size, &mock_is_free_pos, &mut rng);
+
+    assert_eq!(apple.get_pos(), &Vec2 { x: 3, y: 4 });
+}
+
 #[test]
 fn it_can_draw_itself() {
-    let apple = Apple::new();
+    let apple = new_apple();
     let paint = apple.draw();
     assert_eq!(paint.len(), 1);
     assert_eq!(paint.first().unwrap().shape.len(), 1);
     assert_eq!(paint.first().unwrap().shape.first().unwrap().0, Direction::Up);
     assert_eq!(paint.first().unwrap().shape.first().unwrap().1, Symbol::Apple);
 }
+
+#[test]
+fn it_finds_the_last_free_cell() {
+    let mut apple = new_apple();
+    let mut mock_is_free_pos = MockIsFreePos::new();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    mock_is_free_pos
+        .expect_is_free_pos()
+        .returning(|pos| *pos == Vec2 { x: 3, y: 2 });
+
+    apple.spawn(&Vec2 { x: 40, y: 40 }, &mock_is_free_pos, &mut rng);
+
+    assert_eq!(apple.get_pos(), &Vec2 { x: 3, y: 2 });
+}
+
+#[test]
+fn it_stays_in_place_on_a_full_board() {
+    let mut apple = new_apple();
+    let mut mock_is_free_pos = MockIsFreePos::new();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+    mock_is_free_pos.expect_is_free_pos().returning(|_| false);
+
+    apple.spawn(&Vec2 { x: 5, y: 5 }, &mock_is_free_pos, &mut rng);
+
+    assert_eq!(apple.get_pos(), &Vec2 { x: 0, y: 0 });
+}
+
+#[test]
+fn it_spawns_the_kinds_of_the_weights() {
+    let weights = FoodWeights::new(vec![(FoodKind::Golden, 1), (FoodKind::Poison, 1)]).unwrap();
+    let mut apple = Apple::with_weights(weights);
+    let mut mock_is_free_pos = MockIsFreePos::new();
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+    mock_is_free_pos.expect_is_free_pos().returning(|_| true);
+
+    let mut kinds = Vec::new();
+    for _ in 0..20 {
+        apple.spawn(&Vec2 { x: 10, y: 10 }, &mock_is_free_pos, &mut rng);
+        kinds.push(apple.get_kind());
+    }
+
+    assert!(kinds.contains(&FoodKind::Golden));
+    assert!(kinds.contains(&FoodKind::Poison));
+    assert!(!kinds.contains(&FoodKind::Apple));
+}
+
+#[test]
+fn it_goes_off_after_its_lifetime() {
+    // GIVEN
+    let weights = FoodWeights::new(vec![(FoodKind::Bonus, 1)]).unwrap();
+    let mut apple = Apple::with_weights(weights);
+    let mut mock_is_free_pos = MockIsFreePos::new();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    mock_is_free_pos.expect_is_free_pos().returning(|_| true);
+
+    // WHEN
+    apple.spawn(&Vec2 { x: 10, y: 10 }, &mock_is_free_pos, &mut rng);
+    let ticks = (0..).take_while(|_| apple.tick()).count();
+
+    // THEN
+    assert_eq!(ticks as u32, BONUS_TICKS);
+    assert_eq!(apple.draw()[0].shape[0].1, Symbol::BonusFood);
+}
+
+#[test]
+fn it_can_save_and_restore_its_state() {
+    let mut apple = Apple::with_weights(FoodWeights::parse("bonus=1").unwrap());
+    let mut mock_is_free_pos = MockIsFreePos::new();
+    mock_is_free_pos.expect_is_free_pos().returning(|_| true);
+    apple.spawn(
+        &Vec2 { x: 10, y: 10 },
+        &mock_is_free_pos,
+        &mut ChaCha8Rng::seed_from_u64(1),
+    );
+    apple.tick();
+
+    let mut restored = new_apple();
+    restored.load_state(&apple.save_state()).unwrap();
+
+    assert_eq!(restored.get_pos(), apple.get_pos());
+    assert_eq!(restored.get_kind(), FoodKind::Bonus);
+    assert_eq!(restored.save_state(), apple.save_state());
+    assert!(restored
+        .save_state()
+        .ends_with(&format!(" bonus {}", BONUS_TICKS - 1)));
+    assert_eq!(restored.load_state("1 2 plum -"), None);
+}
@@ -4,49 +4,148 @@ use super::game::{
 };
 use super::game::{
     draw_instruction::{DrawInstruction, Symbol},
-    traits::{Draw, Food, IsFreePos},
+    food::{FoodKind, FoodWeights},
+    traits::{Draw, Food, IsFreePos, Snapshot},
 };
-use rand::{thread_rng, Rng};
+use rand::{Rng, RngCore};
 
+/// A piece of food, it becomes a new kind every time it spawns
 pub struct Apple {
     pub pos: Vec2,
+    pub kind: FoodKind,
+    weights: FoodWeights,
+    /// Remaining ticks until the food goes off
+    lifetime: Option<u32>,
 }
 
 impl Apple {
-    pub fn new() -> Apple {
+    /// Creates a food whose kind is chosen with the given weights
+    pub fn with_weights(weights: FoodWeights) -> Apple {
         Apple {
             pos: Vec2 { x: 0, y: 0 },
+            kind: FoodKind::Apple,
+            weights,
+            lifetime: None,
         }
     }
 }
 
 impl Food for Apple {
-    fn spawn(&mut self, boundary: &Vec2, position_checker: &dyn IsFreePos) {
-        let mut x: usize;
-        let mut y: usize;
+    fn spawn(
+        &mut self,
+        boundary: &Vec2,
+        position_checker: &dyn IsFreePos,
+        rng: &mut dyn RngCore,
+    ) -> bool {
+        self.kind = self.weights.choose(rng);
+        self.lifetime = self.kind.get_lifetime();
 
-        while {
-            x = thread_rng().gen_range(1, boundary.x - 2);
-            y = thread_rng().gen_range(1, boundary.y - 2);
-            !position_checker.is_free_pos(&Vec2 { x, y })
-        } {}
+        let cells = boundary.x * boundary.y;
+        for _ in 0..4 * cells {
+            let x = rng.gen_range(0, boundary.x);
+            let y = rng.gen_range(0, boundary.y);
+            if position_checker.is_free_pos(&Vec2 { x, y }) {
+                self.pos = Vec2 { x, y };
+                return true;
+            }
+        }
 
-        self.pos = Vec2 { x, y }
+        // Random tries rarely find the last few free cells, and a full board has none at all
+        let free = (0..cells)
+            .map(|cell| Vec2 {
+                x: cell % boundary.x,
+                y: cell / boundary.x,
+            })
+            .find(|pos| position_checker.is_free_pos(pos));
+        match free {
+            Some(pos) => {
+                self.pos = pos;
+                true
+            }
+            None => false,
+        }
     }
 
     fn get_pos(&self) -> &Vec2 {
         &self.pos
     }
 
+    fn move_to(&mut self, pos: Vec2) {
+        self.pos = pos;
+    }
+
+    fn get_kind(&self) -> FoodKind {
+        self.kind
+    }
+
+    fn tick(&mut self) -> bool {
+        match &mut self.lifetime {
+            Some(0) => false,
+            Some(lifetime) => {
+                *lifetime -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+
     fn as_draw(&self) -> &dyn Draw {
         self
     }
 }
 
+/// The position, the kind and the remaining lifetime, e.g. `12 4 bonus 37` or `3 8 apple -`
+impl Snapshot for Apple {
+    fn save_state(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.pos.x,
+            self.pos.y,
+            self.kind.get_name(),
+            match self.lifetime {
+                Some(lifetime) => lifetime.to_string(),
+                None => "-".to_string(),
+            }
+        )
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        let words: Vec<&str> = state.split_whitespace().collect();
+        let (pos, kind, lifetime) = match words.as_slice() {
+            [x, y, kind, lifetime] => (
+                Vec2 {
+                    x: x.parse().ok()?,
+                    y: y.parse().ok()?,
+                },
+                FoodKind::from_name(kind)?,
+                match *lifetime {
+                    "-" => None,
+                    lifetime => Some(lifetime.parse().ok()?),
+                },
+            ),
+            _ => return None,
+        };
+        self.pos = pos;
+        self.kind = kind;
+        self.lifetime = lifetime;
+        Some(())
+    }
+}
+
 impl Draw for Apple {
     fn draw(&self) -> Vec<DrawInstruction> {
         let pos = self.pos.clone();
-        let shape = vec![Directed(Direction::Up, Symbol::Apple)];
+        let symbol = match self.kind {
+            FoodKind::Apple => Symbol::Apple,
+            FoodKind::Golden => Symbol::GoldenApple,
+            FoodKind::Shrinking => Symbol::ShrinkingFood,
+            FoodKind::SpeedUp => Symbol::SpeedUp,
+            FoodKind::SlowDown => Symbol::SlowDown,
+            FoodKind::Bonus => Symbol::BonusFood,
+            FoodKind::Poison => Symbol::Poison,
+            FoodKind::PowerUp(power_up) => Symbol::PowerUp(power_up),
+        };
+        let shape = vec![Directed(Direction::Up, symbol)];
         vec![DrawInstruction { pos, shape }]
     }
 }
@@ -0,0 +1,100 @@
+use super::*;
+
+fn level(name: &str, quota: u32) -> Level {
+    let content = format!(
+        "name: {}\nspawn: 5 2 right\napples: {}\n---\n.........\n.........\n.........\n",
+        name, quota
+    );
+    Level::parse(&content).unwrap()
+}
+
+fn new_campaign() -> Campaign {
+    Campaign::with_levels(
+        vec![level("One", 2), level("Two", 3), level("Three", 1)],
+        0,
+        Progress::new(),
+    )
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("rust-snake-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn it_counts_only_the_apples_of_the_current_level() {
+    let mut campaign = new_campaign();
+    assert!(!campaign.is_level_done(1));
+    assert!(campaign.is_level_done(2));
+
+    campaign.advance(2);
+
+    assert_eq!(campaign.get_level().name, "Two");
+    assert!(!campaign.is_level_done(4));
+    assert!(campaign.is_level_done(5));
+}
+
+#[test]
+fn it_ends_after_the_last_level() {
+    let mut campaign = new_campaign();
+
+    assert!(campaign.advance(2).is_some());
+    assert!(campaign.advance(5).is_some());
+    assert!(campaign.advance(6).is_none());
+    assert_eq!(campaign.get_level_number(), 3);
+}
+
+#[test]
+fn it_can_restart_from_its_first_level() {
+    let mut campaign = Campaign::with_levels(
+        vec![level("One", 2), level("Two", 3), level("Three", 1)],
+        1,
+        Progress::new(),
+    );
+    campaign.advance(3);
+
+    assert_eq!(campaign.restart().name, "Two");
+    assert!(!campaign.is_level_done(2));
+}
+
+#[test]
+fn it_has_bundled_levels_of_the_same_size() {
+    let levels = Campaign::get_levels();
+
+    assert_eq!(levels.len(), LEVELS.len());
+    assert!(levels
+        .iter()
+        .all(|level| level.get_size() == levels[0].get_size()));
+}
+
+#[test]
+fn it_remembers_the_unlocked_levels() {
+    // GIVEN
+    let path = temp_path("campaign");
+    let mut campaign = Campaign::with_levels(
+        vec![level("One", 2), level("Two", 3)],
+        0,
+        Progress::load(&path),
+    );
+    assert_eq!(Progress::load(&path).get_unlocked(), 1);
+
+    // WHEN
+    campaign.advance(2);
+
+    // THEN
+    assert_eq!(Progress::load(&path).get_unlocked(), 2);
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn it_unlocks_the_first_level_from_a_broken_file() {
+    let path = temp_path("campaign-broken");
+    fs::write(&path, "broken").unwrap();
+
+    assert_eq!(Progress::load(&path).get_unlocked(), 1);
+
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,184 @@
+use super::high_scores::data_dir;
+use super::level::Level;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Bundled levels of the campaign in the order they are played
+const LEVELS: [&str; 5] = ["box", "pillars", "corridors", "tunnels", "hazards"];
+
+/// Apples to eat on a level that doesn't set its own quota
+pub const DEFAULT_QUOTA: u32 = 10;
+
+/// A sequence of levels, the next one comes when the quota of the current one is met
+pub struct Campaign {
+    levels: Vec<Level>,
+    /// Index of the level the campaign starts from
+    start: usize,
+    current: usize,
+    /// Apples eaten on the earlier levels, they don't count into the current quota
+    apples_before: u32,
+    progress: Progress,
+}
+
+impl Campaign {
+    /// Creates the bundled campaign from the given level
+    pub fn new(start: usize, progress: Progress) -> Campaign {
+        Self::with_levels(Self::get_levels(), start, progress)
+    }
+
+    pub fn with_levels(levels: Vec<Level>, start: usize, progress: Progress) -> Campaign {
+        Campaign {
+            levels,
+            start,
+            current: start,
+            apples_before: 0,
+            progress,
+        }
+    }
+
+    /// Returns the levels of the bundled campaign
+    pub fn get_levels() -> Vec<Level> {
+        LEVELS
+            .iter()
+            .map(|name| Level::bundled(name).expect("Unknown campaign level"))
+            .collect()
+    }
+
+    pub fn get_level(&self) -> &Level {
+        &self.levels[self.current]
+    }
+
+    /// Returns the number of the current level, counted from 1
+    pub fn get_level_number(&self) -> usize {
+        self.current + 1
+    }
+
+    pub fn get_level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Returns the apples eaten on the earlier levels
+    pub fn get_apples_before(&self) -> u32 {
+        self.apples_before
+    }
+
+    /// Returns the number of apples to eat on the current level
+    pub fn get_quota(&self) -> u32 {
+        self.get_level().quota.unwrap_or(DEFAULT_QUOTA)
+    }
+
+    /// Returns whether the quota of the current level is met with the apples eaten in total
+    pub fn is_level_done(&self, apples: u32) -> bool {
+        apples.saturating_sub(self.apples_before) >= self.get_quota()
+    }
+
+    /// Moves on to the next level and unlocks it
+    ///
+    /// Returns None if the campaign is over.
+    pub fn advance(&mut self, apples: u32) -> Option<&Level> {
+        if self.current + 1 >= self.levels.len() {
+            return None;
+        }
+        self.current += 1;
+        self.apples_before = apples;
+        self.progress.unlock(self.get_level_number());
+        Some(self.get_level())
+    }
+
+    /// Continues the campaign on the level with the given number, e.g. from a saved game
+    ///
+    /// Returns None if there is no such level.
+    pub fn resume(&mut self, number: usize, apples_before: u32) -> Option<&Level> {
+        if number == 0 || number > self.levels.len() {
+            return None;
+        }
+        self.current = number - 1;
+        self.apples_before = apples_before;
+        Some(self.get_level())
+    }
+
+    /// Plays the current level again from a fresh score
+    pub fn restart_level(&mut self) {
+        self.apples_before = 0;
+    }
+
+    /// Goes back to the level the campaign has started from
+    pub fn restart(&mut self) -> &Level {
+        self.current = self.start;
+        self.apples_before = 0;
+        self.get_level()
+    }
+}
+
+/// The furthest level the player has reached, persisted in a plain text file
+pub struct Progress {
+    path: Option<PathBuf>,
+    unlocked: usize,
+}
+
+impl Progress {
+    /// Creates a progress that lives only in memory
+    pub fn new() -> Progress {
+        Progress {
+            path: None,
+            unlocked: 1,
+        }
+    }
+
+    /// Loads the progress from the given file, a missing or broken file means only the first level
+    pub fn load(path: &Path) -> Progress {
+        let unlocked = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| content.trim().parse().ok())
+            .unwrap_or(1)
+            .max(1);
+
+        Progress {
+            path: Some(path.to_path_buf()),
+            unlocked,
+        }
+    }
+
+    /// Loads the progress from its default location, if there is one
+    pub fn load_default() -> Progress {
+        match Self::default_path() {
+            Some(path) => Self::load(&path),
+            None => Self::new(),
+        }
+    }
+
+    /// Returns the default location of the progress in the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("campaign"))
+    }
+
+    /// Returns the number of levels that can be started
+    pub fn get_unlocked(&self) -> usize {
+        self.unlocked
+    }
+
+    /// Makes the levels up to the given number playable
+    pub fn unlock(&mut self, level: usize) {
+        if level > self.unlocked {
+            self.unlocked = level;
+            // A read-only data directory shouldn't end the game
+            let _ = self.save();
+        }
+    }
+
+    /// Writes the progress to its file
+    pub fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, format!("{}\n", self.unlocked))
+    }
+}
+
+#[cfg(test)]
+mod campaign_test;
@@ -0,0 +1,124 @@
+use super::ai::Strategy;
+use super::campaign::Campaign;
+use super::game::food::FoodWeights;
+use super::game::vec2::Vec2;
+use super::level::Level;
+use super::snake::Snake;
+use super::speed::Difficulty;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// Settings of a game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Width of the map
+    pub width: usize,
+    /// Height of the map, the HUD is not included
+    pub height: usize,
+    pub difficulty: Difficulty,
+    pub accelerate: bool,
+    /// Whether the map has no border walls and the snake re-enters on the opposite edge
+    pub wrap: bool,
+    /// Seed of the random generator, the same seed with the same input gives the same game
+    pub seed: u64,
+    /// Layout of the map, an empty board with walls around is used if missing
+    pub level: Option<Level>,
+    /// Index of the first level when playing the campaign
+    pub campaign: Option<usize>,
+    /// Whether two players play against each other on the same board
+    pub versus: bool,
+    /// Computer player of the second snake in versus mode, a person plays it if missing
+    pub opponent: Option<Strategy>,
+    /// Computer player that plays on the title screen
+    pub demo: Option<Strategy>,
+    /// Whether the players play over the network, each one with the arrows of their own terminal
+    pub online: bool,
+    /// How often each kind of food is spawned
+    pub food: FoodWeights,
+    /// Number of foods on the board at the same time
+    pub food_count: usize,
+}
+
+impl Config {
+    pub fn new(width: usize, height: usize) -> Config {
+        Config {
+            width,
+            height,
+            difficulty: Difficulty::Normal,
+            accelerate: false,
+            wrap: false,
+            seed: rand::random(),
+            level: None,
+            campaign: None,
+            versus: false,
+            opponent: None,
+            demo: None,
+            online: false,
+            food: FoodWeights::default(),
+            food_count: 1,
+        }
+    }
+
+    /// Creates the settings of a game on the given level
+    pub fn with_level(level: Level) -> Config {
+        let size = level.get_size();
+        Config {
+            difficulty: level.difficulty.unwrap_or(Difficulty::Normal),
+            level: Some(level),
+            ..Self::new(size.x, size.y)
+        }
+    }
+
+    /// Creates the settings of the campaign starting from the given level
+    pub fn with_campaign(start: usize) -> Config {
+        let level = Campaign::get_levels().swap_remove(start);
+        Config {
+            campaign: Some(start),
+            ..Self::with_level(level)
+        }
+    }
+
+    /// Checks whether a game can be played with these settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        // levels check their own layout when they get parsed
+        if self.level.is_some() {
+            return Ok(());
+        }
+
+        let min = match self.versus {
+            true => Snake::get_min_versus_map_size(),
+            false => Snake::get_min_map_size(),
+        };
+        if self.width < min.x || self.height < min.y {
+            return Err(ConfigError::BoardTooSmall {
+                size: Vec2 {
+                    x: self.width,
+                    y: self.height,
+                },
+                min,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// # ConfigError
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    BoardTooSmall { size: Vec2, min: Vec2 },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BoardTooSmall { size, min } => write!(
+                f,
+                "The board is {}x{}, but the snakes need at least {}x{}",
+                size.x, size.y, min.x, min.y
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
@@ -1,4 +1,4 @@
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub enum Direction {
     Up,
     Left,
@@ -6,5 +6,26 @@ pub enum Direction {
     Down,
 }
 
-#[derive(Debug, PartialEq)]
+impl Direction {
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Left, Self::Right, Self::Down];
+
+    /// Returns the direction with the given name
+    pub fn from_name(name: &str) -> Option<Direction> {
+        Self::ALL
+            .iter()
+            .find(|direction| direction.get_name() == name)
+            .cloned()
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Down => "down",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Directed<T>(pub Direction, pub T);
@@ -1,16 +1,50 @@
-use super::{direction::Directed, vec2::Vec2};
+use super::{
+    direction::{Directed, Direction},
+    power_up::PowerUp,
+    vec2::Vec2,
+};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Symbol {
     Empty,
     Wall,
     Apple,
-    SnakeBody,
-    SnakeHead,
+    GoldenApple,
+    ShrinkingFood,
+    SpeedUp,
+    SlowDown,
+    BonusFood,
+    Poison,
+    PowerUp(PowerUp),
+    /// Wall that opens and closes on a timer
+    Gate {
+        open: bool,
+    },
+    /// Portal with the label of its pair
+    Portal(char),
+    /// Moving block
+    Patrol,
+    /// Part of the snake of the given player
+    SnakeBody(usize),
+    SnakeHead(usize),
+    Text(char),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DrawInstruction {
     pub pos: Vec2, 
     pub shape: Vec<Directed<Symbol>>,
-}
\ No newline at end of file
+}
+
+impl DrawInstruction {
+    /// Creates an instruction that writes the given text from the given position
+    pub fn text(pos: Vec2, text: &str) -> DrawInstruction {
+        DrawInstruction {
+            pos,
+            shape: text
+                .chars()
+                .map(|c| Directed(Direction::Up, Symbol::Text(c)))
+                .collect(),
+        }
+    }
+}
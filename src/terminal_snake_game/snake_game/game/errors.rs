@@ -4,7 +4,7 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 
 /// Snake Problems
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct SelfEatingStepError;
 impl Display for SelfEatingStepError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -13,7 +13,7 @@ impl Display for SelfEatingStepError {
 }
 impl Error for SelfEatingStepError {}
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct SelfTurningDirectionError;
 impl Display for SelfTurningDirectionError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -23,11 +23,17 @@ impl Display for SelfTurningDirectionError {
 impl Error for SelfTurningDirectionError {}
 
 /// # GameError
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum GameError {
     KilledByWall,
+    KilledBySnake,
+    Poisoned,
+    /// The characters have taken every cell, no food can be put on the board
+    BoardFilled,
     SelfTurningDirectionError(SelfTurningDirectionError),
     SelfEatingStepError(SelfEatingStepError),
+    /// Some players of a multiplayer round have lost, each with its reason
+    PlayersLost(Vec<(usize, GameError)>),
 }
 
 impl From<SelfEatingStepError> for GameError {
@@ -45,8 +51,18 @@ impl Display for GameError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::KilledByWall => write!(f, "You've been killed by the wall"),
+            Self::KilledBySnake => write!(f, "You've run into a snake"),
+            Self::Poisoned => write!(f, "You've eaten poison"),
+            Self::BoardFilled => write!(f, "You've filled the board"),
             Self::SelfEatingStepError(e) => write!(f, "{}", e),
             Self::SelfTurningDirectionError(e) => write!(f, "{}", e),
+            Self::PlayersLost(losers) => {
+                let reasons: Vec<String> = losers
+                    .iter()
+                    .map(|(player, reason)| format!("Player {}: {}", player + 1, reason))
+                    .collect();
+                write!(f, "{}", reasons.join(", "))
+            }
         }
     }
 }
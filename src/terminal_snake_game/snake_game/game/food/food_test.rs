@@ -0,0 +1,55 @@
+use super::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+#[test]
+fn it_can_parse_weights() {
+    let weights = FoodWeights::parse("apple=10, golden=2,poison=0").unwrap();
+
+    assert_eq!(
+        weights,
+        FoodWeights::new(vec![(FoodKind::Apple, 10), (FoodKind::Golden, 2)]).unwrap()
+    );
+    assert_eq!(weights.to_string(), "apple=10,golden=2");
+}
+
+#[test]
+fn it_refuses_bad_weights() {
+    assert_eq!(FoodWeights::parse("apple"), None);
+    assert_eq!(FoodWeights::parse("banana=1"), None);
+    assert_eq!(FoodWeights::parse("apple=-1"), None);
+    assert_eq!(FoodWeights::parse("apple=0,poison=0"), None);
+    assert_eq!(FoodWeights::parse("apple=4294967295,poison=1"), None);
+}
+
+#[test]
+fn it_chooses_by_the_weights() {
+    let weights = FoodWeights::parse("apple=3,speed-up=1").unwrap();
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+
+    let speed_ups = (0..1000)
+        .filter(|_| weights.choose(&mut rng) == FoodKind::SpeedUp)
+        .count();
+
+    assert!(speed_ups > 150 && speed_ups < 350);
+}
+
+#[test]
+fn it_uses_no_randomness_for_a_single_kind() {
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    let mut untouched = ChaCha8Rng::seed_from_u64(5);
+
+    assert_eq!(FoodWeights::default().choose(&mut rng), FoodKind::Apple);
+    assert_eq!(rng.next_u64(), untouched.next_u64());
+}
+
+#[test]
+fn it_returns_to_the_normal_pace() {
+    let mut pace = Pace::Slow(2);
+
+    pace = pace.tick();
+    assert_eq!(pace, Pace::Slow(1));
+    pace = pace.tick();
+    assert_eq!(pace, Pace::Normal);
+    assert_eq!(pace.get_factor(), 1.0);
+}
@@ -0,0 +1,210 @@
+use super::power_up::PowerUp;
+use super::traits::Snapshot;
+use rand::{Rng, RngCore};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// Kinds of food, each has its own effect when eaten
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FoodKind {
+    Apple,
+    /// Worth more points
+    Golden,
+    /// Makes the character shorter instead of longer
+    Shrinking,
+    /// Makes the game faster for a while
+    SpeedUp,
+    /// Makes the game slower for a while
+    SlowDown,
+    /// Worth more points, but goes off after a while
+    Bonus,
+    /// Ends the round of the character eating it
+    Poison,
+    /// Gives the character a timed effect instead of growing it
+    PowerUp(PowerUp),
+}
+
+/// Ticks a bonus food stays on the board
+pub const BONUS_TICKS: u32 = 50;
+
+/// Ticks the pace stays changed after eating a speed-up or a slow-down
+pub const PACE_TICKS: u32 = 40;
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 11] = [
+        Self::Apple,
+        Self::Golden,
+        Self::Shrinking,
+        Self::SpeedUp,
+        Self::SlowDown,
+        Self::Bonus,
+        Self::Poison,
+        Self::PowerUp(PowerUp::Ghost),
+        Self::PowerUp(PowerUp::Invincible),
+        Self::PowerUp(PowerUp::Magnet),
+        Self::PowerUp(PowerUp::Multiplier),
+    ];
+
+    /// Returns the kind with the given name
+    pub fn from_name(name: &str) -> Option<FoodKind> {
+        Self::ALL
+            .iter()
+            .find(|kind| kind.get_name() == name)
+            .copied()
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Apple => "apple",
+            Self::Golden => "golden",
+            Self::Shrinking => "shrinking",
+            Self::SpeedUp => "speed-up",
+            Self::SlowDown => "slow-down",
+            Self::Bonus => "bonus",
+            Self::Poison => "poison",
+            Self::PowerUp(power_up) => power_up.get_name(),
+        }
+    }
+
+    /// Returns the points the food is worth
+    pub fn get_points(&self) -> u32 {
+        match self {
+            Self::Golden => 5,
+            Self::Bonus => 3,
+            Self::Poison | Self::PowerUp(_) => 0,
+            _ => 1,
+        }
+    }
+
+    /// Returns the number of ticks the food stays on the board, None if it stays until eaten
+    pub fn get_lifetime(&self) -> Option<u32> {
+        match self {
+            Self::Bonus => Some(BONUS_TICKS),
+            _ => None,
+        }
+    }
+}
+
+/// How often each kind of food is spawned compared to the others
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoodWeights(Vec<(FoodKind, u32)>);
+
+impl FoodWeights {
+    /// Returns None if no kind can be spawned or the total weight doesn't fit into a u32
+    pub fn new(weights: Vec<(FoodKind, u32)>) -> Option<FoodWeights> {
+        let weights: Vec<(FoodKind, u32)> = weights
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .collect();
+        weights
+            .iter()
+            .try_fold(0u32, |total, (_, weight)| total.checked_add(*weight))?;
+        match weights.is_empty() {
+            true => None,
+            false => Some(FoodWeights(weights)),
+        }
+    }
+
+    /// Parses a list like `apple=10,golden=1`
+    pub fn parse(value: &str) -> Option<FoodWeights> {
+        let weights = value
+            .split(',')
+            .map(|item| {
+                let (name, weight) = item.split_once('=')?;
+                Some((
+                    FoodKind::from_name(name.trim())?,
+                    weight.trim().parse().ok()?,
+                ))
+            })
+            .collect::<Option<Vec<(FoodKind, u32)>>>()?;
+        Self::new(weights)
+    }
+
+    /// Picks a kind of food, the random generator is only used if there is a choice
+    pub fn choose(&self, rng: &mut dyn RngCore) -> FoodKind {
+        if let [(kind, _)] = self.0.as_slice() {
+            return *kind;
+        }
+        let total: u32 = self.0.iter().map(|(_, weight)| weight).sum();
+        let mut pick = rng.gen_range(0, total);
+        for (kind, weight) in &self.0 {
+            if pick < *weight {
+                return *kind;
+            }
+            pick -= weight;
+        }
+        unreachable!("The pick is less than the total weight")
+    }
+}
+
+/// Only apples
+impl Default for FoodWeights {
+    fn default() -> Self {
+        FoodWeights(vec![(FoodKind::Apple, 1)])
+    }
+}
+
+/// The same list that `FoodWeights::parse` reads
+impl Display for FoodWeights {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let items: Vec<String> = self
+            .0
+            .iter()
+            .map(|(kind, weight)| format!("{}={}", kind.get_name(), weight))
+            .collect();
+        write!(f, "{}", items.join(","))
+    }
+}
+
+/// Temporary change of the game's pace caused by food, with the remaining ticks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pace {
+    Normal,
+    Fast(u32),
+    Slow(u32),
+}
+
+impl Pace {
+    /// Counts down the remaining ticks
+    pub fn tick(self) -> Pace {
+        match self {
+            Self::Fast(ticks) if ticks > 1 => Self::Fast(ticks - 1),
+            Self::Slow(ticks) if ticks > 1 => Self::Slow(ticks - 1),
+            _ => Self::Normal,
+        }
+    }
+
+    /// Returns the multiplier of the time between two ticks
+    pub fn get_factor(&self) -> f64 {
+        match self {
+            Self::Normal => 1.0,
+            Self::Fast(_) => 2.0 / 3.0,
+            Self::Slow(_) => 1.5,
+        }
+    }
+}
+
+/// `normal`, or the change with its remaining ticks, e.g. `fast 12`
+impl Snapshot for Pace {
+    fn save_state(&self) -> String {
+        match self {
+            Self::Normal => "normal".to_string(),
+            Self::Fast(ticks) => format!("fast {}", ticks),
+            Self::Slow(ticks) => format!("slow {}", ticks),
+        }
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        let words: Vec<&str> = state.split_whitespace().collect();
+        *self = match words.as_slice() {
+            ["normal"] => Self::Normal,
+            ["fast", ticks] => Self::Fast(ticks.parse().ok()?),
+            ["slow", ticks] => Self::Slow(ticks.parse().ok()?),
+            _ => return None,
+        };
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod food_test;
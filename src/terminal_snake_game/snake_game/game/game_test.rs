@@ -0,0 +1,512 @@
+use super::super::{apple::Apple, level::Level, map::Map as GameMap, snake::Snake};
+use super::food::FoodWeights;
+use super::vec2::Vec2;
+use super::*;
+use rand::SeedableRng;
+
+fn new_apple(pos: Vec2, kind: FoodKind) -> Box<dyn Food> {
+    let mut apple = Apple::with_weights(FoodWeights::new(vec![(kind, 1)]).unwrap());
+    apple.pos = pos;
+    apple.kind = kind;
+    Box::new(apple)
+}
+
+fn new_game(score_board: MockScoreBoard) -> Game {
+    Game::new(
+        Box::new(GameMap::new(10, 10)),
+        vec![Box::new(Snake::new(Vec2 { x: 10, y: 10 }))],
+        vec![new_apple(Vec2 { x: 0, y: 0 }, FoodKind::Apple)],
+        Box::new(score_board),
+        GameRng::seed_from_u64(1),
+    )
+}
+
+#[test]
+fn it_starts_from_the_best_score() {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 42);
+
+    let game = new_game(score_board);
+
+    assert_eq!(
+        game.get_score(),
+        &Score {
+            length: 4,
+            ..Score::new(42)
+        }
+    );
+}
+
+#[test]
+fn it_counts_ticks() {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+
+    let mut game = new_game(score_board);
+    game.tick().unwrap();
+    game.tick().unwrap();
+
+    assert_eq!(game.get_score().ticks, 2);
+}
+
+#[test]
+fn it_submits_the_score_on_death() {
+    // GIVEN
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    score_board
+        .expect_submit()
+        .withf(|score| score.ticks == 5)
+        .times(1)
+        .returning(|_| ());
+    let mut game = new_game(score_board);
+    game.foods = vec![new_apple(Vec2 { x: 1, y: 1 }, FoodKind::Apple)];
+
+    // WHEN
+    // the snake heads down from (5, 4) so it hits the wall on the fifth tick
+    for _ in 0..4 {
+        game.tick().unwrap();
+    }
+    let result = game.tick();
+
+    // THEN
+    assert!(matches!(result, Err(GameError::KilledByWall)));
+    assert_eq!(game.get_score().ticks, 5);
+}
+
+#[test]
+fn it_starts_a_new_round_on_reset() {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+
+    let mut game = new_game(score_board);
+    game.tick().unwrap();
+    game.reset();
+
+    assert_eq!(
+        game.get_score(),
+        &Score {
+            length: 4,
+            ..Score::new(0)
+        }
+    );
+}
+
+#[test]
+fn it_draws_the_hud_below_the_map() {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+
+    let game = new_game(score_board);
+    let paint = game.draw();
+
+    assert_eq!(paint.len(), 11);
+    assert_eq!(paint.last().unwrap().pos, Vec2 { x: 0, y: 10 });
+}
+
+#[test]
+fn it_keeps_the_score_on_another_map() {
+    // GIVEN
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    let mut game = new_game(score_board);
+    game.scores[0].eat(3);
+
+    // WHEN
+    game.change_map(
+        Box::new(GameMap::new(20, 15)),
+        vec![Box::new(Snake::new(Vec2 { x: 20, y: 15 }))],
+    );
+
+    // THEN
+    assert_eq!(game.get_size(), Vec2 { x: 20, y: 15 });
+    assert_eq!(game.get_score().points, 3);
+    assert_eq!(game.get_score().length, 4);
+}
+
+fn occupied(pos: Vec2) -> MockIsFreePos {
+    let mut mock = MockIsFreePos::new();
+    mock.expect_is_free_pos()
+        .returning(move |checked| *checked != pos);
+    mock
+}
+
+#[test]
+fn it_finds_heads_in_walls_and_other_snakes() {
+    // GIVEN
+    let map = occupied(Vec2 { x: 0, y: 0 });
+    // the first snake's body lies where the second one's head is
+    let first = occupied(Vec2 { x: 2, y: 2 });
+    let second = occupied(Vec2 { x: 1, y: 1 });
+    let heads = [Some(Vec2 { x: 0, y: 0 }), Some(Vec2 { x: 2, y: 2 })];
+    let none = Effects::new();
+
+    // WHEN
+    let collisions = find_collisions(&heads, &map, &[], &[&first, &second], &[&none, &none]);
+
+    // THEN
+    assert_eq!(
+        collisions,
+        vec![(0, GameError::KilledByWall), (1, GameError::KilledBySnake)]
+    );
+}
+
+#[test]
+fn it_ends_head_to_head_collisions_for_both_snakes() {
+    // GIVEN
+    let map = occupied(Vec2 { x: 0, y: 0 });
+    let first = occupied(Vec2 { x: 3, y: 3 });
+    let second = occupied(Vec2 { x: 3, y: 3 });
+    let heads = [Some(Vec2 { x: 3, y: 3 }), Some(Vec2 { x: 3, y: 3 })];
+    let none = Effects::new();
+
+    // WHEN
+    let collisions = find_collisions(&heads, &map, &[], &[&first, &second], &[&none, &none]);
+
+    // THEN
+    assert_eq!(
+        collisions,
+        vec![(0, GameError::KilledBySnake), (1, GameError::KilledBySnake)]
+    );
+}
+
+#[test]
+fn it_ignores_players_that_have_already_lost() {
+    // GIVEN
+    let map = occupied(Vec2 { x: 0, y: 0 });
+    let snake = occupied(Vec2 { x: 3, y: 3 });
+
+    // WHEN
+    let collisions = find_collisions(&[None], &map, &[], &[&snake], &[&Effects::new()]);
+
+    // THEN
+    assert!(collisions.is_empty());
+}
+
+/// Puts a food of the given kind right in front of the snake, which heads down from (5, 4)
+fn new_game_with_food(kind: FoodKind) -> Game {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    score_board.expect_submit().returning(|_| ());
+    let mut game = new_game(score_board);
+    game.foods = vec![new_apple(Vec2 { x: 5, y: 5 }, kind)];
+    game
+}
+
+#[test]
+fn it_gives_more_points_for_golden_apples() {
+    let mut game = new_game_with_food(FoodKind::Golden);
+
+    game.tick().unwrap();
+    game.tick().unwrap();
+
+    assert_eq!(game.get_score().points, 5);
+    assert_eq!(game.get_score().length, 5);
+}
+
+#[test]
+fn it_shrinks_the_snake_on_shrinking_food() {
+    let mut game = new_game_with_food(FoodKind::Shrinking);
+
+    game.tick().unwrap();
+    game.tick().unwrap();
+
+    assert_eq!(game.get_score().points, 1);
+    assert_eq!(game.get_score().length, 3);
+}
+
+#[test]
+fn it_ends_the_round_on_poison() {
+    let mut game = new_game_with_food(FoodKind::Poison);
+
+    game.tick().unwrap();
+    let result = game.tick();
+
+    assert_eq!(result, Err(GameError::Poisoned));
+    assert_eq!(game.get_score().points, 0);
+}
+
+#[test]
+fn it_changes_the_pace_for_a_while() {
+    let mut game = new_game_with_food(FoodKind::SpeedUp);
+
+    game.tick().unwrap();
+    game.tick().unwrap();
+    assert_eq!(game.get_pace(), Pace::Fast(PACE_TICKS - 1));
+
+    game.reset();
+    assert_eq!(game.get_pace(), Pace::Normal);
+}
+
+#[test]
+fn it_spawns_every_food_to_a_different_position() {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+
+    let game = Game::new(
+        Box::new(GameMap::new(6, 6)),
+        vec![Box::new(Snake::new(Vec2 { x: 6, y: 6 }))],
+        (0..8)
+            .map(|_| new_apple(Vec2 { x: 0, y: 0 }, FoodKind::Apple))
+            .collect(),
+        Box::new(score_board),
+        GameRng::seed_from_u64(1),
+    );
+
+    let mut positions: Vec<Vec2> = game
+        .foods
+        .iter()
+        .map(|food| food.get_pos().clone())
+        .collect();
+    positions.sort_by_key(|pos| (pos.x, pos.y));
+    positions.dedup();
+    assert_eq!(positions.len(), 8);
+}
+
+#[test]
+fn it_lets_invincible_heads_through_walls() {
+    let map = occupied(Vec2 { x: 0, y: 0 });
+    let snake = occupied(Vec2 { x: 3, y: 3 });
+    let mut effects = Effects::new();
+    effects.activate(PowerUp::Invincible);
+
+    let heads = [Some(Vec2 { x: 0, y: 0 })];
+    let collisions = find_collisions(&heads, &map, &[], &[&snake], &[&effects]);
+
+    assert!(collisions.is_empty());
+}
+
+#[test]
+fn it_gives_power_ups_instead_of_growing() {
+    // GIVEN
+    let mut game = new_game_with_food(FoodKind::PowerUp(PowerUp::Invincible));
+
+    // WHEN
+    // the snake would hit the wall on the fifth tick
+    for _ in 0..6 {
+        game.tick().unwrap();
+    }
+
+    // THEN
+    assert_eq!(game.get_score().length, 4);
+    assert_eq!(game.get_score().apples, 0);
+    assert!(game.characters[0]
+        .get_effects()
+        .is_active(PowerUp::Invincible));
+}
+
+#[test]
+fn it_multiplies_the_points_of_food() {
+    let mut game = new_game_with_food(FoodKind::Golden);
+    game.characters[0]
+        .get_effects_mut()
+        .activate(PowerUp::Multiplier);
+
+    game.tick().unwrap();
+    game.tick().unwrap();
+
+    assert_eq!(game.get_score().points, 10);
+}
+
+#[test]
+fn it_pulls_food_towards_a_magnet() {
+    let mut game = new_game_with_food(FoodKind::Apple);
+    // the head gets to (5, 5), the food moves along the longer distance
+    game.foods = vec![new_apple(Vec2 { x: 2, y: 4 }, FoodKind::Apple)];
+    game.characters[0]
+        .get_effects_mut()
+        .activate(PowerUp::Magnet);
+
+    game.tick().unwrap();
+
+    assert_eq!(game.foods[0].get_pos(), &Vec2 { x: 3, y: 4 });
+}
+
+#[test]
+fn it_shows_the_active_power_ups_in_the_hud() {
+    let mut game = new_game_with_food(FoodKind::Apple);
+    game.characters[0]
+        .get_effects_mut()
+        .activate(PowerUp::Ghost);
+
+    let hud: String = game
+        .draw_hud()
+        .iter()
+        .flat_map(|instruction| instruction.shape.iter())
+        .filter_map(|symbol| match symbol.1 {
+            draw_instruction::Symbol::Text(c) => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    assert!(hud.ends_with("  Ghost 30"));
+}
+
+#[test]
+fn it_ends_the_round_once_the_board_is_filled() {
+    // GIVEN
+    let level =
+        Level::parse("name: Full\nspawn: 4 1 right\n---\n#######\n#.....#\n#######\n").unwrap();
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    score_board.expect_submit().times(1).returning(|_| ());
+    let mut game = Game::new(
+        Box::new(GameMap::from_level(&level)),
+        vec![Box::new(Snake::with_spawn(level.get_size(), &level.spawn))],
+        vec![new_apple(Vec2 { x: 0, y: 0 }, FoodKind::Apple)],
+        Box::new(score_board),
+        GameRng::seed_from_u64(1),
+    );
+    // the snake takes the last free cell with its head
+    game.characters[0].grow();
+    game.tick().unwrap();
+    assert_eq!(game.foods[0].get_pos(), &Vec2 { x: 5, y: 1 });
+
+    // WHEN
+    let result = game.tick();
+
+    // THEN
+    assert_eq!(result, Err(GameError::BoardFilled));
+    assert_eq!(game.get_score().apples, 1);
+}
+
+/// Creates a game on the level, the food is out of the way
+fn new_game_on_level(level: &Level) -> Game {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    score_board.expect_submit().returning(|_| ());
+    Game::new(
+        Box::new(GameMap::from_level(level)),
+        vec![Box::new(Snake::with_spawn(level.get_size(), &level.spawn))],
+        vec![new_apple(Vec2 { x: 1, y: 1 }, FoodKind::Apple)],
+        Box::new(score_board),
+        GameRng::seed_from_u64(1),
+    )
+}
+
+#[test]
+fn it_ends_the_round_when_a_head_meets_a_patrol() {
+    // GIVEN
+    let level = Level::parse(
+        "name: Head-on\nspawn: 4 2 right\npatrol: 7 2 left 2\n---\n#########\n#.......#\n#.......#\n#########\n",
+    )
+    .unwrap();
+    let mut game = new_game_on_level(&level);
+    game.tick().unwrap();
+
+    // WHEN
+    // the head and the patrol would swap their cells
+    let result = game.tick();
+
+    // THEN
+    assert_eq!(result, Err(GameError::KilledByWall));
+}
+
+#[test]
+fn it_ends_the_round_when_a_patrol_moves_onto_a_body() {
+    // GIVEN
+    let level = Level::parse(&format!(
+        "name: Crossing\nspawn: 4 3 right\npatrol: 7 1 down 2\n---\n{}{}{}",
+        "#############\n",
+        "#...........#\n".repeat(3),
+        "#############\n"
+    ))
+    .unwrap();
+    let mut game = new_game_on_level(&level);
+    for _ in 0..5 {
+        game.tick().unwrap();
+    }
+
+    // WHEN
+    // the patrol comes down onto the tail
+    let result = game.tick();
+
+    // THEN
+    assert_eq!(result, Err(GameError::KilledByWall));
+}
+
+#[test]
+fn it_takes_heads_through_portals() {
+    // GIVEN
+    let level = Level::parse(
+        "name: Portals\nspawn: 4 2 right\n---\n#########\n#.......#\n#.....1.#\n#.......#\n#.1.....#\n#########\n",
+    )
+    .unwrap();
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    let mut game = Game::new(
+        Box::new(GameMap::from_level(&level)),
+        vec![Box::new(Snake::with_spawn(level.get_size(), &level.spawn))],
+        vec![new_apple(Vec2 { x: 1, y: 1 }, FoodKind::Apple)],
+        Box::new(score_board),
+        GameRng::seed_from_u64(1),
+    );
+
+    // WHEN
+    game.tick().unwrap();
+    game.tick().unwrap();
+
+    // THEN
+    assert_eq!(game.characters[0].get_head_pos(), &Vec2 { x: 2, y: 4 });
+    game.tick().unwrap();
+    assert_eq!(game.characters[0].get_head_pos(), &Vec2 { x: 3, y: 4 });
+}
+
+fn new_mixed_game() -> Game {
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    score_board.expect_submit().returning(|_| ());
+    let weights = FoodWeights::parse("apple=2,golden=1,bonus=1,ghost=1").unwrap();
+    Game::new(
+        Box::new(GameMap::new(20, 20)),
+        vec![Box::new(Snake::new(Vec2 { x: 20, y: 20 }))],
+        (0..3)
+            .map(|_| Box::new(Apple::with_weights(weights.clone())) as Box<dyn Food>)
+            .collect(),
+        Box::new(score_board),
+        GameRng::seed_from_u64(7),
+    )
+}
+
+#[test]
+fn it_continues_exactly_from_a_saved_state() {
+    // GIVEN
+    let mut game = new_mixed_game();
+    for _ in 0..3 {
+        game.tick().unwrap();
+    }
+    game.turn_character(0, Direction::Right);
+    game.tick().unwrap();
+
+    // WHEN
+    let mut restored = new_mixed_game();
+    restored.load_state(&game.save_state()).unwrap();
+
+    // THEN
+    assert_eq!(restored.save_state(), game.save_state());
+    assert_eq!(restored.draw(), game.draw());
+    for _ in 0..8 {
+        assert_eq!(restored.tick().is_ok(), game.tick().is_ok());
+        assert_eq!(restored.get_scores(), game.get_scores());
+        assert_eq!(restored.draw(), game.draw());
+    }
+}
+
+#[test]
+fn it_refuses_states_of_other_games() {
+    let game = new_mixed_game();
+    let state = game.save_state();
+    let mut score_board = MockScoreBoard::new();
+    score_board.expect_best().returning(|| 0);
+    let mut other = new_game(score_board);
+    let before = other.save_state();
+
+    // the other game has only one food
+    assert_eq!(other.load_state(&state), None);
+    assert_eq!(other.load_state("rng 1\npace normal"), None);
+    assert_eq!(
+        other.load_state(&format!("{}\nweather sunny", before)),
+        None
+    );
+    assert_eq!(other.save_state(), before);
+}
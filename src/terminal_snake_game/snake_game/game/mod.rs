@@ -1,54 +1,410 @@
 pub mod direction;
 pub mod draw_instruction;
 pub mod errors;
+pub mod food;
+pub mod power_up;
+pub mod score;
 pub mod traits;
 pub mod vec2;
 
+use super::ai::BoardView;
 use super::traits::Game as GameTrait;
-use direction::Direction;
+use direction::{Directed, Direction};
+use food::{FoodKind, Pace, PACE_TICKS};
+use power_up::{Effects, PowerUp, MAGNET_RANGE};
+use rand_chacha::ChaCha8Rng;
+use score::Score;
 use traits::*;
 
+/// The only source of randomness of a game, so a seed determines the whole game
+pub type GameRng = ChaCha8Rng;
+
 pub struct Game {
     map: Box<dyn Map>,
-    character: Box<dyn Character>,
-    food: Box<dyn Food>,
+    /// One character for every player, in the order of the players
+    characters: Vec<Box<dyn Character>>,
+    foods: Vec<Box<dyn Food>>,
+    scores: Vec<Score>,
+    score_board: Box<dyn ScoreBoard>,
+    rng: GameRng,
+    /// Change of the pace caused by the food eaten lately
+    pace: Pace,
 }
 
 impl Game {
-    pub fn new(map: Box<dyn Map>, character: Box<dyn Character>, mut food: Box<dyn Food>) -> Game {
-        food.spawn(&map.get_size(), character.as_is_free_pos());
+    /// Creates a game where each player controls one of the characters
+    pub fn new(
+        map: Box<dyn Map>,
+        characters: Vec<Box<dyn Character>>,
+        foods: Vec<Box<dyn Food>>,
+        score_board: Box<dyn ScoreBoard>,
+        rng: GameRng,
+    ) -> Game {
+        let best = score_board.best();
+        let scores = characters
+            .iter()
+            .map(|character| Score {
+                length: character.get_length(),
+                ..Score::new(best)
+            })
+            .collect();
 
-        Game {
+        let mut game = Game {
             map,
-            character,
-            food,
+            characters,
+            foods,
+            scores,
+            score_board,
+            rng,
+            pace: Pace::Normal,
+        };
+        game.spawn_foods();
+        game
+    }
+
+    fn spawn_foods(&mut self) {
+        for index in 0..self.foods.len() {
+            self.spawn_food(index);
         }
     }
 
-    fn step_character(&mut self) -> Result<(), GameError> {
+    /// Puts the food to a position that is free of the map, the characters and the other foods
+    ///
+    /// Returns false if there is no such position left.
+    fn spawn_food(&mut self, index: usize) -> bool {
         let map_size = self.map.get_size();
-        if self.character.can_eat(self.food.get_pos()) {
-            self.character.grow();
-            self.food.spawn(&map_size, self.character.as_is_free_pos());
+        let others = self.get_other_foods(index);
+        let portals = NoPortal(self.map.as_ref());
+        let position_checker =
+            get_food_checker(self.map.as_ref(), &self.characters, &others, &portals);
+        self.foods[index].spawn(&map_size, &position_checker, &mut self.rng)
+    }
+
+    /// Returns the positions of the foods except the given one
+    fn get_other_foods(&self, index: usize) -> Taken {
+        Taken(
+            self.foods
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != index)
+                .map(|(_, food)| food.get_pos().clone())
+                .collect(),
+        )
+    }
+
+    /// Moves the foods near the characters with a magnet one step closer to their heads
+    fn pull_foods(&mut self) {
+        let magnets: Vec<vec2::Vec2> = self
+            .characters
+            .iter()
+            .filter(|character| character.get_effects().is_active(PowerUp::Magnet))
+            .map(|character| character.get_head_pos().clone())
+            .collect();
+        for head in magnets {
+            for index in 0..self.foods.len() {
+                let food = self.foods[index].get_pos().clone();
+                let distance = get_distance(&food, &head);
+                if distance <= 1 || distance > MAGNET_RANGE {
+                    continue;
+                }
+                let next = get_step_towards(&food, &head);
+                let others = self.get_other_foods(index);
+                let portals = NoPortal(self.map.as_ref());
+                let checker =
+                    get_food_checker(self.map.as_ref(), &self.characters, &others, &portals);
+                if checker.is_free_pos(&next) {
+                    self.foods[index].move_to(next);
+                }
+            }
+        }
+    }
+
+    /// Applies the effects of the food the characters are about to eat
+    ///
+    /// Returns the indexes of the eaten foods and the players who have eaten poison.
+    fn eat_foods(&mut self) -> (Vec<usize>, Vec<(usize, GameError)>) {
+        let mut eaten = Vec::new();
+        let mut poisoned = Vec::new();
+        for (player, (character, score)) in
+            self.characters.iter_mut().zip(&mut self.scores).enumerate()
+        {
+            for (index, food) in self.foods.iter().enumerate() {
+                if !character.can_eat(food.get_pos()) {
+                    continue;
+                }
+                let kind = food.get_kind();
+                match kind {
+                    FoodKind::Poison => poisoned.push((player, GameError::Poisoned)),
+                    FoodKind::Shrinking => character.shrink(),
+                    FoodKind::PowerUp(power_up) => character.get_effects_mut().activate(power_up),
+                    _ => character.grow(),
+                }
+                match kind {
+                    FoodKind::SpeedUp => self.pace = Pace::Fast(PACE_TICKS),
+                    FoodKind::SlowDown => self.pace = Pace::Slow(PACE_TICKS),
+                    _ => (),
+                }
+                let points = kind.get_points() * character.get_effects().get_multiplier();
+                if points > 0 {
+                    score.eat(points);
+                }
+                eaten.push(index);
+            }
+        }
+        (eaten, poisoned)
+    }
+
+    fn step_characters(&mut self) -> Result<(), GameError> {
+        let (eaten, mut losers) = self.eat_foods();
+        let mut filled = false;
+        for index in 0..self.foods.len() {
+            // food that has gone off is put somewhere else
+            if !self.foods[index].tick() || eaten.contains(&index) {
+                filled |= !self.spawn_food(index);
+            }
+        }
+        if filled && losers.is_empty() {
+            return Err(GameError::BoardFilled);
+        }
+        self.pace = self.pace.tick();
+
+        self.map.tick();
+        let map = &self.map;
+        let mut heads = Vec::new();
+        for (player, (character, score)) in
+            self.characters.iter_mut().zip(&mut self.scores).enumerate()
+        {
+            if losers.iter().any(|(loser, _)| *loser == player) {
+                heads.push(None);
+                continue;
+            }
+            let step = character.step().and_then(|()| {
+                match map.get_portal_exit(character.get_head_pos()) {
+                    Some(exit) => character.teleport(exit),
+                    None => Ok(()),
+                }
+            });
+            match step {
+                Ok(()) => {
+                    score.tick();
+                    score.length = character.get_length();
+                    heads.push(Some(character.get_head_pos().clone()));
+                }
+                Err(err) => {
+                    losers.push((player, GameError::from(err)));
+                    heads.push(None);
+                }
+            }
+        }
+
+        let characters: Vec<&dyn IsFreePos> = self
+            .characters
+            .iter()
+            .map(|character| character.as_is_free_pos())
+            .collect();
+        let effects: Vec<&Effects> = self
+            .characters
+            .iter()
+            .map(|character| character.get_effects())
+            .collect();
+        losers.extend(find_collisions(
+            &heads,
+            self.map.as_is_free_pos(),
+            &self.map.get_patrols(),
+            &characters,
+            &effects,
+        ));
+        losers.sort_by_key(|(player, _)| *player);
+
+        self.pull_foods();
+        for character in &mut self.characters {
+            character.get_effects_mut().tick();
+        }
+
+        match losers.len() {
+            0 => Ok(()),
+            _ if self.characters.len() == 1 => Err(losers.remove(0).1),
+            _ => Err(GameError::PlayersLost(losers)),
         }
-        self.character.step()?;
-        self.draw();
+    }
 
-        if !self.map.is_free_pos(self.character.get_head_pos()) {
-            Err(GameError::KilledByWall)
-        } else {
-            Ok(())
+    fn draw_hud(&self) -> Vec<draw_instruction::DrawInstruction> {
+        match (self.scores.as_slice(), self.characters.as_slice()) {
+            ([score], [character]) => {
+                let mut hud = score.draw();
+                let effects = character.get_effects();
+                if !effects.is_empty() {
+                    let end = hud.iter().map(|text| text.shape.len()).sum();
+                    hud.push(draw_instruction::DrawInstruction::text(
+                        vec2::Vec2 { x: end, y: 0 },
+                        &format!("  {}", effects),
+                    ));
+                }
+                hud
+            }
+            (scores, characters) => {
+                let text: String = scores
+                    .iter()
+                    .zip(characters)
+                    .enumerate()
+                    .map(|(player, (score, character))| {
+                        let effects = character.get_effects();
+                        format!(
+                            " P{}: {}  Length: {}{} ",
+                            player + 1,
+                            score.points,
+                            score.length,
+                            match effects.is_empty() {
+                                true => String::new(),
+                                false => format!("  {}", effects),
+                            }
+                        )
+                    })
+                    .collect();
+                vec![draw_instruction::DrawInstruction::text(
+                    vec2::Vec2 { x: 0, y: 0 },
+                    &text,
+                )]
+            }
         }
     }
+
+    /// Returns the states of the map, the characters and the foods
+    fn save_board(&self) -> (String, Vec<String>, Vec<String>) {
+        (
+            self.map.save_state(),
+            self.characters
+                .iter()
+                .map(|character| character.save_state())
+                .collect(),
+            self.foods.iter().map(|food| food.save_state()).collect(),
+        )
+    }
+
+    /// Restores the map, the characters and the foods, the food has to be on the board
+    fn load_board(&mut self, map: &str, characters: &[&str], foods: &[&str]) -> Option<()> {
+        self.map.load_state(map)?;
+        for (character, state) in self.characters.iter_mut().zip(characters) {
+            character.load_state(state)?;
+        }
+        let size = self.map.get_size();
+        for (food, state) in self.foods.iter_mut().zip(foods) {
+            food.load_state(state)?;
+            let pos = food.get_pos();
+            if pos.x >= size.x || pos.y >= size.y {
+                return None;
+            }
+        }
+        Some(())
+    }
+}
+
+/// Returns the players whose head has run into the map or into another character
+///
+/// Players without a head have already lost. Every head is checked against the other
+/// characters, so on a head-to-head collision both players lose. A patrol that moves onto
+/// a body crushes the character; a head and a patrol that swap their cells collide this way
+/// too, as the patrol ends up on the neck. Invincible players pass through the walls of the
+/// map and the patrols.
+fn find_collisions(
+    heads: &[Option<vec2::Vec2>],
+    map: &dyn IsFreePos,
+    patrols: &[vec2::Vec2],
+    characters: &[&dyn IsFreePos],
+    effects: &[&Effects],
+) -> Vec<(usize, GameError)> {
+    heads
+        .iter()
+        .enumerate()
+        .filter_map(|(player, head)| {
+            let head = head.as_ref()?;
+            let invincible = effects[player].is_active(PowerUp::Invincible);
+            let crushed = patrols
+                .iter()
+                .any(|patrol| !characters[player].is_free_pos(patrol));
+            if !invincible && (!map.is_free_pos(head) || crushed) {
+                return Some((player, GameError::KilledByWall));
+            }
+            let hit = characters
+                .iter()
+                .enumerate()
+                .any(|(other, character)| other != player && !character.is_free_pos(head));
+            match hit {
+                true => Some((player, GameError::KilledBySnake)),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+/// Position checker that reports the portals of the map taken, food never gets eaten there
+struct NoPortal<'a>(&'a dyn Map);
+
+impl IsFreePos for NoPortal<'_> {
+    fn is_free_pos(&self, pos: &vec2::Vec2) -> bool {
+        self.0.get_portal_exit(pos).is_none()
+    }
+}
+
+/// Returns a checker of the positions free of the map, the characters and the other foods
+fn get_food_checker<'a>(
+    map: &'a dyn Map,
+    characters: &'a [Box<dyn Character>],
+    others: &'a Taken,
+    portals: &'a NoPortal<'a>,
+) -> AllFree<'a> {
+    AllFree(
+        std::iter::once(map.as_is_free_pos())
+            .chain(std::iter::once(portals as &dyn IsFreePos))
+            .chain(
+                characters
+                    .iter()
+                    .map(|character| character.as_is_free_pos()),
+            )
+            .chain(std::iter::once(others as &dyn IsFreePos))
+            .collect(),
+    )
+}
+
+/// Returns the number of steps between the positions, the edges aren't crossed
+fn get_distance(from: &vec2::Vec2, to: &vec2::Vec2) -> usize {
+    let distance = |a: usize, b: usize| a.max(b) - a.min(b);
+    distance(from.x, to.x) + distance(from.y, to.y)
+}
+
+/// Returns the neighbour of the position that is closer to the target, along the longer axis
+fn get_step_towards(from: &vec2::Vec2, to: &vec2::Vec2) -> vec2::Vec2 {
+    let towards = |a: usize, b: usize| match a.cmp(&b) {
+        std::cmp::Ordering::Less => a + 1,
+        std::cmp::Ordering::Greater => a - 1,
+        std::cmp::Ordering::Equal => a,
+    };
+    let (dx, dy) = (
+        from.x.max(to.x) - from.x.min(to.x),
+        from.y.max(to.y) - from.y.min(to.y),
+    );
+    match dx >= dy {
+        true => vec2::Vec2 {
+            x: towards(from.x, to.x),
+            y: from.y,
+        },
+        false => vec2::Vec2 {
+            x: from.x,
+            y: towards(from.y, to.y),
+        },
+    }
 }
 
 impl Draw for Game {
     fn draw(&self) -> Vec<draw_instruction::DrawInstruction> {
         let mut map = self.map.as_draw().draw();
-        let food = self.food.as_draw().draw();
-        let character = self.character.as_draw().draw();
+        let food = self.foods.iter().flat_map(|food| food.as_draw().draw());
+        let characters = self
+            .characters
+            .iter()
+            .flat_map(|character| character.as_draw().draw());
 
-        for instruction in food.into_iter().chain(character.into_iter()) {
+        for instruction in food.into_iter().chain(characters) {
             map.get_mut(instruction.pos.y)
                 .expect("Map does not cover the food's positon")
                 .shape
@@ -58,27 +414,117 @@ impl Draw for Game {
                 );
         }
 
+        // The HUD goes right below the map
+        let map_height = self.map.get_size().y;
+        map.extend(self.draw_hud().into_iter().map(|mut instruction| {
+            instruction.pos.y += map_height;
+            instruction
+        }));
+
         map
     }
 }
 
 impl GameTrait for Game {
-    fn tick(&mut self) {
-        match self.step_character() {
-            Ok(()) => (),
-            Err(GameError::SelfEatingStepError(_)) => self.reset(),
-            Err(GameError::KilledByWall) => self.reset(),
-            Err(_) => self.reset(),
-        };
+    fn tick(&mut self) -> Result<(), GameError> {
+        let result = self.step_characters();
+        if result.is_err() {
+            self.submit_score();
+        }
+        result
     }
 
-    fn turn_character(&mut self, direction: Direction) {
-        match self.character.turn(direction) {
-            Ok(()) => (),
-            // on self turning direction, we do nothing
-            Err(SelfTurningDirectionError) => (),
-        };
-        self.draw();
+    fn turn_character(&mut self, player: usize, direction: Direction) {
+        // on self turning direction, we do nothing
+        if let Some(character) = self.characters.get_mut(player) {
+            let _ = character.turn(direction);
+        }
+    }
+
+    fn change_map(&mut self, map: Box<dyn Map>, characters: Vec<Box<dyn Character>>) {
+        self.map = map;
+        self.characters = characters;
+        for (character, score) in self.characters.iter().zip(&mut self.scores) {
+            score.length = character.get_length();
+        }
+        self.spawn_foods();
+    }
+
+    fn submit_score(&mut self) {
+        // multiplayer results don't get into the table
+        if let [score] = self.scores.as_slice() {
+            self.score_board.submit(score);
+        }
+    }
+
+    fn get_score(&self) -> &Score {
+        &self.scores[0]
+    }
+
+    fn get_scores(&self) -> &[Score] {
+        &self.scores
+    }
+
+    fn get_high_scores(&self) -> Vec<score::HighScore> {
+        self.score_board.entries()
+    }
+
+    fn get_size(&self) -> vec2::Vec2 {
+        self.map.get_size()
+    }
+
+    fn get_pace(&self) -> Pace {
+        self.pace
+    }
+
+    fn get_board_view(&self, player: usize) -> BoardView<'_> {
+        let character = &self.characters[player];
+        let head = character.get_head_pos().clone();
+        let (poison, foods): (Vec<&dyn Food>, Vec<&dyn Food>) = self
+            .foods
+            .iter()
+            .map(|food| food.as_ref())
+            .partition(|food| food.get_kind() == FoodKind::Poison);
+        let mut view = BoardView::new(
+            self.map.get_size(),
+            Directed(character.get_direction().clone(), head.clone()),
+            head.clone(),
+            self.characters
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != player)
+                .map(|(_, character)| character.get_head_pos().clone())
+                .collect(),
+            self.map.as_is_free_pos(),
+            self.characters
+                .iter()
+                .map(|character| character.as_is_free_pos())
+                .collect(),
+        )
+        .with_wrapping(self.map.is_wrapping())
+        .with_poison(poison.iter().map(|food| food.get_pos().clone()).collect())
+        .with_foods(
+            self.foods
+                .iter()
+                .map(|food| (food.get_kind(), food.get_pos().clone()))
+                .collect(),
+        )
+        .with_bodies(
+            player,
+            self.characters
+                .iter()
+                .map(|character| character.get_body())
+                .collect(),
+        );
+        // the nearest food that is worth eating is the target
+        if let Some(food) = foods
+            .iter()
+            .map(|food| food.get_pos().clone())
+            .min_by_key(|pos| view.get_distance(&head, pos))
+        {
+            view.food = food;
+        }
+        view
     }
 
     fn as_draw(&self) -> &dyn Draw {
@@ -86,12 +532,113 @@ impl GameTrait for Game {
     }
 }
 
+/// A line for every part of the game, they start with the name of the part
+///
+/// ```text
+/// rng <position in the stream of the random generator>
+/// pace <pace>
+/// map <map>
+/// character <character>
+/// score <score>
+/// food <food>
+/// ```
+///
+/// There is a character and a score line for every player and a food line for every food,
+/// in their order. The random generator is restored from the same seed, so only its position
+/// is kept.
+impl Snapshot for Game {
+    fn save_state(&self) -> String {
+        let mut lines = vec![
+            format!("rng {}", self.rng.get_word_pos()),
+            format!("pace {}", self.pace.save_state()),
+            format!("map {}", self.map.save_state()),
+        ];
+        lines.extend(
+            self.characters
+                .iter()
+                .map(|character| format!("character {}", character.save_state())),
+        );
+        lines.extend(
+            self.scores
+                .iter()
+                .map(|score| format!("score {}", score.save_state())),
+        );
+        lines.extend(
+            self.foods
+                .iter()
+                .map(|food| format!("food {}", food.save_state())),
+        );
+        lines.join("\n")
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        let mut parts: Vec<(&str, &str)> = Vec::new();
+        for line in state.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut words = line.splitn(2, ' ');
+            parts.push((words.next()?, words.next().unwrap_or("")));
+        }
+        let get_all = |name: &str| -> Vec<&str> {
+            parts
+                .iter()
+                .filter(|(part, _)| *part == name)
+                .map(|(_, state)| *state)
+                .collect()
+        };
+        let (characters, scores, foods) = (get_all("character"), get_all("score"), get_all("food"));
+        let known = ["rng", "pace", "map", "character", "score", "food"];
+        if characters.len() != self.characters.len()
+            || scores.len() != self.scores.len()
+            || foods.len() != self.foods.len()
+            || parts.iter().any(|(part, _)| !known.contains(part))
+        {
+            return None;
+        }
+
+        // the parts are all parsed before the game changes
+        let (word_pos, pace_state, map) = match (
+            get_all("rng").as_slice(),
+            get_all("pace").as_slice(),
+            get_all("map").as_slice(),
+        ) {
+            ([rng], [pace], [map]) => (rng.parse().ok()?, *pace, *map),
+            _ => return None,
+        };
+        let mut pace = self.pace;
+        pace.load_state(pace_state)?;
+        let mut new_scores = self.scores.clone();
+        for (score, state) in new_scores.iter_mut().zip(scores) {
+            score.load_state(state)?;
+        }
+        // the board is put back as it was if a part of it is invalid
+        let board_before = self.save_board();
+        if self.load_board(map, &characters, &foods).is_none() {
+            let (map, characters, foods) = &board_before;
+            let characters: Vec<&str> = characters.iter().map(String::as_str).collect();
+            let foods: Vec<&str> = foods.iter().map(String::as_str).collect();
+            self.load_board(map, &characters, &foods)
+                .expect("The board was valid");
+            return None;
+        }
+
+        self.rng.set_word_pos(word_pos);
+        self.pace = pace;
+        self.scores = new_scores;
+        Some(())
+    }
+}
+
 impl Reset for Game {
     fn reset(&mut self) {
-        self.draw();
-        self.character.reset();
-
-        let map_size = self.map.get_size();
-        self.food.spawn(&map_size, self.character.as_is_free_pos());
+        for (character, score) in self.characters.iter_mut().zip(&mut self.scores) {
+            score.reset();
+            character.reset();
+            score.length = character.get_length();
+        }
+        self.pace = Pace::Normal;
+        self.map.reset();
+        self.spawn_foods();
     }
 }
+
+#[cfg(test)]
+mod game_test;
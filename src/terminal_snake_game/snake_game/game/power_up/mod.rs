@@ -0,0 +1,154 @@
+use super::traits::Snapshot;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// Timed effects that change how the character moves or scores
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PowerUp {
+    /// The character can pass through its own body
+    Ghost,
+    /// The character can pass through walls
+    Invincible,
+    /// Food nearby moves towards the character's head
+    Magnet,
+    /// Food is worth more points
+    Multiplier,
+}
+
+/// Distance from the head within food is pulled by a magnet
+pub const MAGNET_RANGE: usize = 5;
+
+/// Points of food are multiplied by this while the multiplier is active
+pub const SCORE_MULTIPLIER: u32 = 2;
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 4] = [
+        Self::Ghost,
+        Self::Invincible,
+        Self::Magnet,
+        Self::Multiplier,
+    ];
+
+    pub fn from_name(name: &str) -> Option<PowerUp> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|power_up| power_up.get_name() == name)
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Ghost => "ghost",
+            Self::Invincible => "invincible",
+            Self::Magnet => "magnet",
+            Self::Multiplier => "multiplier",
+        }
+    }
+
+    /// Returns the text shown in the HUD while the effect is active
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            Self::Ghost => "Ghost",
+            Self::Invincible => "Invincible",
+            Self::Magnet => "Magnet",
+            Self::Multiplier => "x2",
+        }
+    }
+
+    /// Returns the number of ticks the effect lasts
+    pub fn get_duration(&self) -> u32 {
+        match self {
+            Self::Ghost | Self::Invincible => 30,
+            Self::Magnet | Self::Multiplier => 50,
+        }
+    }
+}
+
+/// Power-ups active on a character with their remaining ticks
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Effects(Vec<(PowerUp, u32)>);
+
+impl Effects {
+    pub fn new() -> Effects {
+        Effects(Vec::new())
+    }
+
+    /// Starts the effect, or restarts it if it is already active
+    pub fn activate(&mut self, power_up: PowerUp) {
+        self.0.retain(|(active, _)| *active != power_up);
+        self.0.push((power_up, power_up.get_duration()));
+    }
+
+    pub fn is_active(&self, power_up: PowerUp) -> bool {
+        self.0.iter().any(|(active, _)| *active == power_up)
+    }
+
+    /// Returns the number the points of food are multiplied by
+    pub fn get_multiplier(&self) -> u32 {
+        match self.is_active(PowerUp::Multiplier) {
+            true => SCORE_MULTIPLIER,
+            false => 1,
+        }
+    }
+
+    /// Counts down the remaining ticks, the effects that have run out are removed
+    pub fn tick(&mut self) {
+        for (_, ticks) in &mut self.0 {
+            *ticks -= 1;
+        }
+        self.0.retain(|(_, ticks)| *ticks > 0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The active effects with their remaining ticks, e.g. `Ghost 12  x2 40`
+impl Display for Effects {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let items: Vec<String> = self
+            .0
+            .iter()
+            .map(|(power_up, ticks)| format!("{} {}", power_up.get_label(), ticks))
+            .collect();
+        write!(f, "{}", items.join("  "))
+    }
+}
+
+/// The active effects with their remaining ticks, e.g. `ghost:12,multiplier:40`, or `-` if none
+impl Snapshot for Effects {
+    fn save_state(&self) -> String {
+        if self.0.is_empty() {
+            return "-".to_string();
+        }
+        let items: Vec<String> = self
+            .0
+            .iter()
+            .map(|(power_up, ticks)| format!("{}:{}", power_up.get_name(), ticks))
+            .collect();
+        items.join(",")
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        if state == "-" {
+            self.0.clear();
+            return Some(());
+        }
+        let mut effects = Vec::new();
+        for item in state.split(',') {
+            let mut parts = item.splitn(2, ':');
+            let power_up = PowerUp::from_name(parts.next()?)?;
+            let ticks: u32 = parts.next()?.parse().ok()?;
+            if ticks == 0 || effects.iter().any(|(active, _)| *active == power_up) {
+                return None;
+            }
+            effects.push((power_up, ticks));
+        }
+        self.0 = effects;
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod power_up_test;
@@ -0,0 +1,68 @@
+use super::super::traits::Snapshot;
+use super::*;
+
+#[test]
+fn it_runs_out_after_its_duration() {
+    let mut effects = Effects::new();
+
+    effects.activate(PowerUp::Ghost);
+    for _ in 1..PowerUp::Ghost.get_duration() {
+        effects.tick();
+    }
+    assert!(effects.is_active(PowerUp::Ghost));
+
+    effects.tick();
+    assert!(!effects.is_active(PowerUp::Ghost));
+    assert!(effects.is_empty());
+}
+
+#[test]
+fn it_restarts_an_active_effect() {
+    let mut effects = Effects::new();
+    effects.activate(PowerUp::Magnet);
+    effects.tick();
+
+    effects.activate(PowerUp::Magnet);
+
+    assert_eq!(effects.to_string(), "Magnet 50");
+}
+
+#[test]
+fn it_multiplies_the_points_while_active() {
+    let mut effects = Effects::new();
+    assert_eq!(effects.get_multiplier(), 1);
+
+    effects.activate(PowerUp::Multiplier);
+    effects.activate(PowerUp::Invincible);
+    effects.tick();
+
+    assert_eq!(effects.get_multiplier(), SCORE_MULTIPLIER);
+    assert_eq!(effects.to_string(), "x2 49  Invincible 29");
+}
+
+#[test]
+fn it_can_save_and_restore_the_effects() {
+    let mut effects = Effects::new();
+    effects.activate(PowerUp::Ghost);
+    effects.activate(PowerUp::Multiplier);
+    effects.tick();
+
+    let state = effects.save_state();
+    let mut restored = Effects::new();
+    restored.load_state(&state).unwrap();
+
+    assert_eq!(state, "ghost:29,multiplier:49");
+    assert_eq!(restored, effects);
+}
+
+#[test]
+fn it_refuses_invalid_effect_states() {
+    let mut effects = Effects::new();
+
+    assert_eq!(effects.load_state("-"), Some(()));
+    assert_eq!(effects.load_state("ghost"), None);
+    assert_eq!(effects.load_state("ghost:0"), None);
+    assert_eq!(effects.load_state("ghost:2,ghost:3"), None);
+    assert_eq!(effects.load_state("flying:3"), None);
+    assert!(effects.is_empty());
+}
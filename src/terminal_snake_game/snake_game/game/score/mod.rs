@@ -0,0 +1,105 @@
+use super::draw_instruction::DrawInstruction;
+use super::traits::{Draw, Reset, Snapshot};
+use super::vec2::Vec2;
+
+/// Statistics of the running game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    /// Points collected in the current round
+    pub points: u32,
+    /// Number of apples eaten in the current round
+    pub apples: u32,
+    /// Current length of the character
+    pub length: usize,
+    /// Number of ticks since the round has started
+    pub ticks: u64,
+    /// Best points ever collected
+    pub best: u32,
+}
+
+impl Score {
+    pub fn new(best: u32) -> Score {
+        Score {
+            points: 0,
+            apples: 0,
+            length: 0,
+            ticks: 0,
+            best,
+        }
+    }
+
+    /// Registers an eaten apple worth the given points
+    pub fn eat(&mut self, points: u32) {
+        self.apples += 1;
+        self.points += points;
+        self.best = self.best.max(self.points);
+    }
+
+    /// Registers a unit of time
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+}
+
+/// An entry of the high-score table
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighScore {
+    pub points: u32,
+    pub length: usize,
+    pub ticks: u64,
+}
+
+impl From<&Score> for HighScore {
+    fn from(score: &Score) -> Self {
+        HighScore {
+            points: score.points,
+            length: score.length,
+            ticks: score.ticks,
+        }
+    }
+}
+
+impl Draw for Score {
+    fn draw(&self) -> Vec<DrawInstruction> {
+        let text = format!(
+            " Score: {}  Length: {}  Time: {}  Best: {}",
+            self.points, self.length, self.ticks, self.best
+        );
+        vec![DrawInstruction::text(Vec2 { x: 0, y: 0 }, &text)]
+    }
+}
+
+impl Reset for Score {
+    /// Starts a new round, only the best score is kept
+    fn reset(&mut self) {
+        *self = Score::new(self.best);
+    }
+}
+
+/// The statistics separated by spaces: `<points> <apples> <length> <ticks> <best>`
+impl Snapshot for Score {
+    fn save_state(&self) -> String {
+        format!(
+            "{} {} {} {} {}",
+            self.points, self.apples, self.length, self.ticks, self.best
+        )
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        let words: Vec<&str> = state.split_whitespace().collect();
+        *self = match words.as_slice() {
+            [points, apples, length, ticks, best] => Score {
+                points: points.parse().ok()?,
+                apples: apples.parse().ok()?,
+                length: length.parse().ok()?,
+                ticks: ticks.parse().ok()?,
+                best: best.parse().ok()?,
+            },
+            _ => return None,
+        };
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod score_test;
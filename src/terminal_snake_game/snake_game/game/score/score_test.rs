@@ -0,0 +1,78 @@
+use super::super::draw_instruction::Symbol;
+use super::*;
+
+#[test]
+fn it_can_count_apples() {
+    let mut score = Score::new(0);
+
+    score.eat(1);
+    score.eat(2);
+
+    assert_eq!(score.apples, 2);
+    assert_eq!(score.points, 3);
+}
+
+#[test]
+fn it_can_track_the_best_score() {
+    // GIVEN
+    let mut score = Score::new(2);
+
+    // WHEN
+    score.eat(1);
+
+    // THEN
+    assert_eq!(score.best, 2);
+
+    score.eat(1);
+    score.eat(1);
+    assert_eq!(score.best, 3);
+}
+
+#[test]
+fn it_keeps_the_best_score_on_reset() {
+    let mut score = Score::new(0);
+    score.eat(5);
+    score.tick();
+    score.length = 9;
+
+    score.reset();
+
+    assert_eq!(score, Score::new(5));
+}
+
+#[test]
+fn it_can_draw_itself() {
+    let mut score = Score::new(7);
+    score.eat(1);
+    score.length = 5;
+    score.tick();
+
+    let paint = score.draw();
+    let text: String = paint
+        .first()
+        .unwrap()
+        .shape
+        .iter()
+        .map(|directed| match directed.1 {
+            Symbol::Text(c) => c,
+            _ => unreachable!("HUD should contain text only"),
+        })
+        .collect();
+
+    assert_eq!(paint.len(), 1);
+    assert_eq!(text, " Score: 1  Length: 5  Time: 1  Best: 7");
+}
+
+#[test]
+fn it_can_save_and_restore_the_statistics() {
+    let mut score = Score::new(7);
+    score.eat(3);
+    score.tick();
+    score.length = 5;
+
+    let mut restored = Score::new(0);
+    restored.load_state(&score.save_state()).unwrap();
+
+    assert_eq!(restored, score);
+    assert_eq!(restored.load_state("1 2 3"), None);
+}
@@ -1,7 +1,11 @@
 use super::draw_instruction::DrawInstruction;
 pub use super::errors::*;
+use super::food::FoodKind;
+use super::power_up::Effects;
+use super::score::{HighScore, Score};
 use super::{direction::Direction, vec2::Vec2};
 use mockall::automock;
+use rand::RngCore;
 
 /// Objects that can draw themselves
 pub trait Draw {
@@ -15,6 +19,15 @@ pub trait Reset {
     fn reset(&mut self);
 }
 
+/// Objects whose state can be written into a save file and restored exactly
+pub trait Snapshot {
+    /// Returns the state as text, see the implementations for the format
+    fn save_state(&self) -> String;
+
+    /// Restores the state returned by `save_state`, returns None if the text is invalid
+    fn load_state(&mut self, state: &str) -> Option<()>;
+}
+
 /// Objects that can check wheter the given position is free or not
 #[automock]
 pub trait IsFreePos {
@@ -23,32 +36,89 @@ pub trait IsFreePos {
     fn is_free_pos(&self, pos: &Vec2) -> bool;
 }
 
+/// Position checker that reports a position free only if all of its checkers do
+pub struct AllFree<'a>(pub Vec<&'a dyn IsFreePos>);
+
+impl IsFreePos for AllFree<'_> {
+    fn is_free_pos(&self, pos: &Vec2) -> bool {
+        self.0.iter().all(|checker| checker.is_free_pos(pos))
+    }
+}
+
+/// Position checker that reports the given positions taken
+pub struct Taken(pub Vec<Vec2>);
+
+impl IsFreePos for Taken {
+    fn is_free_pos(&self, pos: &Vec2) -> bool {
+        !self.0.contains(pos)
+    }
+}
+
 /// Methods that an Reward should be able to do
-pub trait Food: Draw {
+pub trait Food: Draw + Snapshot {
     /// Generates a random position inside the given boundary until getting a free position
-    fn spawn(&mut self, boundary: &Vec2, position_checker: &dyn IsFreePos);
+    ///
+    /// Returns false if there is no free position left, the food stays where it is then.
+    fn spawn(
+        &mut self,
+        boundary: &Vec2,
+        position_checker: &dyn IsFreePos,
+        rng: &mut dyn RngCore,
+    ) -> bool;
 
     /// Returns the position of the apple
     fn get_pos(&self) -> &Vec2;
 
+    /// Puts the food to another position, e.g. when it is pulled by a magnet
+    fn move_to(&mut self, pos: Vec2);
+
+    /// Returns what eating the food does
+    fn get_kind(&self) -> FoodKind {
+        FoodKind::Apple
+    }
+
+    /// Counts down the lifetime of the food, returns false once it has gone off
+    fn tick(&mut self) -> bool {
+        true
+    }
+
     // Casts:
     fn as_draw(&self) -> &dyn Draw;
 }
 
 /// Methods that a character should be able to do
-pub trait Character: Draw + IsFreePos + Reset {
+pub trait Character: Draw + IsFreePos + Reset + Snapshot {
     /// Grows the character somehow
     fn grow(&mut self);
 
+    /// Makes the character shorter, it keeps at least its head and the part behind it
+    fn shrink(&mut self);
+
     /// Turns the character
     fn turn(&mut self, direction: Direction) -> Result<(), SelfTurningDirectionError>;
 
     /// Moves character to the next position
     fn step(&mut self) -> Result<(), SelfEatingStepError>;
 
+    /// Moves the head of the character to the given position, e.g. through a portal
+    fn teleport(&mut self, pos: Vec2) -> Result<(), SelfEatingStepError>;
+
+    /// Returns the number of cells the character takes
+    fn get_length(&self) -> usize;
+
     /// Returns the position of the character's head
     fn get_head_pos(&self) -> &Vec2;
 
+    /// Returns the cells the character takes, the head first
+    fn get_body(&self) -> Vec<Vec2>;
+
+    /// Returns the direction the character moves to in the next step
+    fn get_direction(&self) -> &Direction;
+
+    /// Returns the power-ups active on the character
+    fn get_effects(&self) -> &Effects;
+    fn get_effects_mut(&mut self) -> &mut Effects;
+
     // Returns whether the character's head is on the position or not
     fn can_eat(&self, pos: &Vec2) -> bool {
         self.get_head_pos() == pos
@@ -57,15 +127,39 @@ pub trait Character: Draw + IsFreePos + Reset {
     // Casts:
     fn as_draw(&self) -> &dyn Draw;
     fn as_is_free_pos(&self) -> &dyn IsFreePos;
-    fn as_reset(&self) -> &dyn Reset;
 }
 
 /// Methods that a Map should be able to do
-pub trait Map: Draw + IsFreePos {
+pub trait Map: Draw + IsFreePos + Reset + Snapshot {
     /// Returns the size of the map
     fn get_size(&self) -> Vec2;
 
+    /// Moves the hazards of the map, this function is called when a unit time has spent
+    fn tick(&mut self);
+
+    /// Returns where a head on the position is taken, None if it isn't on a portal
+    fn get_portal_exit(&self, pos: &Vec2) -> Option<Vec2>;
+
+    /// Returns whether a step over an edge can lead to the opposite edge
+    fn is_wrapping(&self) -> bool;
+
+    /// Returns the positions of the moving blocks
+    fn get_patrols(&self) -> Vec<Vec2>;
+
     // Casts:
     fn as_draw(&self) -> &dyn Draw;
     fn as_is_free_pos(&self) -> &dyn IsFreePos;
-}
\ No newline at end of file
+}
+
+/// Objects that can remember the best results between rounds
+#[automock]
+pub trait ScoreBoard {
+    /// Records the result of a finished round
+    fn submit(&mut self, score: &Score);
+
+    /// Returns the best points recorded so far
+    fn best(&self) -> u32;
+
+    /// Returns the recorded results, the best one first
+    fn entries(&self) -> Vec<HighScore>;
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+fn score(points: u32) -> Score {
+    Score {
+        points,
+        apples: points,
+        length: 4 + points as usize,
+        ticks: 10 * points as u64,
+        best: points,
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("rust-snake-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn it_keeps_the_best_ten_results() {
+    let mut table = HighScores::new();
+
+    for points in 1..=12 {
+        table.submit(&score(points));
+    }
+
+    let entries = table.entries();
+    assert_eq!(entries.len(), TABLE_SIZE);
+    assert_eq!(entries.first().unwrap().points, 12);
+    assert_eq!(entries.last().unwrap().points, 3);
+    assert_eq!(table.best(), 12);
+}
+
+#[test]
+fn it_ignores_empty_rounds() {
+    let mut table = HighScores::new();
+
+    table.submit(&score(0));
+
+    assert!(table.entries().is_empty());
+    assert_eq!(table.best(), 0);
+}
+
+#[test]
+fn it_can_persist_itself() {
+    // GIVEN
+    let path = temp_path("persist");
+    let mut table = HighScores::load(&path);
+
+    // WHEN
+    table.submit(&score(3));
+    table.submit(&score(8));
+
+    // THEN
+    let loaded = HighScores::load(&path);
+    assert_eq!(loaded.entries(), table.entries());
+    assert_eq!(
+        loaded.entries().first().unwrap(),
+        &HighScore::from(&score(8))
+    );
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn it_skips_broken_lines() {
+    // the points of the fourth line don't fit into a u32
+    let entries = HighScores::parse("5 9 50\nbroken\n7 11\n4294967296 10 60\n9 13 90\n");
+
+    assert_eq!(
+        entries,
+        vec![
+            HighScore {
+                points: 9,
+                length: 13,
+                ticks: 90
+            },
+            HighScore {
+                points: 5,
+                length: 9,
+                ticks: 50
+            },
+        ]
+    );
+}
+
+#[test]
+fn it_can_be_shared_by_several_games() {
+    let table = Rc::new(RefCell::new(HighScores::new()));
+    let mut first: Box<dyn ScoreBoard> = Box::new(table.clone());
+    let mut second: Box<dyn ScoreBoard> = Box::new(table.clone());
+
+    first.submit(&score(5));
+    second.submit(&score(8));
+
+    assert_eq!(first.best(), 8);
+    assert_eq!(table.borrow().entries().len(), 2);
+}
@@ -0,0 +1,139 @@
+use super::game::{
+    score::{HighScore, Score},
+    traits::ScoreBoard,
+};
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Number of entries kept in the table
+pub const TABLE_SIZE: usize = 10;
+
+/// Top-10 table of the best results, persisted in a plain text file
+pub struct HighScores {
+    path: Option<PathBuf>,
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Creates a table that lives only in memory
+    pub fn new() -> HighScores {
+        HighScores {
+            path: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Loads the table from the given file, a missing or broken file means an empty table
+    pub fn load(path: &Path) -> HighScores {
+        let entries = fs::read_to_string(path)
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default();
+
+        HighScores {
+            path: Some(path.to_path_buf()),
+            entries,
+        }
+    }
+
+    /// Returns the default location of the table in the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        data_dir().map(|dir| dir.join("high_scores"))
+    }
+
+    /// Writes the table to its file
+    pub fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, Self::serialize(&self.entries))
+    }
+
+    /// Every line is an entry: `<points> <length> <ticks>`
+    fn parse(content: &str) -> Vec<HighScore> {
+        let mut entries: Vec<HighScore> = content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                Some(HighScore {
+                    points: fields.next()?.parse().ok()?,
+                    length: fields.next()?.parse().ok()?,
+                    ticks: fields.next()?.parse().ok()?,
+                })
+            })
+            .collect();
+
+        Self::sort_and_truncate(&mut entries);
+        entries
+    }
+
+    fn serialize(entries: &[HighScore]) -> String {
+        entries
+            .iter()
+            .map(|entry| format!("{} {} {}\n", entry.points, entry.length, entry.ticks))
+            .collect()
+    }
+
+    fn sort_and_truncate(entries: &mut Vec<HighScore>) {
+        // stable sort, so an older entry stays ahead of a newer one with the same points
+        entries.sort_by_key(|entry| Reverse(entry.points));
+        entries.truncate(TABLE_SIZE);
+    }
+}
+
+impl ScoreBoard for HighScores {
+    fn submit(&mut self, score: &Score) {
+        if score.points == 0 {
+            return;
+        }
+
+        self.entries.push(HighScore::from(score));
+        Self::sort_and_truncate(&mut self.entries);
+
+        // A read-only data directory shouldn't end the game
+        let _ = self.save();
+    }
+
+    fn best(&self) -> u32 {
+        self.entries.first().map_or(0, |entry| entry.points)
+    }
+
+    fn entries(&self) -> Vec<HighScore> {
+        self.entries.clone()
+    }
+}
+
+/// A table shared by several games, e.g. the ones of the telnet sessions
+impl ScoreBoard for Rc<RefCell<HighScores>> {
+    fn submit(&mut self, score: &Score) {
+        self.borrow_mut().submit(score);
+    }
+
+    fn best(&self) -> u32 {
+        self.borrow().best()
+    }
+
+    fn entries(&self) -> Vec<HighScore> {
+        self.borrow().entries()
+    }
+}
+
+/// Returns the directory where the game keeps its files
+pub fn data_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("rust-snake")),
+        _ => {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share/rust-snake"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod high_scores_test;
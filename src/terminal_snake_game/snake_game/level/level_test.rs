@@ -0,0 +1,133 @@
+use super::*;
+
+const SMALL: &str = "\
+name: Small
+spawn: 5 2 right
+speed: hard
+apples: 3
+---
+##########
+#........#
+#........#
+#...##...#
+##########
+";
+
+fn parse_error(content: &str) -> (usize, usize) {
+    match Level::parse(content) {
+        Err(LevelError::Parse { line, column, .. }) => (line, column),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn it_can_parse_a_level() {
+    let level = Level::parse(SMALL).unwrap();
+
+    assert_eq!(level.name, "Small");
+    assert_eq!(level.spawn, Directed(Direction::Right, Vec2 { x: 5, y: 2 }));
+    assert_eq!(level.difficulty, Some(Difficulty::Hard));
+    assert_eq!(level.quota, Some(3));
+    assert_eq!(level.get_size(), Vec2 { x: 10, y: 5 });
+    assert!(level.is_empty(&Vec2 { x: 3, y: 3 }));
+    assert!(!level.is_empty(&Vec2 { x: 4, y: 3 }));
+}
+
+#[test]
+fn it_fills_short_rows_with_empty_cells() {
+    let level = Level::parse("name: Open\nspawn: 3 0 right\n---\n\n     #\n").unwrap();
+
+    assert_eq!(level.get_size(), Vec2 { x: 6, y: 2 });
+    assert!(level.is_empty(&Vec2 { x: 5, y: 0 }));
+    assert!(!level.is_empty(&Vec2 { x: 5, y: 1 }));
+}
+
+#[test]
+fn it_can_parse_what_it_has_written() {
+    let level = Level::parse(SMALL).unwrap();
+
+    assert_eq!(Level::parse(&level.to_string()).unwrap(), level);
+}
+
+#[test]
+fn it_reports_the_position_of_unknown_cells() {
+    let content = SMALL.replace("#...##...#", "#...#X...#");
+
+    assert_eq!(parse_error(&content), (9, 6));
+}
+
+#[test]
+fn it_reports_bad_headers() {
+    assert_eq!(parse_error("name: A\nsize: 3\n---\n#\n"), (2, 1));
+    assert_eq!(parse_error("name: A\nspawn: 1 1 north\n---\n#\n"), (2, 1));
+    assert_eq!(parse_error("name: A\nspawn: 5 2 right\n"), (3, 1));
+    assert_eq!(parse_error("name: A\n---\n#\n"), (2, 1));
+}
+
+#[test]
+fn it_refuses_spawns_in_walls() {
+    let content = SMALL.replace("spawn: 5 2 right", "spawn: 5 3 right");
+
+    assert_eq!(parse_error(&content), (2, 1));
+}
+
+#[test]
+fn it_refuses_spawns_facing_a_wall() {
+    let content = SMALL.replace("spawn: 5 2 right", "spawn: 8 2 right");
+
+    assert_eq!(parse_error(&content), (2, 1));
+}
+
+#[test]
+fn it_needs_a_free_cell_for_the_food() {
+    let content = "name: Full\nspawn: 3 0 right\n---\n....\n";
+
+    assert_eq!(parse_error(content), (3, 1));
+}
+
+#[test]
+fn it_has_valid_bundled_levels() {
+    for (name, _) in BUNDLED.iter() {
+        assert!(Level::bundled(name).is_some());
+    }
+    assert_eq!(Level::bundled("nothing"), None);
+}
+
+const HAZARDS: &str = "\
+name: Hazards
+spawn: 5 2 right
+patrol: 2 4 right 5
+---
+##########
+#1.......#
+#........#
+#...++..1#
+#........#
+##########
+";
+
+#[test]
+fn it_can_parse_hazards() {
+    let level = Level::parse(HAZARDS).unwrap();
+
+    assert_eq!(
+        level.patrols,
+        vec![Patrol {
+            start: Directed(Direction::Right, Vec2 { x: 2, y: 4 }),
+            length: 5,
+        }]
+    );
+    assert_eq!(level.get_content()[3][4], MapElement::Gate);
+    assert_eq!(level.get_content()[1][1], MapElement::Portal('1'));
+    assert_eq!(Level::parse(&level.to_string()).unwrap(), level);
+}
+
+#[test]
+fn it_refuses_bad_hazards() {
+    // a portal without a pair
+    assert_eq!(parse_error(&HAZARDS.replace("..1#", "..2#")), (6, 2));
+    // a patrol running into the wall
+    assert_eq!(parse_error(&HAZARDS.replace("right 5", "right 7")), (3, 1));
+    // a patrol over the top edge
+    assert_eq!(parse_error(&HAZARDS.replace("right 5", "up 5")), (3, 1));
+}
@@ -0,0 +1,383 @@
+use super::game::direction::{Directed, Direction};
+use super::game::vec2::Vec2;
+use super::map::{MapElement, Patrol};
+use super::snake::Snake;
+use super::speed::Difficulty;
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Levels shipped with the game, they can be chosen by name
+const BUNDLED: [(&str, &str); 5] = [
+    ("box", include_str!("../../../../levels/box.txt")),
+    ("pillars", include_str!("../../../../levels/pillars.txt")),
+    (
+        "corridors",
+        include_str!("../../../../levels/corridors.txt"),
+    ),
+    ("tunnels", include_str!("../../../../levels/tunnels.txt")),
+    ("hazards", include_str!("../../../../levels/hazards.txt")),
+];
+
+/// Line between the header and the grid
+const SEPARATOR: &str = "---";
+
+/// A map layout with the starting position of the snake
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub name: String,
+    /// Position and direction of the snake's head at the beginning of a round
+    pub spawn: Directed<Vec2>,
+    /// Pace of the level, the player's choice is used if missing
+    pub difficulty: Option<Difficulty>,
+    /// Number of apples to eat to finish the level in the campaign
+    pub quota: Option<u32>,
+    /// Blocks moving on the map
+    pub patrols: Vec<Patrol>,
+    content: Vec<Vec<MapElement>>,
+}
+
+impl Level {
+    /// Returns the bundled level with the given name
+    pub fn bundled(name: &str) -> Option<Level> {
+        BUNDLED
+            .iter()
+            .find(|(bundled, _)| *bundled == name)
+            .map(|(_, content)| Self::parse(content).expect("Invalid bundled level"))
+    }
+
+    pub fn load(path: &Path) -> Result<Level, LevelError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a level file
+    ///
+    /// ```text
+    /// name: Box
+    /// spawn: <x> <y> <up|down|left|right>
+    /// speed: <difficulty>
+    /// apples: <number>
+    /// patrol: <x> <y> <up|down|left|right> <length>
+    /// ---
+    /// #######
+    /// #1.+.1#
+    /// #######
+    /// ```
+    ///
+    /// The speed and the apples to eat in the campaign are optional. In the grid `#` is a wall, `.` or a space is an empty cell,
+    /// short rows are filled with empty cells. Edges without walls lead to the opposite edge.
+    ///
+    /// Hazards are optional too: `+` is a gate that opens and closes on a timer, a digit is a portal
+    /// leading to the other cell with the same digit. Each `patrol` line adds a block that moves
+    /// back and forth between the given cell and the one `length` cells away in the given direction.
+    pub fn parse(content: &str) -> Result<Level, LevelError> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+
+        let mut name = None;
+        let mut spawn = None;
+        let mut difficulty = None;
+        let mut quota = None;
+        let mut patrols = Vec::new();
+        let separator_line = loop {
+            let (number, line) = match lines.next() {
+                Some(line) => line,
+                None => {
+                    let number = content.lines().count() + 1;
+                    return Err(LevelError::parse(number, 1, "Missing separator line"));
+                }
+            };
+            let line = line.trim();
+            if line == SEPARATOR {
+                break number;
+            }
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(LevelError::parse(number, 1, "Expected a 'key: value' line")),
+            };
+            match key {
+                "name" => name = Some(value.to_string()),
+                "spawn" => {
+                    let parsed = Self::parse_spawn(value)
+                        .ok_or_else(|| LevelError::parse(number, 1, "Invalid spawn"))?;
+                    spawn = Some((number, parsed));
+                }
+                "speed" => {
+                    difficulty = Some(
+                        Difficulty::from_name(value)
+                            .ok_or_else(|| LevelError::parse(number, 1, "Unknown speed"))?,
+                    )
+                }
+                "apples" => {
+                    quota =
+                        Some(value.parse().map_err(|_| {
+                            LevelError::parse(number, 1, "Invalid number of apples")
+                        })?)
+                }
+                "patrol" => {
+                    let patrol = Self::parse_patrol(value)
+                        .ok_or_else(|| LevelError::parse(number, 1, "Invalid patrol"))?;
+                    patrols.push((number, patrol));
+                }
+                _ => return Err(LevelError::parse(number, 1, "Unknown key")),
+            }
+        };
+
+        let name = name.ok_or_else(|| LevelError::parse(separator_line, 1, "Missing name"))?;
+        let (spawn_line, spawn) =
+            spawn.ok_or_else(|| LevelError::parse(separator_line, 1, "Missing spawn"))?;
+
+        let mut rows = Vec::new();
+        for (number, line) in lines {
+            let row = line
+                .trim_end()
+                .chars()
+                .enumerate()
+                .map(|(index, cell)| match cell {
+                    '#' => Ok(MapElement::Block),
+                    '+' => Ok(MapElement::Gate),
+                    '1'..='9' => Ok(MapElement::Portal(cell)),
+                    '.' | ' ' => Ok(MapElement::Empty),
+                    _ => Err(LevelError::parse(number, index + 1, "Unknown cell")),
+                })
+                .collect::<Result<Vec<MapElement>, LevelError>>()?;
+            rows.push(row);
+        }
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        if width == 0 {
+            return Err(LevelError::parse(separator_line + 1, 1, "Missing grid"));
+        }
+        for row in &mut rows {
+            row.resize(width, MapElement::Empty);
+        }
+
+        let level = Level {
+            name,
+            spawn,
+            difficulty,
+            quota,
+            patrols: patrols.iter().map(|(_, patrol)| patrol.clone()).collect(),
+            content: rows,
+        };
+        level.validate(spawn_line, separator_line)?;
+        level.validate_hazards(&patrols, separator_line)?;
+        Ok(level)
+    }
+
+    fn parse_spawn(value: &str) -> Option<Directed<Vec2>> {
+        let words: Vec<&str> = value.split_whitespace().collect();
+        match words.as_slice() {
+            [x, y, direction] => Some(Directed(
+                Direction::from_name(direction)?,
+                Vec2 {
+                    x: x.parse().ok()?,
+                    y: y.parse().ok()?,
+                },
+            )),
+            _ => None,
+        }
+    }
+
+    fn parse_patrol(value: &str) -> Option<Patrol> {
+        let words: Vec<&str> = value.split_whitespace().collect();
+        match words.as_slice() {
+            [x, y, direction, length] => Some(Patrol {
+                start: Self::parse_spawn(&format!("{} {} {}", x, y, direction))?,
+                length: length.parse().ok()?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Checks that the portals come in pairs and the patrols move on empty cells
+    /// out of the snake's way at the start
+    fn validate_hazards(
+        &self,
+        patrols: &[(usize, Patrol)],
+        separator_line: usize,
+    ) -> Result<(), LevelError> {
+        for (y, row) in self.content.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let pairs = self.content.iter().flatten().filter(|other| *other == cell);
+                if matches!(cell, MapElement::Portal(_)) && pairs.count() != 2 {
+                    return Err(LevelError::parse(
+                        separator_line + y + 1,
+                        x + 1,
+                        "A portal needs exactly one pair",
+                    ));
+                }
+            }
+        }
+
+        let body = Snake::get_spawn_body(&self.spawn).unwrap_or_default();
+        for (number, patrol) in patrols {
+            let size = self.get_size();
+            let Directed(direction, start) = &patrol.start;
+            // the path must not reach over the top or left edge
+            let is_inside = match direction {
+                Direction::Up => start.y >= patrol.length,
+                Direction::Left => start.x >= patrol.length,
+                _ => true,
+            };
+            let is_valid = is_inside
+                && patrol.get_path().all(|cell| {
+                    cell.x < size.x
+                        && cell.y < size.y
+                        && self.is_empty(&cell)
+                        && body.iter().all(|part| part.1 != cell)
+                });
+            if !is_valid {
+                return Err(LevelError::parse(
+                    *number,
+                    1,
+                    "The patrol has to move on empty cells",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the snake can start and the food has a place
+    fn validate(&self, spawn_line: usize, separator_line: usize) -> Result<(), LevelError> {
+        let body = Snake::get_spawn_body(&self.spawn)
+            .filter(|body| body.iter().all(|part| self.is_empty(&part.1)))
+            .ok_or_else(|| LevelError::parse(spawn_line, 1, "The snake doesn't fit there"))?;
+
+        let snake = Snake::with_spawn(self.get_size(), &self.spawn);
+        if !self.is_empty(&snake.get_next_pos()) {
+            return Err(LevelError::parse(spawn_line, 1, "The snake faces a wall"));
+        }
+
+        let empty_cells = self
+            .content
+            .iter()
+            .flatten()
+            .filter(|cell| **cell == MapElement::Empty)
+            .count();
+        if empty_cells <= body.len() {
+            return Err(LevelError::parse(
+                separator_line,
+                1,
+                "No free cell for the food",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn is_empty(&self, pos: &Vec2) -> bool {
+        matches!(
+            self.content.get(pos.y).and_then(|row| row.get(pos.x)),
+            Some(MapElement::Empty)
+        )
+    }
+
+    pub fn get_size(&self) -> Vec2 {
+        Vec2 {
+            x: self.content.first().map_or(0, |row| row.len()),
+            y: self.content.len(),
+        }
+    }
+
+    pub fn get_content(&self) -> &Vec<Vec<MapElement>> {
+        &self.content
+    }
+}
+
+/// Text form of the level, the same that `Level::parse` reads
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name: {}", self.name)?;
+        let Directed(direction, pos) = &self.spawn;
+        writeln!(f, "spawn: {} {} {}", pos.x, pos.y, direction.get_name())?;
+        if let Some(difficulty) = self.difficulty {
+            writeln!(f, "speed: {}", difficulty.get_name())?;
+        }
+        if let Some(quota) = self.quota {
+            writeln!(f, "apples: {}", quota)?;
+        }
+        for patrol in &self.patrols {
+            let Directed(direction, pos) = &patrol.start;
+            writeln!(
+                f,
+                "patrol: {} {} {} {}",
+                pos.x,
+                pos.y,
+                direction.get_name(),
+                patrol.length
+            )?;
+        }
+        writeln!(f, "{}", SEPARATOR)?;
+        for row in &self.content {
+            let row: String = row
+                .iter()
+                .map(|cell| match cell {
+                    MapElement::Block => '#',
+                    MapElement::Empty => '.',
+                    MapElement::Gate => '+',
+                    MapElement::Portal(label) => *label,
+                })
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// # LevelError
+#[derive(Debug, PartialEq)]
+pub enum LevelError {
+    Io(io::ErrorKind),
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl LevelError {
+    fn parse(line: usize, column: usize, message: &str) -> LevelError {
+        LevelError::Parse {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err.kind())
+    }
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(kind) => write!(f, "Couldn't read the level: {}", kind),
+            Self::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "Line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+#[cfg(test)]
+mod level_test;
@@ -1,6 +1,7 @@
 use super::*;
 
 #[test]
+#[allow(clippy::get_first)]
 fn it_can_make_a_new_map() {
     let map = Map::new(20, 20);
     assert_eq!(map.content.len(), 20);
@@ -8,6 +9,7 @@ fn it_can_make_a_new_map() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn it_can_check_collision() {
     let map = Map::new(20, 20);
     assert_eq!(map.is_free_pos(&Vec2 { x: 10, y: 0 }), false);
@@ -48,3 +50,112 @@ fn it_can_draw_itself() {
         map.draw()
     )
 }
+
+#[test]
+fn it_can_make_a_map_without_walls() {
+    let map = Map::new_wrapping(20, 10);
+
+    assert_eq!(map.get_size(), Vec2 { x: 20, y: 10 });
+    assert!(map.is_free_pos(&Vec2 { x: 0, y: 0 }));
+    assert!(map.is_free_pos(&Vec2 { x: 19, y: 9 }));
+}
+
+#[test]
+fn it_wraps_positions_beyond_the_edges() {
+    let mut map = Map::new_wrapping(20, 10);
+    map.content[2][3] = MapElement::Block;
+
+    assert!(!map.is_free_pos(&Vec2 { x: 23, y: 2 }));
+    assert!(!map.is_free_pos(&Vec2 { x: 3, y: 12 }));
+    assert!(map.is_free_pos(&Vec2 { x: 24, y: 12 }));
+}
+
+#[test]
+fn it_wraps_only_with_an_open_edge() {
+    let mut walled = Map::new(20, 10);
+    walled.wrapping = true;
+    let mut open = Map::new(20, 10);
+    open.wrapping = true;
+    open.content[0][5] = MapElement::Empty;
+
+    assert!(!Map::new(20, 10).is_wrapping());
+    assert!(!walled.is_wrapping());
+    assert!(open.is_wrapping());
+    assert!(Map::new_wrapping(20, 10).is_wrapping());
+}
+
+fn new_hazard_map() -> Map {
+    let mut map = Map::new(10, 6);
+    map.content[2][4] = MapElement::Gate;
+    map.content[1][1] = MapElement::Portal('1');
+    map.content[4][8] = MapElement::Portal('1');
+    map.patrols = vec![Patrol {
+        start: Directed(Direction::Down, Vec2 { x: 6, y: 1 }),
+        length: 2,
+    }];
+    map
+}
+
+#[test]
+fn it_opens_and_closes_gates() {
+    let mut map = new_hazard_map();
+    let gate = Vec2 { x: 4, y: 2 };
+    assert!(!map.is_free_pos(&gate));
+
+    for _ in 0..GATE_TICKS {
+        map.tick();
+    }
+    assert!(map.is_free_pos(&gate));
+
+    map.reset();
+    assert!(!map.is_free_pos(&gate));
+}
+
+#[test]
+fn it_moves_patrols_back_and_forth() {
+    let mut map = new_hazard_map();
+    let mut positions = Vec::new();
+
+    for _ in 0..5 {
+        positions.push(map.patrols[0].get_pos(map.ticks));
+        map.tick();
+    }
+
+    assert_eq!(
+        positions.iter().map(|pos| pos.y).collect::<Vec<usize>>(),
+        vec![1, 2, 3, 2, 1]
+    );
+    assert!(!map.is_free_pos(&Vec2 { x: 6, y: 2 }));
+    assert!(map.is_free_pos(&Vec2 { x: 6, y: 1 }));
+}
+
+#[test]
+fn it_pairs_portals() {
+    let map = new_hazard_map();
+
+    assert_eq!(
+        map.get_portal_exit(&Vec2 { x: 1, y: 1 }),
+        Some(Vec2 { x: 8, y: 4 })
+    );
+    assert_eq!(
+        map.get_portal_exit(&Vec2 { x: 8, y: 4 }),
+        Some(Vec2 { x: 1, y: 1 })
+    );
+    assert_eq!(map.get_portal_exit(&Vec2 { x: 2, y: 2 }), None);
+    assert!(map.is_free_pos(&Vec2 { x: 1, y: 1 }));
+}
+
+#[test]
+fn it_restores_the_hazards() {
+    let mut map = new_hazard_map();
+    for _ in 0..GATE_TICKS + 1 {
+        map.tick();
+    }
+
+    let mut restored = new_hazard_map();
+    restored.load_state(&map.save_state()).unwrap();
+
+    assert!(restored.is_free_pos(&Vec2 { x: 4, y: 2 }));
+    assert_eq!(restored.draw(), map.draw());
+    assert_eq!(restored.load_state("soon"), None);
+}
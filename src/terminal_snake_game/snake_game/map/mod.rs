@@ -1,18 +1,82 @@
 use super::game::{direction::{Directed, Direction}, vec2::Vec2};
 use super::game::{
     draw_instruction::{DrawInstruction, Symbol},
-    traits::{Draw, IsFreePos, Map as MapTrait},
+    traits::{Draw, IsFreePos, Map as MapTrait, Reset, Snapshot},
 };
+use super::level::Level;
 use std::mem;
 
-#[derive(Clone)]
-enum MapElement {
+#[derive(Clone, Debug, PartialEq)]
+pub enum MapElement {
     Empty,
     Block,
+    /// Wall that opens and closes every `GATE_TICKS` ticks, it is closed first
+    Gate,
+    /// Takes the head to the other portal with the same label
+    Portal(char),
+}
+
+/// Number of ticks a gate stays closed or open
+pub const GATE_TICKS: u64 = 20;
+
+/// A block that moves back and forth along a straight path, one cell every tick
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patrol {
+    /// The first cell of the path and the direction the block starts to move to
+    pub start: Directed<Vec2>,
+    /// Number of steps from one end of the path to the other
+    pub length: usize,
+}
+
+impl Patrol {
+    /// Returns the cells of the path from the start
+    pub fn get_path(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (0..=self.length).map(move |distance| self.get_cell(distance))
+    }
+
+    /// Returns the position of the block after the given number of ticks
+    pub fn get_pos(&self, ticks: u64) -> Vec2 {
+        let period = 2 * self.length as u64;
+        let distance = match period {
+            0 => 0,
+            _ => {
+                let phase = ticks % period;
+                phase.min(period - phase)
+            }
+        };
+        self.get_cell(distance as usize)
+    }
+
+    fn get_cell(&self, distance: usize) -> Vec2 {
+        let Directed(direction, pos) = &self.start;
+        match direction {
+            Direction::Up => Vec2 {
+                x: pos.x,
+                y: pos.y - distance,
+            },
+            Direction::Left => Vec2 {
+                x: pos.x - distance,
+                y: pos.y,
+            },
+            Direction::Right => Vec2 {
+                x: pos.x + distance,
+                y: pos.y,
+            },
+            Direction::Down => Vec2 {
+                x: pos.x,
+                y: pos.y + distance,
+            },
+        }
+    }
 }
 
 pub struct Map {
     content: Vec<Vec<MapElement>>,
+    /// Whether the positions beyond an edge continue on the opposite one
+    wrapping: bool,
+    patrols: Vec<Patrol>,
+    /// Number of ticks since the round has started, the hazards move according to it
+    ticks: u64,
 }
 
 impl Map {
@@ -26,23 +90,72 @@ impl Map {
         let _ = mem::replace(content.first_mut().unwrap(), vec![MapElement::Block; width]);
         let _ = mem::replace(content.last_mut().unwrap(), vec![MapElement::Block; width]);
 
-        Map { content }
+        Map {
+            content,
+            wrapping: false,
+            patrols: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    /// Creates the map of a level, edges without walls lead to the opposite edge
+    pub fn from_level(level: &Level) -> Map {
+        Map {
+            content: level.get_content().clone(),
+            wrapping: true,
+            patrols: level.patrols.clone(),
+            ticks: 0,
+        }
+    }
+
+    /// Creates a map without border walls where the edges are connected
+    pub fn new_wrapping(width: usize, height: usize) -> Map {
+        Map {
+            content: vec![vec![MapElement::Empty; width]; height],
+            wrapping: true,
+            patrols: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    fn is_gate_closed(&self) -> bool {
+        (self.ticks / GATE_TICKS).is_multiple_of(2)
     }
 }
 
 impl IsFreePos for Map {
     fn is_free_pos(&self, pos: &Vec2) -> bool {
-        match self.content.get(pos.y).and_then(|row| row.get(pos.x)) {
+        let pos = match self.wrapping {
+            true => {
+                let size = self.get_size();
+                Vec2 {
+                    x: pos.x % size.x,
+                    y: pos.y % size.y,
+                }
+            }
+            false => pos.clone(),
+        };
+
+        let is_free = match self.content.get(pos.y).and_then(|row| row.get(pos.x)) {
             Some(MapElement::Block) => false,
-            Some(MapElement::Empty) => true,
+            Some(MapElement::Gate) => !self.is_gate_closed(),
+            Some(MapElement::Empty) | Some(MapElement::Portal(_)) => true,
             None => panic!("Cell not found on {:?}", &pos),
-        }
+        };
+        is_free
+            && self
+                .patrols
+                .iter()
+                .all(|patrol| patrol.get_pos(self.ticks) != pos)
     }
 }
 
 impl Draw for Map {
     fn draw(&self) -> Vec<DrawInstruction> {
-        let instructions = self
+        let gate = Symbol::Gate {
+            open: !self.is_gate_closed(),
+        };
+        let mut instructions: Vec<DrawInstruction> = self
             .content
             .iter()
             .enumerate()
@@ -53,17 +166,25 @@ impl Draw for Map {
                         .map(|el| match el {
                             MapElement::Empty => Directed(Direction::Up, Symbol::Empty),
                             MapElement::Block => Directed(Direction::Up, Symbol::Wall),
+                            MapElement::Gate => Directed(Direction::Up, gate.clone()),
+                            MapElement::Portal(label) => Directed(Direction::Up, Symbol::Portal(*label)),
                         })
                         .collect(),
                 }
             })
             .collect();
 
+        for patrol in &self.patrols {
+            let pos = patrol.get_pos(self.ticks);
+            instructions[pos.y].shape[pos.x] = Directed(Direction::Up, Symbol::Patrol);
+        }
+
         instructions
     }
 }
 
 impl MapTrait for Map {
+    #[allow(clippy::get_first)]
     fn get_size(&self) -> Vec2 {
         Vec2 {
             x: self.content.get(0).expect("Empty map").len(),
@@ -71,6 +192,42 @@ impl MapTrait for Map {
         }
     }
 
+    fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    fn get_portal_exit(&self, pos: &Vec2) -> Option<Vec2> {
+        let label = match self.content.get(pos.y).and_then(|row| row.get(pos.x)) {
+            Some(MapElement::Portal(label)) => label,
+            _ => return None,
+        };
+        self.content.iter().enumerate().find_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .find(|(x, cell)| **cell == MapElement::Portal(*label) && (*x, y) != (pos.x, pos.y))
+                .map(|(x, _)| Vec2 { x, y })
+        })
+    }
+
+    fn is_wrapping(&self) -> bool {
+        // a level walled all around can't be left over its edges
+        let size = self.get_size();
+        self.wrapping
+            && self.content.iter().enumerate().any(|(y, row)| {
+                row.iter().enumerate().any(|(x, cell)| {
+                    let on_edge = x == 0 || y == 0 || x + 1 == size.x || y + 1 == size.y;
+                    on_edge && *cell != MapElement::Block
+                })
+            })
+    }
+
+    fn get_patrols(&self) -> Vec<Vec2> {
+        self.patrols
+            .iter()
+            .map(|patrol| patrol.get_pos(self.ticks))
+            .collect()
+    }
+
     fn as_draw(&self) -> &dyn Draw {
         self
     }
@@ -80,5 +237,24 @@ impl MapTrait for Map {
     }
 }
 
+/// The number of ticks the hazards have moved, the layout comes from the settings
+impl Snapshot for Map {
+    fn save_state(&self) -> String {
+        self.ticks.to_string()
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        self.ticks = state.trim().parse().ok()?;
+        Some(())
+    }
+}
+
+impl Reset for Map {
+    /// Puts the hazards back to their starting state
+    fn reset(&mut self) {
+        self.ticks = 0;
+    }
+}
+
 #[cfg(test)]
 mod map_test;
@@ -1,47 +1,722 @@
+pub mod ai;
 mod apple;
+pub mod campaign;
+pub mod config;
 pub mod game;
+pub mod high_scores;
+pub mod level;
 mod map;
+mod overlay;
 mod snake;
+pub mod speed;
+pub mod state;
 mod traits;
 use super::traits::SnakeGame as SnakeGameTrait;
 use super::update_reason::{Control, UpdateReason};
+use ai::{Controller, Strategy};
+use campaign::{Campaign, Progress};
+use config::Config;
 use game::draw_instruction::DrawInstruction;
-use game::traits::Draw;
+use game::errors::GameError;
+use game::score::{HighScore, Score};
+use game::traits::{Character, Draw, Food, ScoreBoard, Snapshot};
+use game::vec2::Vec2;
+use high_scores::HighScores;
+use level::Level;
+use rand::SeedableRng;
+use speed::{Difficulty, Speed};
+use state::State;
+use std::time::Duration;
 use traits::*;
 
+/// Number of rows reserved below the map for the HUD
+pub const HUD_HEIGHT: usize = 1;
+
+/// Number of won rounds needed to win a versus match
+pub const MATCH_WINS: u32 = 3;
+
 pub struct SnakeGame {
     game: Box<dyn Game>,
+    state: State,
+    quit: bool,
+    difficulty: Difficulty,
+    accelerate: bool,
+    speed: Speed,
+    start_length: usize,
+    seed: u64,
+    level_name: Option<String>,
+    campaign: Option<Campaign>,
+    /// Rounds won by each player in the current match
+    wins: Vec<u32>,
+    /// Players that are played by the computer
+    controllers: Vec<(usize, Box<dyn Controller>)>,
+    /// Game played by the computer on the title screen
+    demo: Option<Box<SnakeGame>>,
+    /// Whether the players play over the network
+    online: bool,
 }
 
 impl SnakeGame {
-    pub fn new(size: (usize, usize)) -> SnakeGame {
-        let mut game = Box::new(game::Game::new(
-            Box::new(map::Map::new(size.0, size.1)),
-            Box::new(snake::Snake::new()),
-            Box::new(apple::Apple::new()),
-        ));
+    /// Creates a game from validated settings
+    pub fn new(config: &Config) -> SnakeGame {
+        let high_scores = match HighScores::default_path() {
+            Some(path) => HighScores::load(&path),
+            None => HighScores::new(),
+        };
+
+        Self::with_score_board(config, Box::new(high_scores), Progress::load_default())
+    }
+
+    /// Creates a game whose results don't get into the high-score table, e.g. for replays
+    ///
+    /// The campaign progress isn't saved either.
+    pub fn new_unranked(config: &Config) -> SnakeGame {
+        Self::with_score_board(config, Box::new(HighScores::new()), Progress::new())
+    }
+
+    /// Creates a game that records its results into the given table, e.g. one shared with other
+    /// games; the campaign progress isn't saved
+    pub fn new_sharing_scores(config: &Config, score_board: Box<dyn ScoreBoard>) -> SnakeGame {
+        Self::with_score_board(config, score_board, Progress::new())
+    }
+
+    fn with_score_board(
+        config: &Config,
+        score_board: Box<dyn ScoreBoard>,
+        progress: Progress,
+    ) -> SnakeGame {
+        let boundary = Vec2 {
+            x: config.width,
+            y: config.height,
+        };
+        let (map, characters) = match &config.level {
+            Some(level) => Self::new_level_board(level),
+            None => {
+                let map = match config.wrap {
+                    true => map::Map::new_wrapping(config.width, config.height),
+                    false => map::Map::new(config.width, config.height),
+                };
+                let characters: Vec<Box<dyn Character>> = match config.versus {
+                    true => snake::Snake::get_versus_spawns(&boundary)
+                        .iter()
+                        .enumerate()
+                        .map(|(player, spawn)| {
+                            let snake = snake::Snake::with_spawn(boundary.clone(), spawn);
+                            Box::new(snake.with_player(player)) as Box<dyn Character>
+                        })
+                        .collect(),
+                    false => vec![Box::new(snake::Snake::new(boundary))],
+                };
+                (map, characters)
+            }
+        };
+        let players = characters.len();
+        let mut snake_game = Self::from_game(Box::new(game::Game::new(
+            Box::new(map),
+            characters,
+            (0..config.food_count)
+                .map(|_| Box::new(apple::Apple::with_weights(config.food.clone())) as Box<dyn Food>)
+                .collect(),
+            score_board,
+            game::GameRng::seed_from_u64(config.seed),
+        )));
+        snake_game.configure_speed(config.difficulty, config.accelerate);
+        snake_game.seed = config.seed;
+        snake_game.level_name = config.level.as_ref().map(|level| level.name.clone());
+        snake_game.campaign = config.campaign.map(|start| Campaign::new(start, progress));
+        snake_game.wins = vec![0; players];
+        if config.versus {
+            snake_game.controllers = config
+                .opponent
+                .iter()
+                .map(|strategy| (1, strategy.new_controller()))
+                .collect();
+        }
+        snake_game.demo = config
+            .demo
+            .map(|strategy| Box::new(Self::new_computer_game(config, strategy)));
+        snake_game.online = config.online;
+        snake_game
+    }
+
+    /// Creates a running game where the computer plays every snake, its results aren't kept
+    ///
+    /// A campaign isn't continued, only its first level is played.
+    pub fn new_computer_game(config: &Config, strategy: Strategy) -> SnakeGame {
+        let config = Config {
+            campaign: None,
+            opponent: None,
+            demo: None,
+            ..config.clone()
+        };
+        let mut snake_game = Self::new_unranked(&config);
+        snake_game.controllers = (0..snake_game.wins.len())
+            .map(|player| (player, strategy.new_controller()))
+            .collect();
+        snake_game.state = State::Playing;
+        snake_game
+    }
+
+    /// Lets the given controller steer the player instead of the keyboard, e.g. a bot
+    pub fn with_controller(mut self, player: usize, controller: Box<dyn Controller>) -> SnakeGame {
+        self.controllers.retain(|(other, _)| *other != player);
+        self.controllers.push((player, controller));
+        self
+    }
+
+    fn new_level_board(level: &Level) -> (map::Map, Vec<Box<dyn Character>>) {
+        let snake = snake::Snake::with_spawn(level.get_size(), &level.spawn);
+        (map::Map::from_level(level), vec![Box::new(snake)])
+    }
+
+    fn from_game(game: Box<dyn Game>) -> SnakeGame {
+        let start_length = game.get_score().length;
+        SnakeGame {
+            game,
+            state: State::Title,
+            quit: false,
+            difficulty: Difficulty::Normal,
+            accelerate: false,
+            speed: Speed::default(),
+            start_length,
+            seed: 0,
+            level_name: None,
+            campaign: None,
+            wins: vec![0],
+            controllers: Vec::new(),
+            demo: None,
+            online: false,
+        }
+    }
+
+    pub fn get_state(&self) -> &State {
+        &self.state
+    }
+
+    /// Returns the number of snakes on the board, each one is steered by a player
+    pub fn get_player_count(&self) -> usize {
+        self.wins.len()
+    }
+
+    fn is_versus(&self) -> bool {
+        self.wins.len() > 1
+    }
+
+    /// Returns whether two people share the keyboard
+    fn is_local_versus(&self) -> bool {
+        self.is_versus() && self.controllers.is_empty() && !self.online
+    }
+
+    /// Returns whether the computer steers the player
+    fn is_steered(&self, player: usize) -> bool {
+        self.controllers.iter().any(|(other, _)| *other == player)
+    }
+
+    /// Returns the only player who has survived the round
+    fn get_round_winner(&self, error: &GameError) -> Option<usize> {
+        let losers = match error {
+            GameError::PlayersLost(losers) => losers,
+            _ => return None,
+        };
+        let mut survivors =
+            (0..self.wins.len()).filter(|player| losers.iter().all(|(loser, _)| loser != player));
+        match (survivors.next(), survivors.next()) {
+            (Some(winner), None) => Some(winner),
+            _ => None,
+        }
+    }
+
+    fn get_match_winner(&self) -> Option<usize> {
+        self.wins.iter().position(|wins| *wins >= MATCH_WINS)
+    }
+
+    /// The pace can be changed only between rounds
+    fn configure_speed(&mut self, difficulty: Difficulty, accelerate: bool) {
+        if let State::Title | State::GameOver(_) | State::Completed = self.state {
+            self.difficulty = difficulty;
+            self.accelerate = accelerate;
+            self.speed = Speed::new(difficulty, accelerate);
+        }
+    }
+
+    fn start(&mut self) {
+        match self.state {
+            State::Title | State::LevelIntro => self.state = State::Playing,
+            State::GameOver(_) => {
+                // a campaign goes on from the level it was lost on, but the score starts over
+                self.game.reset();
+                if let Some(campaign) = &mut self.campaign {
+                    campaign.restart_level();
+                }
+                if self.get_match_winner().is_some() {
+                    self.wins.iter_mut().for_each(|wins| *wins = 0);
+                }
+                self.state = State::Playing;
+            }
+            State::Completed => {
+                if let Some(campaign) = &mut self.campaign {
+                    let level = campaign.restart().clone();
+                    self.enter_level(&level);
+                }
+                self.game.reset();
+                self.state = State::Playing;
+            }
+            State::Playing | State::Paused => (),
+        }
+    }
+
+    fn pause(&mut self) {
+        if let State::Playing = self.state {
+            self.state = State::Paused;
+        }
+    }
+
+    fn resume(&mut self) {
+        if let State::Paused = self.state {
+            self.state = State::Playing;
+        }
+    }
+
+    fn tick(&mut self) {
+        if let (State::Title, Some(demo)) = (&self.state, &mut self.demo) {
+            demo.tick();
+            // the demo never ends
+            if let State::GameOver(_) = demo.state {
+                demo.start();
+            }
+        }
+        if let State::Playing = self.state {
+            self.steer();
+            match self.game.tick() {
+                Ok(()) => self.check_quota(),
+                Err(error) => {
+                    if let Some(winner) = self.get_round_winner(&error) {
+                        self.wins[winner] += 1;
+                    }
+                    self.state = State::GameOver(error);
+                }
+            }
+        }
+    }
 
-        game.tick();
+    /// Lets the computer players choose their next step
+    fn steer(&mut self) {
+        for (player, controller) in &mut self.controllers {
+            let direction = controller.decide(&self.game.get_board_view(*player));
+            self.game.turn_character(*player, direction);
+        }
+    }
+
+    /// Moves on to the next level of the campaign once the current one is done
+    fn check_quota(&mut self) {
+        let apples = self.game.get_score().apples;
+        let campaign = match &mut self.campaign {
+            Some(campaign) if campaign.is_level_done(apples) => campaign,
+            _ => return,
+        };
+
+        match campaign.advance(apples).cloned() {
+            Some(level) => {
+                self.enter_level(&level);
+                self.state = State::LevelIntro;
+            }
+            None => {
+                self.game.submit_score();
+                self.state = State::Completed;
+            }
+        }
+    }
+
+    /// Continues the round on the given level
+    fn enter_level(&mut self, level: &Level) {
+        let (map, characters) = Self::new_level_board(level);
+        self.game.change_map(Box::new(map), characters);
+        self.level_name = Some(level.name.clone());
+        if let Some(difficulty) = level.difficulty {
+            self.difficulty = difficulty;
+            self.speed = Speed::new(difficulty, self.accelerate);
+        }
+    }
+
+    /// Goes back to the level and the state the game has had before entering another level
+    fn leave_level(&mut self, before: (usize, u32, String, Difficulty, Speed)) {
+        let (number, apples_before, state, difficulty, speed) = before;
+        if let Some(campaign) = &mut self.campaign {
+            let level = campaign
+                .resume(number, apples_before)
+                .expect("The level was in the campaign")
+                .clone();
+            self.enter_level(&level);
+        }
+        self.game
+            .load_state(&state)
+            .expect("The state of the game was valid");
+        self.difficulty = difficulty;
+        self.speed = speed;
+    }
+
+    fn get_overlay_lines(&self) -> Vec<String> {
+        match &self.state {
+            State::Title => vec!["RUST SNAKE".to_string()]
+                .into_iter()
+                .chain(self.get_level_lines())
+                .chain(self.get_key_lines())
+                .chain(self.get_speed_lines())
+                .collect(),
+            State::Playing => vec![],
+            State::Paused => vec![
+                "PAUSED".to_string(),
+                String::new(),
+                "P: resume  Q: quit".to_string(),
+            ],
+            State::GameOver(error) if self.is_versus() => self
+                .get_round_lines(error)
+                .into_iter()
+                .chain(self.get_problem_lines())
+                .chain(self.get_speed_lines())
+                .collect(),
+            State::GameOver(error) => {
+                let score = self.game.get_score();
+                vec![
+                    match error {
+                        GameError::BoardFilled => "YOU WIN".to_string(),
+                        _ => "GAME OVER".to_string(),
+                    },
+                    String::new(),
+                    error.to_string(),
+                    format!("Score: {}  Best: {}", score.points, score.best),
+                    format!("Seed: {}", self.seed),
+                    String::new(),
+                    "Enter: play again  Q: quit".to_string(),
+                    String::new(),
+                ]
+                .into_iter()
+                .chain(self.get_problem_lines())
+                .chain(self.get_speed_lines())
+                .collect()
+            }
+            State::LevelIntro => vec!["LEVEL COMPLETE".to_string(), String::new()]
+                .into_iter()
+                .chain(self.get_level_lines())
+                .chain(vec![String::new(), "Enter: start  Q: quit".to_string()])
+                .collect(),
+            State::Completed => {
+                let score = self.game.get_score();
+                vec![
+                    "CAMPAIGN COMPLETE".to_string(),
+                    String::new(),
+                    format!("Score: {}  Best: {}", score.points, score.best),
+                    format!("Seed: {}", self.seed),
+                    String::new(),
+                    "Enter: play again  Q: quit".to_string(),
+                    String::new(),
+                ]
+                .into_iter()
+                .chain(self.get_speed_lines())
+                .collect()
+            }
+        }
+    }
+
+    fn get_key_lines(&self) -> Vec<String> {
+        match self.is_versus() {
+            true if self.online => vec![
+                format!("First to {} wins", MATCH_WINS),
+                String::new(),
+                "Arrows: your snake".to_string(),
+                "Enter: start  P: pause  Q: leave".to_string(),
+                String::new(),
+            ],
+            true if !self.is_local_versus() => vec![
+                format!("First to {} wins", MATCH_WINS),
+                String::new(),
+                match self.is_steered(0) {
+                    true => "Bot: player 1  Computer: player 2".to_string(),
+                    false => "Arrows: player 1  Computer: player 2".to_string(),
+                },
+                "Enter: start  P: pause  Q: quit".to_string(),
+                String::new(),
+            ],
+            true => vec![
+                format!("First to {} wins", MATCH_WINS),
+                String::new(),
+                "Arrows: player 1  WASD: player 2".to_string(),
+                "Enter: start  P: pause  Q: quit".to_string(),
+                String::new(),
+            ],
+            false if self.is_steered(0) => vec![
+                String::new(),
+                "The bot moves  P: pause".to_string(),
+                "Enter: start  Q: quit".to_string(),
+                String::new(),
+            ],
+            false => vec![
+                String::new(),
+                "Arrows: move  P: pause".to_string(),
+                "Enter: start  Q: quit".to_string(),
+                String::new(),
+            ],
+        }
+    }
+
+    fn get_round_lines(&self, error: &GameError) -> Vec<String> {
+        let match_winner = self.get_match_winner();
+        let mut lines = vec![
+            match match_winner {
+                Some(_) => "MATCH OVER".to_string(),
+                None => "ROUND OVER".to_string(),
+            },
+            String::new(),
+        ];
+        if let GameError::PlayersLost(losers) = error {
+            lines.extend(
+                losers
+                    .iter()
+                    .map(|(player, reason)| format!("Player {}: {}", player + 1, reason)),
+            );
+        }
+        lines.push(match (match_winner, self.get_round_winner(error)) {
+            (Some(winner), _) => format!("Player {} wins the match", winner + 1),
+            (None, Some(winner)) => format!("Player {} wins the round", winner + 1),
+            (None, None) => "Draw".to_string(),
+        });
+
+        let wins: Vec<String> = self.wins.iter().map(|wins| wins.to_string()).collect();
+        lines.push(format!("Wins: {}", wins.join(" - ")));
+        lines.push(String::new());
+        lines.push(match match_winner {
+            Some(_) => "Enter: new match  Q: quit".to_string(),
+            None => "Enter: next round  Q: quit".to_string(),
+        });
+        lines.push(String::new());
+        lines
+    }
+
+    fn get_level_lines(&self) -> Vec<String> {
+        match &self.campaign {
+            Some(campaign) => vec![
+                format!(
+                    "Level {}/{}: {}",
+                    campaign.get_level_number(),
+                    campaign.get_level_count(),
+                    campaign.get_level().name
+                ),
+                format!("Eat {} apples", campaign.get_quota()),
+            ],
+            None => self
+                .level_name
+                .iter()
+                .map(|name| format!("Level: {}", name))
+                .collect(),
+        }
+    }
+
+    /// What has last gone wrong with the computer players, e.g. a bot that has crashed
+    fn get_problem_lines(&self) -> Vec<String> {
+        let problems: Vec<String> = self
+            .controllers
+            .iter()
+            .filter_map(|(player, controller)| {
+                let problem = controller.get_problem()?;
+                Some(format!("Player {}: {}", player + 1, problem))
+            })
+            .collect();
+        match problems.is_empty() {
+            true => problems,
+            false => problems.into_iter().chain(vec![String::new()]).collect(),
+        }
+    }
 
-        SnakeGame { game }
+    fn get_speed_lines(&self) -> Vec<String> {
+        vec![
+            format!("Difficulty (1-4): {}", self.difficulty.get_name()),
+            format!(
+                "Acceleration{}: {}",
+                // the A key belongs to the second player in versus mode
+                if self.is_local_versus() { "" } else { " (A)" },
+                if self.accelerate { "on" } else { "off" }
+            ),
+        ]
     }
 }
 
 impl Draw for SnakeGame {
     fn draw(&self) -> Vec<DrawInstruction> {
-        self.game.as_draw().draw()
+        let game = match (&self.state, &self.demo) {
+            (State::Title, Some(demo)) => &demo.game,
+            _ => &self.game,
+        };
+        let mut paint = game.as_draw().draw();
+        let lines = self.get_overlay_lines();
+        if !lines.is_empty() {
+            paint.extend(overlay::overlay(&self.game.get_size(), &lines));
+        }
+        paint
+    }
+}
+
+/// The pace, the wins, the campaign and the game, a line for each
+///
+/// ```text
+/// speed <difficulty> <on|off>
+/// wins <wins of every player>
+/// campaign <number of the current level> <apples eaten on the earlier levels>
+/// <the lines of the game, see Game>
+/// ```
+///
+/// The campaign line is present only in campaigns. A restored game is paused, so the
+/// player has time to find the keys again.
+impl Snapshot for SnakeGame {
+    fn save_state(&self) -> String {
+        let wins: Vec<String> = self.wins.iter().map(|wins| wins.to_string()).collect();
+        let mut lines = vec![
+            format!(
+                "speed {} {}",
+                self.difficulty.get_name(),
+                if self.accelerate { "on" } else { "off" }
+            ),
+            format!("wins {}", wins.join(" ")),
+        ];
+        if let Some(campaign) = &self.campaign {
+            lines.push(format!(
+                "campaign {} {}",
+                campaign.get_level_number(),
+                campaign.get_apples_before()
+            ));
+        }
+        lines.push(self.game.save_state());
+        lines.join("\n")
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        let mut speed = None;
+        let mut wins = None;
+        let mut position = None;
+        let mut game = Vec::new();
+        for line in state.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["speed", difficulty, accelerate] => {
+                    let accelerate = match *accelerate {
+                        "on" => true,
+                        "off" => false,
+                        _ => return None,
+                    };
+                    speed = Some((Difficulty::from_name(difficulty)?, accelerate));
+                }
+                ["wins", ..] => {
+                    wins = Some(
+                        words[1..]
+                            .iter()
+                            .map(|wins| wins.parse().ok())
+                            .collect::<Option<Vec<u32>>>()?,
+                    )
+                }
+                ["campaign", number, apples_before] => {
+                    position = Some((number.parse().ok()?, apples_before.parse().ok()?));
+                }
+                _ => game.push(line),
+            }
+        }
+        let (difficulty, accelerate) = speed?;
+        let wins = wins.filter(|wins| wins.len() == self.wins.len())?;
+        let game = game.join("\n");
+        match (&mut self.campaign, position) {
+            (None, None) => self.game.load_state(&game)?,
+            (Some(campaign), Some((number, apples_before))) => {
+                if number == 0 || number > campaign.get_level_count() {
+                    return None;
+                }
+                let before = (
+                    campaign.get_level_number(),
+                    campaign.get_apples_before(),
+                    self.game.save_state(),
+                    self.difficulty,
+                    self.speed.clone(),
+                );
+                // the characters of the level are put on the board before they are restored
+                let level = campaign.resume(number, apples_before)?.clone();
+                self.enter_level(&level);
+                if self.game.load_state(&game).is_none() {
+                    self.leave_level(before);
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+        self.difficulty = difficulty;
+        self.accelerate = accelerate;
+        self.speed = Speed::new(difficulty, accelerate);
+        self.wins = wins;
+        self.state = State::Paused;
+        Some(())
     }
 }
 
 impl SnakeGameTrait for SnakeGame {
     fn update(&mut self, reason: UpdateReason) {
         match reason {
-            UpdateReason::Control(Control::Turn(direction)) => self.game.turn_character(direction),
-            UpdateReason::Time => self.game.tick(),
+            UpdateReason::Control(Control::Turn(player, direction)) => {
+                if let (State::Playing, false) = (&self.state, self.is_steered(player)) {
+                    self.game.turn_character(player, direction)
+                }
+            }
+            UpdateReason::Control(Control::Start) => self.start(),
+            UpdateReason::Control(Control::Pause) => self.pause(),
+            UpdateReason::Control(Control::Resume) => self.resume(),
+            UpdateReason::Control(Control::SetDifficulty(difficulty)) => {
+                self.configure_speed(difficulty, self.accelerate)
+            }
+            UpdateReason::Control(Control::ToggleAcceleration) => {
+                self.configure_speed(self.difficulty, !self.accelerate)
+            }
+            UpdateReason::Control(Control::Quit) => self.quit = true,
+            UpdateReason::Time => self.tick(),
         }
     }
 
+    fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn get_tick_interval(&self) -> Duration {
+        let length = self
+            .game
+            .get_scores()
+            .iter()
+            .map(|score| score.length)
+            .max()
+            .unwrap_or(0);
+        let growth = length.saturating_sub(self.start_length);
+        self.speed
+            .get_interval(growth)
+            .mul_f64(self.game.get_pace().get_factor())
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self.state, State::Playing | State::Paused)
+    }
+
+    fn is_paused(&self) -> bool {
+        matches!(self.state, State::Paused)
+    }
+
+    fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    fn get_score(&self) -> &Score {
+        self.game.get_score()
+    }
+
+    fn get_high_scores(&self) -> Vec<HighScore> {
+        self.game.get_high_scores()
+    }
+
     fn as_draw(&self) -> &dyn Draw {
         self
     }
 }
+
+#[cfg(test)]
+mod snake_game_test;
@@ -0,0 +1,49 @@
+use super::game::{draw_instruction::DrawInstruction, vec2::Vec2};
+
+/// Empty cells around the text of the overlay
+const PADDING: usize = 2;
+
+/// Returns instructions that draw the given lines in a box in the middle of the given area
+///
+/// Lines are centered inside the box, the box is cut if it does not fit into the area.
+pub fn overlay(area: &Vec2, lines: &[String]) -> Vec<DrawInstruction> {
+    let text_width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = (text_width + 2 * PADDING).min(area.x);
+    let height = (lines.len() + 2).min(area.y);
+    let left = (area.x - width) / 2;
+    let top = (area.y - height) / 2;
+
+    let blank = String::new();
+    (0..height)
+        .map(|row| {
+            // the first and the last rows are empty to separate the box from the board
+            let line = match row {
+                0 => &blank,
+                _ => lines.get(row - 1).unwrap_or(&blank),
+            };
+            let line: String = line.chars().take(width).collect();
+            let indent = (width - line.chars().count()) / 2;
+            let text = format!(
+                "{:indent$}{:width$}",
+                "",
+                line,
+                indent = indent,
+                width = width - indent
+            );
+            DrawInstruction::text(
+                Vec2 {
+                    x: left,
+                    y: top + row,
+                },
+                &text,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod overlay_test;
@@ -0,0 +1,40 @@
+use super::super::game::draw_instruction::Symbol;
+use super::*;
+
+fn text_of(instruction: &DrawInstruction) -> String {
+    instruction
+        .shape
+        .iter()
+        .map(|directed| match directed.1 {
+            Symbol::Text(c) => c,
+            _ => unreachable!("overlay should contain text only"),
+        })
+        .collect()
+}
+
+#[test]
+fn it_centers_the_box() {
+    let paint = overlay(
+        &Vec2 { x: 20, y: 10 },
+        &["abc".to_string(), "a".to_string()],
+    );
+
+    assert_eq!(paint.len(), 4);
+    assert_eq!(paint.first().unwrap().pos, Vec2 { x: 6, y: 3 });
+    assert_eq!(text_of(&paint[0]), "       ");
+    assert_eq!(text_of(&paint[1]), "  abc  ");
+    assert_eq!(text_of(&paint[2]), "   a   ");
+    assert_eq!(text_of(&paint[3]), "       ");
+}
+
+#[test]
+fn it_fits_into_small_areas() {
+    let paint = overlay(
+        &Vec2 { x: 4, y: 2 },
+        &["too long".to_string(), "text".to_string()],
+    );
+
+    assert_eq!(paint.len(), 2);
+    assert_eq!(paint.first().unwrap().pos, Vec2 { x: 0, y: 0 });
+    assert_eq!(text_of(&paint[1]), "too ");
+}
@@ -2,23 +2,127 @@ use super::game::{direction::Directed, direction::Direction, vec2::Vec2};
 use super::game::{
     draw_instruction::{DrawInstruction, Symbol},
     errors::{SelfEatingStepError, SelfTurningDirectionError},
-    traits::{Character, Draw, IsFreePos, Reset},
+    power_up::{Effects, PowerUp},
+    traits::{Character, Draw, IsFreePos, Reset, Snapshot},
 };
 use std::collections::LinkedList;
 
 pub struct Snake {
     body: LinkedList<Directed<Vec2>>,
     growing: bool,
+    /// Size of the map, stepping over an edge continues on the opposite one
+    boundary: Vec2,
+    /// Body of the snake at the beginning of every round
+    start: LinkedList<Directed<Vec2>>,
+    /// Index of the player controlling the snake
+    player: usize,
+    /// Power-ups active on the snake
+    effects: Effects,
 }
 
+/// Number of cells of a snake put on the map with `Snake::with_spawn`
+pub const SPAWN_LENGTH: usize = 4;
+
 impl Snake {
-    pub fn new() -> Snake {
+    pub fn new(boundary: Vec2) -> Snake {
+        Self::with_body(boundary, Self::get_new_body())
+    }
+
+    /// Creates a straight snake whose head is at the given position, heading to the given direction
+    pub fn with_spawn(boundary: Vec2, head: &Directed<Vec2>) -> Snake {
+        let body = Self::get_spawn_body(head).expect("The spawn position is too close to the edge");
+        Self::with_body(boundary, body)
+    }
+
+    fn with_body(boundary: Vec2, body: LinkedList<Directed<Vec2>>) -> Snake {
         Snake {
-            body: Self::get_new_body(),
+            body: body.clone(),
             growing: false,
+            boundary,
+            start: body,
+            player: 0,
+            effects: Effects::new(),
+        }
+    }
+
+    /// Makes the snake belong to the given player
+    pub fn with_player(self, player: usize) -> Snake {
+        Snake { player, ..self }
+    }
+
+    /// Returns the cells of a straight snake behind the given head, tail first
+    ///
+    /// Returns None if the body would reach over the top or left edge.
+    pub fn get_spawn_body(head: &Directed<Vec2>) -> Option<LinkedList<Directed<Vec2>>> {
+        let Directed(direction, pos) = head;
+        (0..SPAWN_LENGTH)
+            .rev()
+            .map(|distance| {
+                let pos = match direction {
+                    Direction::Up => Vec2 {
+                        x: pos.x,
+                        y: pos.y + distance,
+                    },
+                    Direction::Left => Vec2 {
+                        x: pos.x + distance,
+                        y: pos.y,
+                    },
+                    Direction::Right => Vec2 {
+                        x: pos.x.checked_sub(distance)?,
+                        y: pos.y,
+                    },
+                    Direction::Down => Vec2 {
+                        x: pos.x,
+                        y: pos.y.checked_sub(distance)?,
+                    },
+                };
+                Some(Directed(direction.clone(), pos))
+            })
+            .collect()
+    }
+
+    /// Returns the position right in front of the head
+    pub fn get_next_pos(&self) -> Vec2 {
+        self.get_next_step(self.get_head()).1
+    }
+
+    /// Returns the smallest map that holds the starting body inside the border walls
+    pub fn get_min_map_size() -> Vec2 {
+        let body = Self::get_new_body();
+        Vec2 {
+            x: body.iter().map(|part| part.1.x).max().unwrap_or(0) + 2,
+            y: body.iter().map(|part| part.1.y).max().unwrap_or(0) + 2,
         }
     }
 
+    /// Returns the smallest map where both snakes of a versus game fit
+    pub fn get_min_versus_map_size() -> Vec2 {
+        Vec2 {
+            x: 4 * SPAWN_LENGTH,
+            y: 8,
+        }
+    }
+
+    /// Returns the heads of the snakes of a versus game, they start on the opposite sides
+    pub fn get_versus_spawns(boundary: &Vec2) -> [Directed<Vec2>; 2] {
+        [
+            Directed(
+                Direction::Right,
+                Vec2 {
+                    x: SPAWN_LENGTH + 2,
+                    y: 2,
+                },
+            ),
+            Directed(
+                Direction::Left,
+                Vec2 {
+                    x: boundary.x - SPAWN_LENGTH - 3,
+                    y: boundary.y - 3,
+                },
+            ),
+        ]
+    }
+
     fn get_new_body() -> LinkedList<Directed<Vec2>> {
         linked_list! {
             Directed(Direction::Right, Vec2 { x: 3, y: 3 }),
@@ -29,9 +133,12 @@ impl Snake {
     }
 
     fn is_self_eating_step(&self, step: &Directed<Vec2>) -> bool {
-        self.body
-            .iter()
-            .any(|i| i.1.x == step.1.x && i.1.y == step.1.y)
+        // a ghost passes through its own body
+        !self.effects.is_active(PowerUp::Ghost)
+            && self
+                .body
+                .iter()
+                .any(|i| i.1.x == step.1.x && i.1.y == step.1.y)
     }
 
     fn get_head(&self) -> &Directed<Vec2> {
@@ -43,25 +150,30 @@ impl Snake {
     }
 
     fn get_next_step(&self, last: &Directed<Vec2>) -> Directed<Vec2> {
+        let Vec2 {
+            x: width,
+            y: height,
+        } = self.boundary;
+
         match last {
             Directed(Direction::Up, pos) => Directed(
                 Direction::Up,
                 Vec2 {
                     x: pos.x,
-                    y: pos.y - 1,
+                    y: (pos.y + height - 1) % height,
                 },
             ),
             Directed(Direction::Left, pos) => Directed(
                 Direction::Left,
                 Vec2 {
-                    x: pos.x - 1,
+                    x: (pos.x + width - 1) % width,
                     y: pos.y,
                 },
             ),
             Directed(Direction::Right, pos) => Directed(
                 Direction::Right,
                 Vec2 {
-                    x: pos.x + 1,
+                    x: (pos.x + 1) % width,
                     y: pos.y,
                 },
             ),
@@ -69,7 +181,7 @@ impl Snake {
                 Direction::Down,
                 Vec2 {
                     x: pos.x,
-                    y: pos.y + 1,
+                    y: (pos.y + 1) % height,
                 },
             ),
         }
@@ -102,17 +214,41 @@ impl Character for Snake {
         }
     }
 
+    fn teleport(&mut self, pos: Vec2) -> Result<(), SelfEatingStepError> {
+        let head = Directed(self.get_head().0.clone(), pos);
+        if self.is_self_eating_step(&head) {
+            return Err(SelfEatingStepError);
+        }
+        *self.get_head_mut() = head;
+        Ok(())
+    }
+
     fn grow(&mut self) {
         self.growing = true
     }
 
+    fn shrink(&mut self) {
+        if self.body.len() > 2 {
+            self.body.pop_front();
+        }
+    }
+
     fn turn(&mut self, direction: Direction) -> Result<(), SelfTurningDirectionError> {
         let next_step = {
             let head = self.get_head();
             let last_step = Directed(direction.clone(), head.1.clone());
             self.get_next_step(&last_step)
         };
-        match self.is_self_eating_step(&next_step) {
+        let is_blocked = match self.effects.is_active(PowerUp::Ghost) {
+            // a ghost may turn into its body, but not back onto its neck
+            true => self
+                .body
+                .iter()
+                .nth_back(1)
+                .is_some_and(|neck| neck.1 == next_step.1),
+            false => self.is_self_eating_step(&next_step),
+        };
+        match is_blocked {
             true => Err(SelfTurningDirectionError),
             false => {
                 self.get_head_mut().0 = direction;
@@ -121,19 +257,36 @@ impl Character for Snake {
         }
     }
 
+    fn get_length(&self) -> usize {
+        self.body.len()
+    }
+
     fn get_head_pos(&self) -> &Vec2 {
         &self.get_head().1
     }
 
+    fn get_body(&self) -> Vec<Vec2> {
+        self.body.iter().rev().map(|part| part.1.clone()).collect()
+    }
+
+    fn get_direction(&self) -> &Direction {
+        &self.get_head().0
+    }
+
+    fn get_effects(&self) -> &Effects {
+        &self.effects
+    }
+
+    fn get_effects_mut(&mut self) -> &mut Effects {
+        &mut self.effects
+    }
+
     fn as_draw(&self) -> &dyn Draw {
         self
     }
     fn as_is_free_pos(&self) -> &dyn IsFreePos {
         self
     }
-    fn as_reset(&self) -> &dyn Reset {
-        self
-    }
 }
 
 impl Draw for Snake {
@@ -143,11 +296,12 @@ impl Draw for Snake {
             .iter()
             .map(|part| DrawInstruction {
                 pos: part.1.clone(),
-                shape: vec![Directed(part.0.clone(), Symbol::SnakeBody)],
+                shape: vec![Directed(part.0.clone(), Symbol::SnakeBody(self.player))],
             })
             .collect();
 
-        paint.last_mut().expect("Empty snake").shape.first_mut().unwrap().1 = Symbol::SnakeHead;
+        let head = paint.last_mut().expect("Empty snake");
+        head.shape.first_mut().expect("Empty snake part").1 = Symbol::SnakeHead(self.player);
 
         paint
     }
@@ -159,10 +313,68 @@ impl IsFreePos for Snake {
     }
 }
 
+/// Whether the snake grows, its effects and its cells from the tail, separated by spaces
+///
+/// E.g. `0 ghost:12 3,3,right 4,3,right 5,3,down`
+impl Snapshot for Snake {
+    fn save_state(&self) -> String {
+        let body: Vec<String> = self
+            .body
+            .iter()
+            .map(|Directed(direction, pos)| format!("{},{},{}", pos.x, pos.y, direction.get_name()))
+            .collect();
+        format!(
+            "{} {} {}",
+            self.growing as u8,
+            self.effects.save_state(),
+            body.join(" ")
+        )
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        let mut words = state.split_whitespace();
+        let growing = match words.next()? {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+        let mut effects = Effects::new();
+        effects.load_state(words.next()?)?;
+        let body = words
+            .map(|part| {
+                let parts: Vec<&str> = part.split(',').collect();
+                match parts.as_slice() {
+                    [x, y, direction] => {
+                        let pos = Vec2 {
+                            x: x.parse().ok()?,
+                            y: y.parse().ok()?,
+                        };
+                        if pos.x >= self.boundary.x || pos.y >= self.boundary.y {
+                            return None;
+                        }
+                        Some(Directed(Direction::from_name(direction)?, pos))
+                    }
+                    _ => None,
+                }
+            })
+            .collect::<Option<LinkedList<_>>>()?;
+        // the snake keeps at least its head and the part behind it
+        if body.len() < 2 {
+            return None;
+        }
+
+        self.body = body;
+        self.growing = growing;
+        self.effects = effects;
+        Some(())
+    }
+}
+
 impl Reset for Snake {
     fn reset(&mut self) {
-        self.body = Self::get_new_body();
+        self.body = self.start.clone();
         self.growing = false;
+        self.effects = Effects::new();
     }
 }
 
@@ -10,6 +10,10 @@ fn it_can_get_head() {
             Directed(Direction::Down, Vec2 { x: 5, y: 4 }),
         },
         growing: false,
+        boundary: Vec2 { x: 10, y: 10 },
+        start: LinkedList::new(),
+        player: 0,
+        effects: Effects::new(),
     };
 
     assert_eq!(snake.get_head().1, Vec2 { x: 5, y: 4 });
@@ -25,6 +29,10 @@ fn it_can_get_head_pos() {
             Directed(Direction::Down, Vec2 { x: 5, y: 4 }),
         },
         growing: false,
+        boundary: Vec2 { x: 10, y: 10 },
+        start: LinkedList::new(),
+        player: 0,
+        effects: Effects::new(),
     };
 
     assert_eq!(snake.get_head_pos().x, 5);
@@ -41,6 +49,10 @@ fn it_can_detect_self_eating_step() {
             Directed(Direction::Down, Vec2 { x: 5, y: 4 }),
         },
         growing: false,
+        boundary: Vec2 { x: 10, y: 10 },
+        start: LinkedList::new(),
+        player: 0,
+        effects: Effects::new(),
     };
 
     assert!(snake.is_self_eating_step(&Directed(Direction::Down, Vec2 { x: 5, y: 3 })));
@@ -49,7 +61,7 @@ fn it_can_detect_self_eating_step() {
 
 #[test]
 fn it_can_step() {
-    let mut snake = Snake::new();
+    let mut snake = Snake::new(Vec2 { x: 10, y: 10 });
 
     let pos_initial = Vec2 {
         x: snake.get_head_pos().x,
@@ -78,27 +90,167 @@ fn it_can_draw_itself() {
             Directed(Direction::Down, Vec2 { x: 5, y: 4 }),
         },
         growing: false,
+        boundary: Vec2 { x: 10, y: 10 },
+        start: LinkedList::new(),
+        player: 0,
+        effects: Effects::new(),
     };
 
     assert_eq!(
         vec![
             DrawInstruction{
                 pos: Vec2 { x: 3, y: 3 }, 
-                shape: vec![Directed(Direction::Right, Symbol::SnakeBody)]
+                shape: vec![Directed(Direction::Right, Symbol::SnakeBody(0))]
             },
             DrawInstruction{
                 pos: Vec2 { x: 4, y: 3 }, 
-                shape: vec![Directed(Direction::Right, Symbol::SnakeBody)]
+                shape: vec![Directed(Direction::Right, Symbol::SnakeBody(0))]
             },
             DrawInstruction{
                 pos: Vec2 { x: 5, y: 3 }, 
-                shape: vec![Directed(Direction::Down, Symbol::SnakeBody)]
+                shape: vec![Directed(Direction::Down, Symbol::SnakeBody(0))]
             },
             DrawInstruction{
                 pos: Vec2 { x: 5, y: 4 }, 
-                shape: vec![Directed(Direction::Down, Symbol::SnakeHead)]
+                shape: vec![Directed(Direction::Down, Symbol::SnakeHead(0))]
             },
         ],
         snake.draw()
     )
 }
+
+#[test]
+fn it_knows_the_smallest_map_it_fits_in() {
+    assert_eq!(Snake::get_min_map_size(), Vec2 { x: 7, y: 6 });
+}
+
+/// A one-cell snake that keeps its tail on the next step
+fn new_lone_head(direction: Direction, pos: Vec2) -> Snake {
+    Snake {
+        body: linked_list! { Directed(direction, pos), },
+        growing: true,
+        boundary: Vec2 { x: 10, y: 8 },
+        start: LinkedList::new(),
+        player: 0,
+        effects: Effects::new(),
+    }
+}
+
+#[test]
+fn it_wraps_around_the_top_edge() {
+    let mut snake = new_lone_head(Direction::Up, Vec2 { x: 4, y: 0 });
+
+    snake.step().unwrap();
+
+    assert_eq!(snake.get_head_pos(), &Vec2 { x: 4, y: 7 });
+}
+
+#[test]
+fn it_wraps_around_the_bottom_edge() {
+    let mut snake = new_lone_head(Direction::Down, Vec2 { x: 4, y: 7 });
+
+    snake.step().unwrap();
+
+    assert_eq!(snake.get_head_pos(), &Vec2 { x: 4, y: 0 });
+}
+
+#[test]
+fn it_wraps_around_the_left_edge() {
+    let mut snake = new_lone_head(Direction::Left, Vec2 { x: 0, y: 3 });
+
+    snake.step().unwrap();
+
+    assert_eq!(snake.get_head_pos(), &Vec2 { x: 9, y: 3 });
+}
+
+#[test]
+fn it_wraps_around_the_right_edge() {
+    let mut snake = new_lone_head(Direction::Right, Vec2 { x: 9, y: 3 });
+
+    snake.step().unwrap();
+
+    assert_eq!(snake.get_head_pos(), &Vec2 { x: 0, y: 3 });
+}
+
+#[test]
+fn it_can_spawn_behind_the_given_head() {
+    let snake = Snake::with_spawn(
+        Vec2 { x: 10, y: 10 },
+        &Directed(Direction::Up, Vec2 { x: 2, y: 5 }),
+    );
+
+    assert_eq!(snake.get_length(), SPAWN_LENGTH);
+    assert_eq!(snake.body.front().unwrap().1, Vec2 { x: 2, y: 8 });
+    assert_eq!(snake.get_head_pos(), &Vec2 { x: 2, y: 5 });
+    assert_eq!(snake.get_next_pos(), Vec2 { x: 2, y: 4 });
+}
+
+#[test]
+fn it_cannot_spawn_over_the_edge() {
+    let head = Directed(Direction::Right, Vec2 { x: 2, y: 5 });
+
+    assert_eq!(Snake::get_spawn_body(&head), None);
+}
+
+#[test]
+fn it_passes_through_its_body_as_a_ghost() {
+    // GIVEN
+    // the head at (5, 4) turns left and up into its own body
+    let mut snake = Snake::new(Vec2 { x: 10, y: 10 });
+    snake.grow();
+    snake.step().unwrap();
+    snake.get_effects_mut().activate(PowerUp::Ghost);
+
+    // WHEN
+    snake.turn(Direction::Left).unwrap();
+    snake.step().unwrap();
+    let turned = snake.turn(Direction::Up);
+
+    // THEN
+    assert!(turned.is_ok());
+    assert!(snake.step().is_ok());
+    assert!(snake.turn(Direction::Down).is_err());
+}
+
+#[test]
+fn it_can_teleport_its_head() {
+    let mut snake = Snake::new(Vec2 { x: 10, y: 10 });
+
+    assert!(snake.teleport(Vec2 { x: 3, y: 3 }).is_err());
+    snake.teleport(Vec2 { x: 7, y: 7 }).unwrap();
+
+    assert_eq!(snake.get_head_pos(), &Vec2 { x: 7, y: 7 });
+    assert_eq!(snake.get_direction(), &Direction::Down);
+}
+
+#[test]
+fn it_can_save_and_restore_its_state() {
+    // GIVEN
+    let mut snake = Snake::new(Vec2 { x: 10, y: 10 });
+    snake.get_effects_mut().activate(PowerUp::Ghost);
+    snake.step().unwrap();
+    snake.grow();
+
+    // WHEN
+    let state = snake.save_state();
+    let mut restored = Snake::new(Vec2 { x: 10, y: 10 });
+    restored.load_state(&state).unwrap();
+
+    // THEN
+    assert_eq!(state, "1 ghost:30 4,3,right 5,3,down 5,4,down 5,5,down");
+    assert_eq!(restored.save_state(), state);
+    restored.step().unwrap();
+    assert_eq!(restored.get_length(), 5);
+}
+
+#[test]
+fn it_refuses_invalid_states() {
+    let mut snake = Snake::new(Vec2 { x: 10, y: 10 });
+    let state = snake.save_state();
+
+    assert_eq!(snake.load_state("2 - 3,3,right 4,3,right"), None);
+    assert_eq!(snake.load_state("0 - 3,3,right"), None);
+    assert_eq!(snake.load_state("0 - 3,3,right 4,3,north"), None);
+    assert_eq!(snake.load_state("0 - 3,3,right 10,3,right"), None);
+    assert_eq!(snake.save_state(), state);
+}
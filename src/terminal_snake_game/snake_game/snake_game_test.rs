@@ -0,0 +1,543 @@
+use super::*;
+use game::direction::Direction;
+use game::errors::GameError;
+use game::traits::{Food, IsFreePos, Snapshot};
+use game::vec2::Vec2;
+use rand::{RngCore, SeedableRng};
+use speed::Difficulty;
+
+/// Food that stays where it is put
+struct FixedFood(Vec2);
+
+impl Food for FixedFood {
+    fn spawn(&mut self, _: &Vec2, _: &dyn IsFreePos, _: &mut dyn RngCore) -> bool {
+        true
+    }
+
+    fn get_pos(&self) -> &Vec2 {
+        &self.0
+    }
+
+    fn move_to(&mut self, pos: Vec2) {
+        self.0 = pos;
+    }
+
+    fn as_draw(&self) -> &dyn Draw {
+        self
+    }
+}
+
+impl Snapshot for FixedFood {
+    fn save_state(&self) -> String {
+        format!("{} {}", self.0.x, self.0.y)
+    }
+
+    fn load_state(&mut self, state: &str) -> Option<()> {
+        let words: Vec<&str> = state.split_whitespace().collect();
+        match words.as_slice() {
+            [x, y] => {
+                self.0 = Vec2 {
+                    x: x.parse().ok()?,
+                    y: y.parse().ok()?,
+                }
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+impl Draw for FixedFood {
+    fn draw(&self) -> Vec<DrawInstruction> {
+        vec![]
+    }
+}
+
+fn new_snake_game() -> SnakeGame {
+    // the snake heads down from (5, 4) so it hits the wall on the fifth tick
+    SnakeGame::from_game(Box::new(game::Game::new(
+        Box::new(map::Map::new(10, 10)),
+        vec![Box::new(snake::Snake::new(Vec2 { x: 10, y: 10 }))],
+        vec![Box::new(FixedFood(Vec2 { x: 1, y: 1 }))],
+        Box::new(HighScores::new()),
+        game::GameRng::seed_from_u64(1),
+    )))
+}
+
+fn tick(snake_game: &mut SnakeGame, times: usize) {
+    for _ in 0..times {
+        snake_game.update(UpdateReason::Time);
+    }
+}
+
+#[test]
+fn it_waits_on_the_title_screen() {
+    let mut snake_game = new_snake_game();
+
+    tick(&mut snake_game, 10);
+
+    assert!(matches!(snake_game.state, State::Title));
+    assert_eq!(snake_game.get_score().ticks, 0);
+}
+
+#[test]
+fn it_can_start_playing() {
+    let mut snake_game = new_snake_game();
+
+    snake_game.update(UpdateReason::Control(Control::Start));
+    tick(&mut snake_game, 2);
+
+    assert!(matches!(snake_game.state, State::Playing));
+    assert_eq!(snake_game.get_score().ticks, 2);
+}
+
+#[test]
+fn it_freezes_the_board_on_death() {
+    // GIVEN
+    let mut snake_game = new_snake_game();
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    // WHEN
+    tick(&mut snake_game, 8);
+
+    // THEN
+    assert!(matches!(
+        snake_game.state,
+        State::GameOver(GameError::KilledByWall)
+    ));
+    assert_eq!(snake_game.get_score().ticks, 5);
+}
+
+#[test]
+fn it_shows_the_reason_of_death() {
+    let mut snake_game = new_snake_game();
+    snake_game.update(UpdateReason::Control(Control::Start));
+    tick(&mut snake_game, 5);
+
+    assert!(snake_game
+        .get_overlay_lines()
+        .contains(&GameError::KilledByWall.to_string()));
+}
+
+#[test]
+fn it_can_restart_after_death() {
+    let mut snake_game = new_snake_game();
+    snake_game.update(UpdateReason::Control(Control::Start));
+    tick(&mut snake_game, 5);
+
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    assert!(matches!(snake_game.state, State::Playing));
+    assert_eq!(snake_game.get_score().ticks, 0);
+}
+
+#[test]
+fn it_ignores_time_and_turns_while_paused() {
+    let mut snake_game = new_snake_game();
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    snake_game.update(UpdateReason::Control(Control::Pause));
+    snake_game.update(UpdateReason::Control(Control::Turn(0, Direction::Left)));
+    tick(&mut snake_game, 10);
+
+    assert!(matches!(snake_game.state, State::Paused));
+    assert_eq!(snake_game.get_score().ticks, 0);
+
+    snake_game.update(UpdateReason::Control(Control::Resume));
+    assert!(matches!(snake_game.state, State::Playing));
+}
+
+#[test]
+fn it_can_pause_only_a_running_round() {
+    let mut snake_game = new_snake_game();
+
+    snake_game.update(UpdateReason::Control(Control::Pause));
+    assert!(!snake_game.is_paused());
+
+    snake_game.update(UpdateReason::Control(Control::Start));
+    snake_game.update(UpdateReason::Control(Control::Pause));
+    snake_game.update(UpdateReason::Control(Control::Pause));
+    assert!(snake_game.is_paused());
+
+    snake_game.update(UpdateReason::Control(Control::Resume));
+    snake_game.update(UpdateReason::Control(Control::Resume));
+    assert!(!snake_game.is_paused());
+}
+
+#[test]
+fn it_draws_an_overlay_while_paused() {
+    let mut snake_game = new_snake_game();
+    snake_game.update(UpdateReason::Control(Control::Start));
+    let playing = snake_game.draw();
+
+    snake_game.update(UpdateReason::Control(Control::Pause));
+
+    assert!(snake_game
+        .get_overlay_lines()
+        .contains(&"PAUSED".to_string()));
+    assert!(snake_game.draw().len() > playing.len());
+}
+
+#[test]
+fn it_can_quit() {
+    let mut snake_game = new_snake_game();
+
+    snake_game.update(UpdateReason::Control(Control::Quit));
+
+    assert!(snake_game.should_quit());
+}
+
+#[test]
+fn it_can_change_the_difficulty_between_rounds() {
+    let mut snake_game = new_snake_game();
+
+    snake_game.update(UpdateReason::Control(Control::SetDifficulty(
+        Difficulty::Hard,
+    )));
+    assert_eq!(
+        snake_game.get_tick_interval(),
+        Difficulty::Hard.get_interval()
+    );
+
+    snake_game.update(UpdateReason::Control(Control::Start));
+    snake_game.update(UpdateReason::Control(Control::SetDifficulty(
+        Difficulty::Easy,
+    )));
+    assert_eq!(
+        snake_game.get_tick_interval(),
+        Difficulty::Hard.get_interval()
+    );
+}
+
+#[test]
+fn it_speeds_up_after_eating_when_accelerating() {
+    // GIVEN
+    let mut snake_game = SnakeGame::from_game(Box::new(game::Game::new(
+        Box::new(map::Map::new(10, 10)),
+        vec![Box::new(snake::Snake::new(Vec2 { x: 10, y: 10 }))],
+        vec![Box::new(FixedFood(Vec2 { x: 5, y: 5 }))],
+        Box::new(HighScores::new()),
+        game::GameRng::seed_from_u64(1),
+    )));
+    snake_game.update(UpdateReason::Control(Control::ToggleAcceleration));
+    snake_game.update(UpdateReason::Control(Control::Start));
+    let initial = snake_game.get_tick_interval();
+
+    // WHEN
+    tick(&mut snake_game, 2);
+
+    // THEN
+    assert_eq!(snake_game.get_score().length, 5);
+    assert!(snake_game.get_tick_interval() < initial);
+}
+
+#[test]
+fn it_keeps_the_pace_without_acceleration() {
+    let mut snake_game = new_snake_game();
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    tick(&mut snake_game, 3);
+
+    assert_eq!(
+        snake_game.get_tick_interval(),
+        Difficulty::Normal.get_interval()
+    );
+}
+
+#[test]
+fn it_plays_the_same_game_with_the_same_seed_and_input() {
+    let inputs = || {
+        vec![
+            UpdateReason::Control(Control::Start),
+            UpdateReason::Time,
+            UpdateReason::Control(Control::Turn(0, Direction::Right)),
+            UpdateReason::Time,
+            UpdateReason::Time,
+            UpdateReason::Control(Control::Turn(0, Direction::Down)),
+            UpdateReason::Time,
+        ]
+    };
+    let config = Config {
+        seed: 1234,
+        ..Config::new(30, 20)
+    };
+    let mut one = SnakeGame::new_unranked(&config);
+    let mut two = SnakeGame::new_unranked(&config);
+
+    for (a, b) in inputs().into_iter().zip(inputs()) {
+        one.update(a);
+        two.update(b);
+        assert_eq!(one.draw(), two.draw());
+    }
+}
+
+#[test]
+fn it_moves_on_to_the_next_level_of_the_campaign() {
+    // GIVEN
+    let one = Level::parse(&format!(
+        "name: One\nspawn: 5 3 right\napples: 1\n---\n{}",
+        "##########\n".to_string() + &"#........#\n".repeat(8) + "##########\n"
+    ))
+    .unwrap();
+    let two = Level::parse(&format!(
+        "name: Two\nspawn: 5 3 down\napples: 1\n---\n{}",
+        "............\n".repeat(8)
+    ))
+    .unwrap();
+    let mut snake_game = SnakeGame::from_game(Box::new(game::Game::new(
+        Box::new(map::Map::new(10, 10)),
+        vec![Box::new(snake::Snake::new(Vec2 { x: 10, y: 10 }))],
+        vec![Box::new(FixedFood(Vec2 { x: 5, y: 5 }))],
+        Box::new(HighScores::new()),
+        game::GameRng::seed_from_u64(1),
+    )));
+    snake_game.campaign = Some(Campaign::with_levels(vec![one, two], 0, Progress::new()));
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    // WHEN
+    tick(&mut snake_game, 2);
+
+    // THEN
+    assert!(matches!(snake_game.state, State::LevelIntro));
+    assert_eq!(snake_game.game.get_size(), Vec2 { x: 12, y: 8 });
+    assert_eq!(snake_game.get_score().apples, 1);
+    let lines = snake_game.get_overlay_lines();
+    assert!(lines.contains(&"Level 2/2: Two".to_string()));
+    assert!(lines.contains(&"Eat 1 apples".to_string()));
+
+    // the second level starts right above the food
+    snake_game.update(UpdateReason::Control(Control::Start));
+    tick(&mut snake_game, 3);
+
+    assert!(matches!(snake_game.state, State::Completed));
+    assert_eq!(snake_game.get_score().apples, 2);
+}
+
+#[test]
+fn it_starts_the_score_over_when_a_level_of_the_campaign_is_lost() {
+    // GIVEN
+    let walls = "##########\n".to_string() + &"#........#\n".repeat(8) + "##########\n";
+    let one = Level::parse(&format!(
+        "name: One\nspawn: 5 3 right\napples: 1\n---\n{}",
+        walls
+    ))
+    .unwrap();
+    let two = Level::parse(&format!(
+        "name: Two\nspawn: 3 7 left\napples: 5\n---\n{}",
+        walls
+    ))
+    .unwrap();
+    let mut snake_game = SnakeGame::from_game(Box::new(game::Game::new(
+        Box::new(map::Map::new(10, 10)),
+        vec![Box::new(snake::Snake::new(Vec2 { x: 10, y: 10 }))],
+        vec![Box::new(FixedFood(Vec2 { x: 5, y: 5 }))],
+        Box::new(HighScores::new()),
+        game::GameRng::seed_from_u64(1),
+    )));
+    snake_game.campaign = Some(Campaign::with_levels(vec![one, two], 0, Progress::new()));
+    snake_game.update(UpdateReason::Control(Control::Start));
+    tick(&mut snake_game, 2);
+    snake_game.update(UpdateReason::Control(Control::Start));
+    assert!(snake_game.get_score().points > 0);
+
+    // the second level starts next to the left wall
+    tick(&mut snake_game, 3);
+    assert!(matches!(snake_game.state, State::GameOver(_)));
+
+    // WHEN
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    // THEN
+    assert!(matches!(snake_game.state, State::Playing));
+    assert_eq!(snake_game.campaign.as_ref().unwrap().get_level_number(), 2);
+    assert_eq!(snake_game.get_score().points, 0);
+    assert_eq!(snake_game.get_score().apples, 0);
+}
+
+fn lose_versus_round(snake_game: &mut SnakeGame) {
+    // the second snake starts on the row above the bottom wall
+    snake_game.update(UpdateReason::Control(Control::Start));
+    snake_game.update(UpdateReason::Control(Control::Turn(1, Direction::Down)));
+    tick(snake_game, 2);
+}
+
+#[test]
+fn it_credits_the_survivor_of_a_versus_round() {
+    let mut snake_game = SnakeGame::new_unranked(&Config {
+        versus: true,
+        ..Config::new(20, 10)
+    });
+
+    lose_versus_round(&mut snake_game);
+
+    assert!(matches!(
+        &snake_game.state,
+        State::GameOver(GameError::PlayersLost(losers)) if *losers == vec![(1, GameError::KilledByWall)]
+    ));
+    assert_eq!(snake_game.wins, vec![1, 0]);
+    assert!(snake_game
+        .get_overlay_lines()
+        .contains(&"Player 1 wins the round".to_string()));
+}
+
+#[test]
+fn it_starts_a_new_match_after_the_last_round() {
+    let mut snake_game = SnakeGame::new_unranked(&Config {
+        versus: true,
+        ..Config::new(20, 10)
+    });
+    for _ in 0..MATCH_WINS {
+        lose_versus_round(&mut snake_game);
+    }
+    assert!(snake_game
+        .get_overlay_lines()
+        .contains(&"MATCH OVER".to_string()));
+
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    assert!(matches!(snake_game.state, State::Playing));
+    assert_eq!(snake_game.wins, vec![0, 0]);
+}
+
+#[test]
+fn it_lets_the_computer_play_the_opponent() {
+    let mut snake_game = SnakeGame::new_unranked(&Config {
+        versus: true,
+        opponent: Some(Strategy::Bfs),
+        seed: 3,
+        ..Config::new(20, 10)
+    });
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    // the first player runs into the right wall, the computer steers clear of it
+    tick(&mut snake_game, 13);
+
+    assert!(matches!(
+        &snake_game.state,
+        State::GameOver(GameError::PlayersLost(losers)) if *losers == vec![(0, GameError::KilledByWall)]
+    ));
+}
+
+/// Goes straight on and complains about it
+struct Sleepy;
+
+impl Controller for Sleepy {
+    fn decide(&mut self, board: &ai::BoardView) -> Direction {
+        board.head.0.clone()
+    }
+
+    fn get_problem(&self) -> Option<&str> {
+        Some("asleep")
+    }
+}
+
+#[test]
+fn it_ignores_the_keys_of_a_player_steered_by_the_computer() {
+    let mut snake_game = new_snake_game().with_controller(0, Box::new(Sleepy));
+    snake_game.update(UpdateReason::Control(Control::Start));
+
+    // turning right would hit the wall a tick earlier
+    snake_game.update(UpdateReason::Control(Control::Turn(0, Direction::Right)));
+    tick(&mut snake_game, 4);
+    assert!(snake_game.is_running());
+    tick(&mut snake_game, 1);
+
+    assert!(!snake_game.is_running());
+    assert!(snake_game
+        .get_overlay_lines()
+        .contains(&"Player 1: asleep".to_string()));
+}
+
+#[test]
+fn it_plays_a_demo_on_the_title_screen() {
+    // GIVEN
+    let mut snake_game = SnakeGame::new_unranked(&Config {
+        demo: Some(Strategy::Hamiltonian),
+        ..Config::new(12, 8)
+    });
+
+    // WHEN
+    tick(&mut snake_game, 300);
+
+    // THEN
+    let demo = snake_game.demo.as_ref().unwrap();
+    assert_eq!(demo.get_score().ticks, 300);
+    assert!(demo.get_score().apples > 0);
+    assert_eq!(snake_game.get_score().ticks, 0);
+}
+
+#[test]
+fn it_restores_a_saved_game_paused() {
+    // GIVEN
+    let config = Config {
+        versus: true,
+        accelerate: true,
+        seed: 5,
+        food: game::food::FoodWeights::parse("apple=3,golden=1,poison=1").unwrap(),
+        food_count: 4,
+        ..Config::new(20, 10)
+    };
+    let mut snake_game = SnakeGame::new_unranked(&config);
+    lose_versus_round(&mut snake_game);
+    snake_game.update(UpdateReason::Control(Control::SetDifficulty(
+        Difficulty::Hard,
+    )));
+    snake_game.update(UpdateReason::Control(Control::Start));
+    tick(&mut snake_game, 3);
+    snake_game.update(UpdateReason::Control(Control::Pause));
+
+    // WHEN
+    let mut restored = SnakeGame::new_unranked(&config);
+    restored.load_state(&snake_game.save_state()).unwrap();
+
+    // THEN
+    assert!(matches!(restored.state, State::Paused));
+    assert_eq!(restored.wins, vec![1, 0]);
+    assert_eq!(restored.get_tick_interval(), snake_game.get_tick_interval());
+    assert_eq!(restored.draw(), snake_game.draw());
+    restored.update(UpdateReason::Control(Control::Resume));
+    snake_game.update(UpdateReason::Control(Control::Resume));
+    for _ in 0..4 {
+        restored.update(UpdateReason::Time);
+        snake_game.update(UpdateReason::Time);
+        assert_eq!(restored.draw(), snake_game.draw());
+    }
+}
+
+#[test]
+fn it_restores_the_level_of_the_campaign() {
+    let config = Config::with_campaign(0);
+    let mut snake_game = SnakeGame::new_unranked(&config);
+    snake_game.update(UpdateReason::Control(Control::Start));
+    let level = snake_game
+        .campaign
+        .as_mut()
+        .unwrap()
+        .resume(2, 10)
+        .unwrap()
+        .clone();
+    snake_game.enter_level(&level);
+    tick(&mut snake_game, 2);
+
+    let mut restored = SnakeGame::new_unranked(&config);
+    restored.load_state(&snake_game.save_state()).unwrap();
+
+    let campaign = restored.campaign.as_ref().unwrap();
+    assert_eq!(campaign.get_level_number(), 2);
+    assert_eq!(campaign.get_apples_before(), 10);
+    assert_eq!(restored.level_name, Some(level.name));
+    assert_eq!(restored.game.save_state(), snake_game.game.save_state());
+}
+
+#[test]
+fn it_refuses_states_that_dont_fit_the_settings() {
+    let versus = SnakeGame::new_unranked(&Config {
+        versus: true,
+        ..Config::new(20, 10)
+    });
+    let mut snake_game = SnakeGame::new_unranked(&Config::new(20, 10));
+
+    assert_eq!(snake_game.load_state(&versus.save_state()), None);
+    assert_eq!(snake_game.load_state("wins 0"), None);
+    assert!(matches!(snake_game.state, State::Title));
+}
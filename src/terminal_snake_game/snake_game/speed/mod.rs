@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+/// Presets of the game's pace
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Insane];
+
+    /// Returns the preset with the given name
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|difficulty| difficulty.get_name() == name)
+    }
+
+    /// Returns the time between two steps at the beginning of a round
+    pub fn get_interval(self) -> Duration {
+        Duration::from_millis(match self {
+            Self::Easy => 150,
+            Self::Normal => 100,
+            Self::Hard => 70,
+            Self::Insane => 40,
+        })
+    }
+
+    /// Returns the shortest time between two steps the acceleration can reach
+    pub fn get_min_interval(self) -> Duration {
+        Duration::from_millis(match self {
+            Self::Easy => 70,
+            Self::Normal => 50,
+            Self::Hard => 35,
+            Self::Insane => 20,
+        })
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Insane => "insane",
+        }
+    }
+}
+
+/// Speeds the game up as the character grows
+#[derive(Clone, Debug, PartialEq)]
+pub struct Acceleration {
+    /// Multiplier of the interval for every cell the character has grown
+    pub factor: f64,
+    /// The interval never gets shorter than this
+    pub min_interval: Duration,
+}
+
+/// Pace of the game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Speed {
+    /// Time between two steps at the beginning of a round
+    pub interval: Duration,
+    /// Optional speed-up, the pace is constant without it
+    pub acceleration: Option<Acceleration>,
+}
+
+impl Speed {
+    /// Every apple makes the game 3% faster when accelerating
+    const ACCELERATION_FACTOR: f64 = 0.97;
+
+    pub fn new(difficulty: Difficulty, accelerate: bool) -> Speed {
+        Speed {
+            interval: difficulty.get_interval(),
+            acceleration: match accelerate {
+                true => Some(Acceleration {
+                    factor: Self::ACCELERATION_FACTOR,
+                    min_interval: difficulty.get_min_interval(),
+                }),
+                false => None,
+            },
+        }
+    }
+
+    /// Returns the time between two steps after the character has grown with the given cells
+    pub fn get_interval(&self, growth: usize) -> Duration {
+        match &self.acceleration {
+            None => self.interval,
+            Some(acceleration) => {
+                let exponent = growth.min(i32::MAX as usize) as i32;
+                self.interval
+                    .mul_f64(acceleration.factor.powi(exponent))
+                    .max(acceleration.min_interval)
+            }
+        }
+    }
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Speed::new(Difficulty::Normal, false)
+    }
+}
+
+#[cfg(test)]
+mod speed_test;
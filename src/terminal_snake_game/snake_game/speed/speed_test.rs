@@ -0,0 +1,51 @@
+use super::*;
+
+#[test]
+fn it_keeps_the_pace_without_acceleration() {
+    let speed = Speed::new(Difficulty::Normal, false);
+
+    assert_eq!(speed.get_interval(0), Duration::from_millis(100));
+    assert_eq!(speed.get_interval(50), Duration::from_millis(100));
+}
+
+#[test]
+fn it_makes_harder_presets_faster() {
+    for pair in Difficulty::ALL.windows(2) {
+        assert!(pair[0].get_interval() > pair[1].get_interval());
+        assert!(pair[0].get_min_interval() > pair[1].get_min_interval());
+    }
+}
+
+#[test]
+fn it_speeds_up_as_the_character_grows() {
+    let speed = Speed::new(Difficulty::Normal, true);
+
+    assert_eq!(speed.get_interval(0), Duration::from_millis(100));
+    assert!(speed.get_interval(1) < speed.get_interval(0));
+    assert!(speed.get_interval(10) < speed.get_interval(1));
+}
+
+#[test]
+fn it_does_not_go_below_the_minimum() {
+    let speed = Speed::new(Difficulty::Hard, true);
+
+    assert_eq!(
+        speed.get_interval(1000),
+        Difficulty::Hard.get_min_interval()
+    );
+    assert_eq!(
+        speed.get_interval(usize::MAX),
+        Difficulty::Hard.get_min_interval()
+    );
+}
+
+#[test]
+fn it_can_find_presets_by_name() {
+    for difficulty in Difficulty::ALL.iter() {
+        assert_eq!(
+            Difficulty::from_name(difficulty.get_name()),
+            Some(*difficulty)
+        );
+    }
+    assert_eq!(Difficulty::from_name("turbo"), None);
+}
@@ -0,0 +1,18 @@
+use super::game::errors::GameError;
+
+/// Phases of the game's lifecycle
+#[derive(Debug)]
+pub enum State {
+    /// Waiting for the player to start the first round
+    Title,
+    /// The round is running
+    Playing,
+    /// The round is frozen until the player continues it
+    Paused,
+    /// The round has ended with the given reason, waiting for a restart or quit
+    GameOver(GameError),
+    /// A level of the campaign is done, waiting for the player to start the next one
+    LevelIntro,
+    /// Every level of the campaign is done, waiting for a restart or quit
+    Completed,
+}
@@ -1,13 +1,44 @@
+use super::ai::BoardView;
 use super::game::direction::Direction;
-use super::game::traits::Draw;
+use super::game::errors::GameError;
+use super::game::food::Pace;
+use super::game::score::{HighScore, Score};
+use super::game::traits::{Character, Draw, Map, Reset, Snapshot};
+use super::game::vec2::Vec2;
 
 /// Methods that a game should be able to do
-pub trait Game: Draw {
+pub trait Game: Draw + Reset + Snapshot {
     /// This function is called when a unit time has spent
-    fn tick(&mut self);
+    ///
+    /// Returns the reason of the end of the round if it has ended.
+    fn tick(&mut self) -> Result<(), GameError>;
 
-    /// Turns the character
-    fn turn_character(&mut self, direction: Direction);
+    /// Turns the character of the given player
+    fn turn_character(&mut self, player: usize, direction: Direction);
+
+    /// Continues the round on another map with new characters, the scores are kept
+    fn change_map(&mut self, map: Box<dyn Map>, characters: Vec<Box<dyn Character>>);
+
+    /// Records the current result in the high-score table
+    fn submit_score(&mut self);
+
+    /// Returns the statistics of the current round of the first player
+    fn get_score(&self) -> &Score;
+
+    /// Returns the statistics of the current round of every player
+    fn get_scores(&self) -> &[Score];
+
+    /// Returns the persisted table of the best results
+    fn get_high_scores(&self) -> Vec<HighScore>;
+
+    /// Returns the size of the board
+    fn get_size(&self) -> Vec2;
+
+    /// Returns the change of the pace caused by the food eaten lately
+    fn get_pace(&self) -> Pace;
+
+    /// Returns what the given player can see of the board, for computer players
+    fn get_board_view(&self, player: usize) -> BoardView<'_>;
 
     // Casts
     fn as_draw(&self) -> &dyn Draw;
@@ -0,0 +1,51 @@
+use super::render::theme::{ColorSupport, Theme};
+use super::snake_game::ai::Strategy;
+use super::*;
+
+/// Starts a round whose first snake is steered by a computer player, the way a bot steers it
+fn game_of_bot() -> TerminalSnakeGame {
+    let config = Config {
+        seed: 7,
+        ..Config::new(30, 20)
+    };
+    let snake_game = snake_game::SnakeGame::new_unranked(&config)
+        .with_controller(0, Strategy::Bfs.new_controller());
+    let mut game = TerminalSnakeGame {
+        snake_game: Box::new(snake_game),
+        renderer: Renderer::new(Theme::default(), ColorSupport::None),
+        recording: None,
+        versus: false,
+        config,
+        last_save: None,
+        broadcaster: None,
+        bot: true,
+    };
+    game.update(UpdateReason::Control(Control::Start));
+    game.update(UpdateReason::Time);
+    game
+}
+
+#[test]
+fn it_doesnt_save_the_round_of_a_bot_on_quitting() {
+    // GIVEN a round played by a bot
+    let mut game = game_of_bot();
+    assert!(game.snake_game.is_running());
+
+    // WHEN it is left in the middle
+    game.save_on_quit();
+
+    // THEN nothing is saved
+    assert_eq!(game.last_save, None);
+}
+
+#[test]
+fn it_refuses_to_save_the_round_of_a_bot() {
+    // GIVEN a round played by a bot
+    let mut game = game_of_bot();
+
+    // WHEN it is saved on purpose
+    game.save();
+
+    // THEN the save is refused
+    assert_eq!(game.last_save, Some(Err(SaveError::BotGame)));
+}
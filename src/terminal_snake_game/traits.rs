@@ -1,22 +1,75 @@
-use super::snake_game::game::traits::Draw;
+use super::snake_game::game::score::{HighScore, Score};
+use super::snake_game::game::traits::{Draw, Snapshot};
 use super::update_reason::UpdateReason;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::Duration;
 use termion::event::Key;
 
 /// Methods that a game should be able to do
-pub trait SnakeGame: Draw {
+pub trait SnakeGame: Draw + Snapshot {
     /// This function is called when a relevant event is triggered
     fn update(&mut self, reason: UpdateReason);
 
+    /// Returns the seed of the random generator, it reproduces the game with the same input
+    fn get_seed(&self) -> u64;
+
+    /// Returns the time that should pass between two `UpdateReason::Time` updates
+    fn get_tick_interval(&self) -> Duration;
+
+    /// Returns true if a round is in progress, playing or paused
+    fn is_running(&self) -> bool;
+
+    /// Returns true if the round is frozen
+    fn is_paused(&self) -> bool;
+
+    /// Returns true if the player wants to leave the game
+    fn should_quit(&self) -> bool;
+
+    /// Returns the statistics of the current round
+    fn get_score(&self) -> &Score;
+
+    /// Returns the persisted table of the best results
+    fn get_high_scores(&self) -> Vec<HighScore>;
+
     // Casts
     fn as_draw(&self) -> &dyn Draw;
 }
 
+#[derive(Debug, PartialEq)]
 pub enum Event {
     Time,
     Key(Key),
+    /// The terminal window has lost the focus
+    FocusLost,
+    /// The process is asked to stop (SIGTSTP)
+    Suspend,
+    /// The process has been continued after a stop (SIGCONT)
+    Continue,
+    /// The terminal has been resized to the given columns and rows (SIGWINCH)
+    Resize(usize, usize),
 }
 
 pub trait EventStream {
-    fn start() -> Receiver<Event>;
+    /// Starts producing events, time events are sent with the given interval
+    fn start(tick_interval: TickInterval) -> Receiver<Event>;
+}
+
+/// Time between two time events, it can be changed while the event stream is running
+#[derive(Clone)]
+pub struct TickInterval(Arc<AtomicU64>);
+
+impl TickInterval {
+    pub fn new(interval: Duration) -> TickInterval {
+        TickInterval(Arc::new(AtomicU64::new(interval.as_micros() as u64)))
+    }
+
+    pub fn get(&self) -> Duration {
+        Duration::from_micros(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, interval: Duration) {
+        self.0.store(interval.as_micros() as u64, Ordering::Relaxed)
+    }
 }
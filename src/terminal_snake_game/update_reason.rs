@@ -1,9 +1,87 @@
 use super::snake_game::game::direction::Direction;
+use super::snake_game::speed::Difficulty;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum Control {
-    Turn(Direction),
+    /// Turns the snake of the given player
+    Turn(usize, Direction),
+    /// Starts a round from the title screen or a new one after the game is over
+    Start,
+    /// Freezes the running round
+    Pause,
+    /// Continues the frozen round
+    Resume,
+    /// Chooses the pace of the next round
+    SetDifficulty(Difficulty),
+    /// Turns on or off the speed-up of the next round
+    ToggleAcceleration,
+    Quit,
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum UpdateReason {
     Control(Control),
     Time,
 }
+
+/// Text form of an update reason, e.g. `turn left` or `difficulty hard`
+///
+/// Turns of the other players are numbered from 1, e.g. `turn 2 up`.
+impl Display for UpdateReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Time => write!(f, "time"),
+            Self::Control(Control::Turn(0, direction)) => {
+                write!(f, "turn {}", direction.get_name())
+            }
+            Self::Control(Control::Turn(player, direction)) => {
+                write!(f, "turn {} {}", player + 1, direction.get_name())
+            }
+            Self::Control(Control::Start) => write!(f, "start"),
+            Self::Control(Control::Pause) => write!(f, "pause"),
+            Self::Control(Control::Resume) => write!(f, "resume"),
+            Self::Control(Control::SetDifficulty(difficulty)) => {
+                write!(f, "difficulty {}", difficulty.get_name())
+            }
+            Self::Control(Control::ToggleAcceleration) => write!(f, "accelerate"),
+            Self::Control(Control::Quit) => write!(f, "quit"),
+        }
+    }
+}
+
+impl FromStr for UpdateReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let reason = match words.as_slice() {
+            ["time"] => Self::Time,
+            ["turn", name] => Self::Control(Control::Turn(0, parse_direction(name)?)),
+            ["turn", player, name] => {
+                let player: usize = player
+                    .parse()
+                    .ok()
+                    .filter(|player| *player > 0)
+                    .ok_or(format!("Unknown player: {}", player))?;
+                Self::Control(Control::Turn(player - 1, parse_direction(name)?))
+            }
+            ["start"] => Self::Control(Control::Start),
+            ["pause"] => Self::Control(Control::Pause),
+            ["resume"] => Self::Control(Control::Resume),
+            ["difficulty", name] => Self::Control(Control::SetDifficulty(
+                Difficulty::from_name(name).ok_or(format!("Unknown difficulty: {}", name))?,
+            )),
+            ["accelerate"] => Self::Control(Control::ToggleAcceleration),
+            ["quit"] => Self::Control(Control::Quit),
+            _ => return Err(format!("Unknown update reason: {}", s)),
+        };
+        Ok(reason)
+    }
+}
+
+fn parse_direction(name: &str) -> Result<Direction, String> {
+    Direction::from_name(name).ok_or(format!("Unknown direction: {}", name))
+}